@@ -0,0 +1,302 @@
+/*
+ * Numrs/src/activations.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Activation functions
+   --------------------
+   The element-wise non-linearities of neural network layers, each with its derivative for the backward pass:
+
+       let hidden = activations::gelu(&pre_activation);
+       let gradient = upstream.zip_with(&activations::gelu_derivative(&pre_activation), |g, d| g * d);
+
+   The derivatives are taken with respect to the input, so they are called with the same Collective as the function.
+
+   - relu(x)             max(0, x)
+   - leaky_relu(x, a)    x for x > 0, a * x otherwise
+   - elu(x, a)           x for x > 0, a * (e^x - 1) otherwise
+   - gelu(x)             x * Phi(x), Phi is the standard normal CDF (the exact form, PyTorch's default)
+   - gelu_tanh(x)        0.5 * x * (1 + tanh(sqrt(2 / pi) * (x + 0.044715 * x^3))) (the approximation of GPT-2 and BERT)
+   - silu(x)             x * sigmoid(x), also known as Swish
+   - sigmoid(x)          1 / (1 + e^-x)
+   - tanh(x)
+   - softplus(x)         ln(1 + e^x)
+
+   Every function is computed in f64 (f32 and the half types are converted back after), in forms that do not overflow:
+   sigmoid never computes e^x for a large positive x, softplus is max(x, 0) + ln(1 + e^-|x|), and the functions that tend to
+   x or 0 (GELU, SiLU) return exactly that far out, so even +-infinity gives finite derivatives. NaN stays NaN.
+   The derivative of ReLU (and leaky ReLU) at 0 is taken to be that of the negative side, like PyTorch.
+
+   `Activation` names a function and its parameter, for code that picks the activation at run time (e.g. from a config).
+*/
+
+use super::{collective::Collective, num::Float};
+use std::f64::consts::{FRAC_2_SQRT_PI, FRAC_1_SQRT_2, PI};
+
+// Beyond this magnitude GELU and SiLU equal x (or 0) in f64, and their derivatives 1 (or 0)
+const SATURATION: f64 = 40.0;
+
+// Coefficient of the cubic term of the tanh approximation of GELU
+const GELU_TANH_COEFFICIENT: f64 = 0.044715;
+
+/*
+   Applies `f`, computed in f64, to every element.
+*/
+fn apply<E: Float>(x: &Collective<E>, f: impl Fn(f64) -> f64) -> Collective<E> {
+    x.map(|value| E::from_f64(f(value.to_f64())))
+}
+
+/// max(0, x) of every element.
+pub fn relu<E: Float>(x: &Collective<E>) -> Collective<E> {
+    leaky_relu(x, E::zero())
+}
+
+/// The derivative of ReLU, 1 for positive elements and 0 otherwise.
+pub fn relu_derivative<E: Float>(x: &Collective<E>) -> Collective<E> {
+    leaky_relu_derivative(x, E::zero())
+}
+
+/// `x` for positive elements and `negative_slope * x` otherwise.
+pub fn leaky_relu<E: Float>(x: &Collective<E>, negative_slope: E) -> Collective<E> {
+    let slope = negative_slope.to_f64();
+
+    // Without the zero slope case ReLU of -infinity would be 0 * -infinity = NaN
+    apply(x, |x| if x > 0.0 || x.is_nan() { x } else if slope == 0.0 { 0.0 } else { slope * x })
+}
+
+/// The derivative of leaky ReLU, 1 for positive elements and `negative_slope` otherwise.
+pub fn leaky_relu_derivative<E: Float>(x: &Collective<E>, negative_slope: E) -> Collective<E> {
+    let slope = negative_slope.to_f64();
+
+    apply(x, |x| if x.is_nan() { x } else if x > 0.0 { 1.0 } else { slope })
+}
+
+/// `x` for positive elements and `alpha * (e^x - 1)` otherwise.
+pub fn elu<E: Float>(x: &Collective<E>, alpha: E) -> Collective<E> {
+    let alpha = alpha.to_f64();
+
+    apply(x, |x| if x > 0.0 { x } else { alpha * x.exp_m1() })
+}
+
+/// The derivative of ELU, 1 for positive elements and `alpha * e^x` otherwise.
+pub fn elu_derivative<E: Float>(x: &Collective<E>, alpha: E) -> Collective<E> {
+    let alpha = alpha.to_f64();
+
+    apply(x, |x| if x > 0.0 { 1.0 } else { alpha * x.exp() })
+}
+
+/// The exact GELU, `x * Phi(x)` with the standard normal CDF Phi.
+pub fn gelu<E: Float>(x: &Collective<E>) -> Collective<E> {
+    apply(x, |x| saturate(x, |x| x * normal_cdf(x)))
+}
+
+/// The derivative of the exact GELU, `Phi(x) + x * phi(x)` with the standard normal density phi.
+pub fn gelu_derivative<E: Float>(x: &Collective<E>) -> Collective<E> {
+    apply(x, |x| saturate_derivative(x, |x| normal_cdf(x) + x * normal_pdf(x)))
+}
+
+/// The tanh approximation of GELU.
+pub fn gelu_tanh<E: Float>(x: &Collective<E>) -> Collective<E> {
+    apply(x, |x| saturate(x, |x| 0.5 * x * (1.0 + gelu_tanh_inner(x).tanh())))
+}
+
+/// The derivative of the tanh approximation of GELU.
+pub fn gelu_tanh_derivative<E: Float>(x: &Collective<E>) -> Collective<E> {
+    apply(x, |x| {
+        saturate_derivative(x, |x| {
+            let t = gelu_tanh_inner(x).tanh();
+            let inner_derivative = (2.0 / PI).sqrt() * (1.0 + 3.0 * GELU_TANH_COEFFICIENT * x * x);
+
+            0.5 * (1.0 + t) + 0.5 * x * (1.0 - t * t) * inner_derivative
+        })
+    })
+}
+
+/// `x * sigmoid(x)`, also known as Swish.
+pub fn silu<E: Float>(x: &Collective<E>) -> Collective<E> {
+    apply(x, |x| saturate(x, |x| x * sigmoid_f64(x)))
+}
+
+/// The derivative of SiLU, `sigmoid(x) * (1 + x * (1 - sigmoid(x)))`.
+pub fn silu_derivative<E: Float>(x: &Collective<E>) -> Collective<E> {
+    apply(x, |x| {
+        saturate_derivative(x, |x| {
+            let s = sigmoid_f64(x);
+
+            s * (1.0 + x * (1.0 - s))
+        })
+    })
+}
+
+/// `1 / (1 + e^-x)` of every element.
+pub fn sigmoid<E: Float>(x: &Collective<E>) -> Collective<E> {
+    apply(x, sigmoid_f64)
+}
+
+/// The derivative of sigmoid, `sigmoid(x) * (1 - sigmoid(x))`.
+pub fn sigmoid_derivative<E: Float>(x: &Collective<E>) -> Collective<E> {
+    apply(x, |x| {
+        let s = sigmoid_f64(x);
+
+        s * (1.0 - s)
+    })
+}
+
+/// The hyperbolic tangent of every element.
+pub fn tanh<E: Float>(x: &Collective<E>) -> Collective<E> {
+    apply(x, f64::tanh)
+}
+
+/// The derivative of tanh, `1 - tanh(x)^2`.
+pub fn tanh_derivative<E: Float>(x: &Collective<E>) -> Collective<E> {
+    apply(x, |x| {
+        let t = x.tanh();
+
+        1.0 - t * t
+    })
+}
+
+/// `ln(1 + e^x)` of every element.
+pub fn softplus<E: Float>(x: &Collective<E>) -> Collective<E> {
+    apply(x, |x| x.max(0.0) + (-x.abs()).exp().ln_1p())
+}
+
+/// The derivative of softplus, which is `sigmoid(x)`.
+pub fn softplus_derivative<E: Float>(x: &Collective<E>) -> Collective<E> {
+    sigmoid(x)
+}
+
+/*
+   An activation function with its parameter, for choosing one at run time.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Activation {
+    ReLU,
+    // Negative slope
+    LeakyReLU(f64),
+    // Alpha
+    ELU(f64),
+    GELU,
+    GELUTanh,
+    SiLU,
+    Sigmoid,
+    Tanh,
+    Softplus,
+}
+
+impl Activation {
+    /// Applies the activation to every element.
+    pub fn apply<E: Float>(&self, x: &Collective<E>) -> Collective<E> {
+        match *self {
+            Activation::ReLU => relu(x),
+            Activation::LeakyReLU(slope) => leaky_relu(x, E::from_f64(slope)),
+            Activation::ELU(alpha) => elu(x, E::from_f64(alpha)),
+            Activation::GELU => gelu(x),
+            Activation::GELUTanh => gelu_tanh(x),
+            Activation::SiLU => silu(x),
+            Activation::Sigmoid => sigmoid(x),
+            Activation::Tanh => tanh(x),
+            Activation::Softplus => softplus(x),
+        }
+    }
+
+    /// The derivative of the activation with respect to every element of its input.
+    pub fn derivative<E: Float>(&self, x: &Collective<E>) -> Collective<E> {
+        match *self {
+            Activation::ReLU => relu_derivative(x),
+            Activation::LeakyReLU(slope) => leaky_relu_derivative(x, E::from_f64(slope)),
+            Activation::ELU(alpha) => elu_derivative(x, E::from_f64(alpha)),
+            Activation::GELU => gelu_derivative(x),
+            Activation::GELUTanh => gelu_tanh_derivative(x),
+            Activation::SiLU => silu_derivative(x),
+            Activation::Sigmoid => sigmoid_derivative(x),
+            Activation::Tanh => tanh_derivative(x),
+            Activation::Softplus => softplus_derivative(x),
+        }
+    }
+}
+
+// Never computes e^x for a large positive x, so it cannot overflow
+pub(crate) fn sigmoid_f64(x: f64) -> f64 {
+    if x >= 0.0 {
+        1.0 / (1.0 + (-x).exp())
+    } else {
+        let e = x.exp();
+
+        e / (1.0 + e)
+    }
+}
+
+// For functions that tend to x on the right and 0 on the left
+fn saturate(x: f64, f: impl Fn(f64) -> f64) -> f64 {
+    if x > SATURATION {
+        x
+    } else if x < -SATURATION {
+        0.0
+    } else {
+        f(x)
+    }
+}
+
+// For their derivatives, which tend to 1 on the right and 0 on the left
+fn saturate_derivative(x: f64, f: impl Fn(f64) -> f64) -> f64 {
+    if x > SATURATION {
+        1.0
+    } else if x < -SATURATION {
+        0.0
+    } else {
+        f(x)
+    }
+}
+
+fn gelu_tanh_inner(x: f64) -> f64 {
+    (2.0 / PI).sqrt() * (x + GELU_TANH_COEFFICIENT * x * x * x)
+}
+
+// Standard normal CDF, through erfc so the left tail keeps its relative precision
+fn normal_cdf(x: f64) -> f64 {
+    0.5 * erfc(-x * FRAC_1_SQRT_2)
+}
+
+// Standard normal density
+fn normal_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() * FRAC_2_SQRT_PI * FRAC_1_SQRT_2 * 0.5
+}
+
+/*
+   The complementary error function, 1 - erf(x), to close to f64 precision.
+   Near zero erf(x) = 2 / sqrt(pi) * e^-x^2 * sum(2^n x^(2n+1) / (1 * 3 * ... * (2n+1))), a series of positive terms,
+   further out the continued fraction erfc(x) = e^-x^2 / sqrt(pi) / (x + 1/2 / (x + 1 / (x + 3/2 / (x + ...)))).
+*/
+pub(crate) fn erfc(x: f64) -> f64 {
+    if x.is_nan() {
+        return x;
+    }
+
+    if x < 0.0 {
+        return 2.0 - erfc(-x);
+    }
+
+    if x < 3.0 {
+        let mut term = x;
+        let mut sum = x;
+        let mut n = 0.0;
+
+        while term > sum * f64::EPSILON {
+            n += 1.0;
+            term *= 2.0 * x * x / (2.0 * n + 1.0);
+            sum += term;
+        }
+
+        return 1.0 - FRAC_2_SQRT_PI * (-x * x).exp() * sum;
+    }
+
+    // Evaluated bottom up, 60 terms are plenty for x >= 3
+    let mut fraction = x;
+    for k in (1..=60).rev() {
+        fraction = x + (k as f64 / 2.0) / fraction;
+    }
+
+    (-x * x).exp() / (PI.sqrt() * fraction)
+}
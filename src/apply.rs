@@ -0,0 +1,262 @@
+/*
+ * Numrs/src/apply.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Applying closures to Collectives
+   --------------------------------
+   The element-wise operations of this crate are all one loop over the data, these methods are that loop with the body
+   left to the caller, e.g. for a custom activation:
+
+       let activated = collective.map(|x| if x > 0.0 { x } else { 0.01 * x });  // a new Collective
+       collective.map_inplace(|x| *x = x.max(0.0));                              // in place
+       let pixels: Collective<u8> = mask.map(|x| if x { 255 } else { 0 });       // the element type may change
+
+   `zip_with` combines two Collectives element by element. Their shapes are broadcast the NumPy way: the extents are
+   aligned from the innermost axis, each pair must be equal or one of them 1, and an extent of 1 (or a missing axis)
+   is repeated to match the other:
+
+       // [batch, features] + [1, features] adds the bias to every sample
+       let biased = inputs.zip_with(&bias, |x, b| x + b);
+
+   `fold` and `reduce` combine the elements along an axis (0 is the outermost, the order of `Dimensions::to_vec()`),
+   the result has that axis removed. `None` combines all the elements into a 1 x 1 Collective. This numeric axis is the
+   convention of every n-dimensional API, `header::Axis` stays with the 2-D ones (see header.rs), `Axis::index()` converts:
+
+       let column_sums = matrix.fold(Some(0), 0.0, |sum, x| sum + x);
+       let maximum = matrix.reduce(None, |a, b| if b > a { b } else { a });
+
+   Unallocated Collectives map to unallocated Collectives.
+*/
+
+use super::{
+    collective::{dimensions_from_extents, strides, Collective},
+    dimensions::Dimensions,
+};
+
+impl<E> Collective<E>
+where
+    E: Copy,
+{
+    /// Applies `f` to every element and returns the results as a new Collective of the same shape.
+    pub fn map<T, F>(&self, f: F) -> Collective<T>
+    where
+        F: FnMut(E) -> T,
+    {
+        Collective {
+            data: self
+                .data
+                .as_ref()
+                .map(|data| data.iter().copied().map(f).collect::<Vec<T>>().into_boxed_slice()),
+            shape: self.shape.clone(),
+        }
+    }
+
+    /// Applies `f` to a mutable reference of every element.
+    pub fn map_inplace<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut E),
+    {
+        self.iter_mut().for_each(f);
+    }
+
+    /// Combines the elements of `self` and `other` with `f`, broadcasting their shapes against each other.
+    ///
+    /// # Returns
+    /// A new Collective of the broadcast shape. If either Collective has no data allocated, neither has the result.
+    ///
+    /// # Panics
+    /// If the shapes cannot be broadcast together.
+    pub fn zip_with<T, R, F>(&self, other: &Collective<T>, mut f: F) -> Collective<R>
+    where
+        T: Copy,
+        F: FnMut(E, T) -> R,
+    {
+        let (left, right) = match (&self.data, &other.data) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return Collective { data: None, shape: None },
+        };
+
+        let (left_extents, right_extents) = (self.extents(), other.extents());
+
+        let extents = match broadcast_extents(&left_extents, &right_extents) {
+            Some(extents) => extents,
+            None => panic!(
+                "Collective::zip_with(): Shapes {:?} and {:?} cannot be broadcast together",
+                left_extents, right_extents
+            ),
+        };
+
+        // Same shape, no index arithmetic needed
+        if left_extents == right_extents {
+            return Collective {
+                data: Some(left.iter().zip(right.iter()).map(|(&a, &b)| f(a, b)).collect::<Vec<R>>().into_boxed_slice()),
+                shape: self.shape.clone(),
+            };
+        }
+
+        let left_strides = broadcast_strides(&left_extents, &extents);
+        let right_strides = broadcast_strides(&right_extents, &extents);
+
+        let n: usize = extents.iter().product();
+        let mut data = Vec::with_capacity(n);
+        let mut coordinates = vec![0; extents.len()];
+        let (mut left_offset, mut right_offset) = (0, 0);
+
+        for _ in 0..n {
+            data.push(f(left[left_offset], right[right_offset]));
+
+            // Advance like an odometer, keeping both offsets in step with the coordinates
+            for axis in (0..extents.len()).rev() {
+                coordinates[axis] += 1;
+                left_offset += left_strides[axis];
+                right_offset += right_strides[axis];
+
+                if coordinates[axis] < extents[axis] {
+                    break;
+                }
+
+                coordinates[axis] = 0;
+                left_offset -= left_strides[axis] * extents[axis];
+                right_offset -= right_strides[axis] * extents[axis];
+            }
+        }
+
+        Collective {
+            data: Some(data.into_boxed_slice()),
+            shape: Some(Box::new(dimensions_from_extents(&extents))),
+        }
+    }
+
+    /// Folds the elements along `axis` (or all of them for `None`) into an accumulator starting from `init`.
+    ///
+    /// # Returns
+    /// A new Collective with `axis` removed, or of shape 1 x 1 for `None`. If the Collective has no data allocated, neither has the result.
+    ///
+    /// # Panics
+    /// If `axis` is not an axis of the Collective.
+    pub fn fold<A, F>(&self, axis: Option<usize>, init: A, mut f: F) -> Collective<A>
+    where
+        A: Clone,
+        F: FnMut(A, E) -> A,
+    {
+        self.lanes("fold", axis, |lane| lane.fold(init.clone(), &mut f))
+    }
+
+    /// Combines the elements along `axis` (or all of them for `None`) pairwise with `f`, starting from the first.
+    ///
+    /// # Returns
+    /// A new Collective with `axis` removed, or of shape 1 x 1 for `None`. If the Collective has no data allocated, neither has the result.
+    ///
+    /// # Panics
+    /// If `axis` is not an axis of the Collective.
+    pub fn reduce<F>(&self, axis: Option<usize>, mut f: F) -> Collective<E>
+    where
+        F: FnMut(E, E) -> E,
+    {
+        // Lanes are never empty, a Collective holding data has no zero extents
+        self.lanes("reduce", axis, |lane| lane.reduce(&mut f).unwrap())
+    }
+
+    /*
+       Calls `combine` with an iterator over every lane along `axis`, the elements whose coordinates differ only along that axis,
+       and collects the results in row-major order of the remaining axes. `method` names the caller in panic messages.
+    */
+    pub(crate) fn lanes<A, C>(&self, method: &str, axis: Option<usize>, mut combine: C) -> Collective<A>
+    where
+        C: FnMut(&mut dyn Iterator<Item = E>) -> A,
+    {
+        let data = match &self.data {
+            Some(data) if !data.is_empty() => data,
+            _ => return Collective { data: None, shape: None },
+        };
+
+        let axis = match axis {
+            Some(axis) => axis,
+            None => {
+                return Collective {
+                    data: Some(vec![combine(&mut data.iter().copied())].into_boxed_slice()),
+                    shape: Some(Box::new(Dimensions::new(1.0, 1.0))),
+                };
+            }
+        };
+
+        let extents = self.extents();
+
+        if axis >= extents.len() {
+            panic!(
+                "Collective::{}(): Axis {} is out of range for a Collective of {} axes",
+                method,
+                axis,
+                extents.len()
+            );
+        }
+
+        let inner = strides(&extents)[axis];
+        let outer: usize = extents[..axis].iter().product();
+        let length = extents[axis];
+
+        let mut result = Vec::with_capacity(outer * inner);
+        for block in 0..outer {
+            for offset in 0..inner {
+                let start = block * length * inner + offset;
+                result.push(combine(&mut (0..length).map(|index| data[start + index * inner])));
+            }
+        }
+
+        let remaining: Vec<usize> = extents
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| index != axis)
+            .map(|(_, &extent)| extent)
+            .collect();
+
+        Collective {
+            data: Some(result.into_boxed_slice()),
+            shape: Some(Box::new(dimensions_from_extents(&remaining))),
+        }
+    }
+}
+
+/*
+   The shape two shapes broadcast to, None if they are incompatible.
+*/
+pub(crate) fn broadcast_extents(left: &[usize], right: &[usize]) -> Option<Vec<usize>> {
+    let ndim = left.len().max(right.len());
+    let mut extents = vec![0; ndim];
+
+    for axis in 0..ndim {
+        // Missing outer axes count as 1
+        let a = if axis < ndim - left.len() { 1 } else { left[axis - (ndim - left.len())] };
+        let b = if axis < ndim - right.len() { 1 } else { right[axis - (ndim - right.len())] };
+
+        extents[axis] = match (a, b) {
+            (a, b) if a == b => a,
+            (1, b) => b,
+            (a, 1) => a,
+            _ => return None,
+        };
+    }
+
+    Some(extents)
+}
+
+/*
+   Strides of `extents` when broadcast to `target`, 0 along the axes that are repeated.
+*/
+pub(crate) fn broadcast_strides(extents: &[usize], target: &[usize]) -> Vec<usize> {
+    let own = strides(extents);
+    let missing = target.len() - extents.len();
+
+    (0..target.len())
+        .map(|axis| {
+            if axis < missing || extents[axis - missing] == 1 {
+                0
+            } else {
+                own[axis - missing]
+            }
+        })
+        .collect()
+}
+
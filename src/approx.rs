@@ -0,0 +1,233 @@
+/*
+ * Numrs/src/approx.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Approximate equality
+   --------------------
+   Floating point results are rarely bit for bit equal to what a test expects, so, like NumPy, two elements a and b are close when
+
+       |a - b| <= atol + rtol * |b|
+
+   with rtol = 1e-5 and atol = 1e-8 by default. The test is not symmetric, `b` is the expected value. Infinities are close only to
+   an infinity of the same sign and NaN is never close to anything, unless `equal_nan` is set, then NaN is close to NaN.
+
+       let close = actual.isclose(&expected);                     // Collective<bool> of the same shape
+       assert!(actual.allclose(&expected));
+       assert!(actual.allclose_with(&expected, &CloseOptions::new().with_atol(1e-3)));
+
+   In tests `assert_allclose!` prints where the Collectives differ and by how much:
+
+       assert_allclose!(actual, expected);
+       assert_allclose!(actual, expected, CloseOptions::new().with_rtol(1e-3).with_equal_nan(true));
+
+   Elements are compared as f64, so any element type (integers, f16, bf16) can be compared.
+*/
+
+use super::{collective::Collective, num::Num};
+
+/*
+   Tolerances of isclose(), allclose() and assert_allclose!. The defaults are NumPy's.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CloseOptions {
+    // Relative tolerance, scaled by the magnitude of the expected element
+    pub rtol: f64,
+    // Absolute tolerance
+    pub atol: f64,
+    // Whether NaN is close to NaN
+    pub equal_nan: bool,
+}
+
+impl CloseOptions {
+    pub const fn new() -> Self {
+        Self {
+            rtol: 1e-5,
+            atol: 1e-8,
+            equal_nan: false,
+        }
+    }
+
+    // Fluent setters
+    pub fn with_rtol(mut self, rtol: f64) -> Self {
+        self.rtol = rtol;
+        self
+    }
+
+    pub fn with_atol(mut self, atol: f64) -> Self {
+        self.atol = atol;
+        self
+    }
+
+    pub fn with_equal_nan(mut self, equal_nan: bool) -> Self {
+        self.equal_nan = equal_nan;
+        self
+    }
+
+    /// Whether `actual` is close to `expected`.
+    pub fn is_close(&self, actual: f64, expected: f64) -> bool {
+        if actual.is_nan() || expected.is_nan() {
+            return self.equal_nan && actual.is_nan() && expected.is_nan();
+        }
+
+        if actual.is_infinite() || expected.is_infinite() {
+            return actual == expected;
+        }
+
+        (actual - expected).abs() <= self.atol + self.rtol * expected.abs()
+    }
+}
+
+impl Default for CloseOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> Collective<E>
+where
+    E: Num,
+{
+    /// Compares the Collective element by element with `expected` using the default tolerances.
+    ///
+    /// # Panics
+    /// If the shapes differ or either Collective is unallocated.
+    pub fn isclose(&self, expected: &Collective<E>) -> Collective<bool> {
+        self.isclose_with(expected, &CloseOptions::new())
+    }
+
+    /// Compares the Collective element by element with `expected`.
+    ///
+    /// # Returns
+    /// A `Collective<bool>` with the shape of `self`, `true` where the elements are close.
+    ///
+    /// # Panics
+    /// If the shapes differ or either Collective is unallocated.
+    pub fn isclose_with(&self, expected: &Collective<E>, options: &CloseOptions) -> Collective<bool> {
+        let (actual_data, expected_data) = match compatible(self, expected) {
+            Ok(data) => data,
+            Err(message) => panic!("Collective::isclose(): {}", message),
+        };
+
+        let data = actual_data
+            .iter()
+            .zip(expected_data.iter())
+            .map(|(&actual, &expected)| options.is_close(actual.to_f64(), expected.to_f64()))
+            .collect::<Vec<bool>>()
+            .into_boxed_slice();
+
+        Collective {
+            data: Some(data),
+            shape: self.shape.clone(),
+        }
+    }
+
+    /// Whether every element is close to the corresponding element of `expected`, using the default tolerances.
+    /// Collectives of different shapes, or unallocated ones, are not close.
+    pub fn allclose(&self, expected: &Collective<E>) -> bool {
+        self.allclose_with(expected, &CloseOptions::new())
+    }
+
+    /// Whether every element is close to the corresponding element of `expected`.
+    /// Collectives of different shapes, or unallocated ones, are not close.
+    pub fn allclose_with(&self, expected: &Collective<E>, options: &CloseOptions) -> bool {
+        check_allclose(self, expected, options).is_ok()
+    }
+}
+
+/*
+   The data of both Collectives, if their shapes match.
+*/
+fn compatible<'a, E>(actual: &'a Collective<E>, expected: &'a Collective<E>) -> Result<(&'a [E], &'a [E]), String> {
+    let extents = |collective: &Collective<E>| collective.shape.as_ref().map(|shape| shape.to_vec());
+
+    let (actual_data, expected_data) = match (&actual.data, &expected.data) {
+        (Some(actual), Some(expected)) => (actual, expected),
+        _ => return Err("Collective data is not allocated".to_string()),
+    };
+
+    if extents(actual) != extents(expected) || actual_data.len() != expected_data.len() {
+        return Err(format!(
+            "Shapes differ, {:?} and {:?}",
+            extents(actual).unwrap_or_else(|| vec![actual_data.len() as f64]),
+            extents(expected).unwrap_or_else(|| vec![expected_data.len() as f64])
+        ));
+    }
+
+    Ok((actual_data, expected_data))
+}
+
+/*
+   Used by assert_allclose!, the error describes the first mismatch, how many elements mismatch and the largest differences.
+*/
+#[doc(hidden)]
+pub fn check_allclose<E: Num>(actual: &Collective<E>, expected: &Collective<E>, options: &CloseOptions) -> Result<(), String> {
+    let (actual_data, expected_data) = compatible(actual, expected)?;
+
+    let mut mismatches = 0;
+    let mut first = None;
+    let mut max_absolute = 0.0f64;
+    let mut max_relative = 0.0f64;
+
+    for (index, (&a, &b)) in actual_data.iter().zip(expected_data.iter()).enumerate() {
+        let (a, b) = (a.to_f64(), b.to_f64());
+
+        if options.is_close(a, b) {
+            continue;
+        }
+
+        mismatches += 1;
+        first.get_or_insert((index, a, b));
+
+        let absolute = (a - b).abs();
+        if !absolute.is_nan() {
+            max_absolute = max_absolute.max(absolute);
+            max_relative = max_relative.max(absolute / b.abs());
+        }
+    }
+
+    match first {
+        None => Ok(()),
+        Some((index, a, b)) => Err(format!(
+            "{} of {} elements differ (rtol={}, atol={}), first at index {}: {} != {}, max absolute difference {}, max relative difference {}",
+            mismatches,
+            actual_data.len(),
+            options.rtol,
+            options.atol,
+            index,
+            a,
+            b,
+            max_absolute,
+            max_relative
+        )),
+    }
+}
+
+/// Asserts that two Collectives have the same shape and that every element of the first is close to the corresponding element
+/// of the second (see approx.rs), with the default tolerances or the given `CloseOptions`.
+///
+/// ```rust
+/// # use numrs::{assert_allclose, approx::CloseOptions, collective::Collective, Dimensions};
+/// let actual = Collective::new(Some(vec![0.1f64 + 0.2].into_boxed_slice()), Some(Box::new(Dimensions::new(1.0, 1.0))));
+/// let expected = Collective::new(Some(vec![0.3f64].into_boxed_slice()), Some(Box::new(Dimensions::new(1.0, 1.0))));
+///
+/// assert_allclose!(actual, expected);
+/// assert_allclose!(actual, expected, CloseOptions::new().with_rtol(0.0));
+/// ```
+#[macro_export]
+macro_rules! assert_allclose {
+    ($actual:expr, $expected:expr $(,)?) => {
+        $crate::assert_allclose!($actual, $expected, $crate::approx::CloseOptions::new())
+    };
+    ($actual:expr, $expected:expr, $options:expr $(,)?) => {
+        if let Err(message) = $crate::approx::check_allclose(&$actual, &$expected, &$options) {
+            panic!(
+                "assertion failed: `{}` is not close to `{}`\n{}",
+                stringify!($actual),
+                stringify!($expected),
+                message
+            );
+        }
+    };
+}
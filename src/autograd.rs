@@ -0,0 +1,803 @@
+/*
+ * Numrs/src/autograd.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Reverse-mode automatic differentiation
+   --------------------------------------
+   Opt-in, nothing else in the crate depends on it. A `Variable` wraps a `Collective` and every operation on Variables
+   computes its value right away (with the Collective methods of this crate) and records on a tape how to take the gradient
+   back through it. `backward()` replays the tape in reverse and leaves d(output)/d(variable) in every leaf Variable:
+
+       let w = Variable::new(Tensor::randn::<f64>(Dimensions::new(3.0, 4.0)));   // [4, 3], a parameter
+       let b = Variable::new(Tensor::zeros::<f64>(Dimensions::new(3.0, 1.0)));  // [1, 3]
+       let x = Variable::constant(inputs);                                       // [batch, 4], no gradient wanted
+
+       let loss = (&x.matmul(&w) + &b).relu().mean(None);
+       loss.backward();
+
+       let gradient = w.grad().unwrap();                                          // [4, 3], like w
+
+   - Leaves are created with `Variable::new` (gradient wanted) or `Variable::constant`. The result of an operation needs a
+     gradient when one of its inputs does.
+   - Gradients accumulate: a second backward() adds to what the first left in the leaves, until `zero_grad()`, so the
+     gradients of several mini-batches can be summed before an update. Only leaves keep their gradient.
+   - Inside `no_grad(|| ...)` nothing is recorded, e.g. for evaluation or for updating the parameters in place through
+     `value_mut()`.
+   - `backward()` needs a single-valued output (a loss), `backward_with(gradient)` takes d(loss)/d(output) of any output.
+
+   Operations:
+   - &a + &b, &a - &b, &a * &b, &a / &b, -&a     element by element, with broadcasting (see `zip_with`); the gradient of a
+                                                  broadcast input is summed back to its shape
+   - add_scalar, mul_scalar, powf, exp, ln
+   - matmul, transpose                            see linalg.rs
+   - sum(axis), mean(axis)                        along an axis, or of all the elements for `None` (1 x 1)
+   - relu, sigmoid, tanh, activation(Activation)  see activations.rs
+   - reshape(extents), slice(axis, start, end)
+
+   The tape is the graph of Variables itself, each result keeps its inputs alive through `Rc`s until it is dropped.
+   Variables are not `Send`, like the Dimensions of a Collective.
+
+   Gradient checking compares backward() with central differences, (f(x + h) - f(x - h)) / 2h for every input element:
+
+       autograd::check_gradients(|v| (&v[0] * &v[1]).tanh().sum(None), &[a, b], &GradCheckOptions::new())?;
+
+   The differences are only meaningful in f64.
+*/
+
+use super::{
+    activations::Activation,
+    apply::broadcast_strides,
+    approx::{check_allclose, CloseOptions},
+    collective::{dimensions_from_extents, strides, Collective},
+    num::Float,
+};
+use std::{
+    cell::{Cell, Ref, RefCell, RefMut},
+    collections::{HashMap, HashSet},
+    fmt, ops,
+    rc::Rc,
+};
+
+thread_local! {
+    // The number of no_grad() scopes this thread is in
+    static NO_GRAD_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Runs `f` without recording operations on this thread, their results need no gradient.
+pub fn no_grad<T, F: FnOnce() -> T>(f: F) -> T {
+    struct Restore;
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            NO_GRAD_DEPTH.with(|depth| depth.set(depth.get() - 1));
+        }
+    }
+
+    NO_GRAD_DEPTH.with(|depth| depth.set(depth.get() + 1));
+    let _restore = Restore;
+
+    f()
+}
+
+/// Whether operations are recorded on this thread, false inside `no_grad`.
+pub fn is_grad_enabled() -> bool {
+    NO_GRAD_DEPTH.with(|depth| depth.get() == 0)
+}
+
+/*
+   Maps the gradient of the result of an operation to the gradients of its inputs, in the order of the inputs.
+*/
+type Backward<E> = Box<dyn Fn(&Collective<E>) -> Vec<Collective<E>>>;
+
+struct Operation<E> {
+    name: &'static str,
+    inputs: Vec<Variable<E>>,
+    backward: Backward<E>,
+}
+
+struct Node<E> {
+    // A RefCell so parameters can be updated in place between steps
+    value: RefCell<Collective<E>>,
+    // Accumulated by backward(), leaves only
+    grad: RefCell<Option<Collective<E>>>,
+    requires_grad: bool,
+    // None for leaves
+    operation: Option<Operation<E>>,
+}
+
+/*
+   Dropping the last handle of a tape drops every node of it, Node -> inputs -> Node, which would recurse once per operation
+   and overflow the stack for the tape of a long loop (as a recursive topological_order() would). The inputs are unlinked
+   here one at a time instead, those still used elsewhere are left alone.
+*/
+impl<E> Drop for Node<E> {
+    fn drop(&mut self) {
+        let mut stack = match self.operation.take() {
+            Some(operation) => operation.inputs,
+            None => return,
+        };
+
+        while let Some(variable) = stack.pop() {
+            if let Ok(mut node) = Rc::try_unwrap(variable.node) {
+                if let Some(operation) = node.operation.take() {
+                    stack.extend(operation.inputs);
+                }
+            }
+        }
+    }
+}
+
+/*
+   A Collective taking part in the differentiation. Cloning a Variable gives another handle to the same value and gradient.
+*/
+pub struct Variable<E> {
+    node: Rc<Node<E>>,
+}
+
+impl<E> Clone for Variable<E> {
+    fn clone(&self) -> Self {
+        Variable { node: Rc::clone(&self.node) }
+    }
+}
+
+impl<E> Variable<E>
+where
+    E: Float + 'static,
+{
+    /// A leaf whose gradient backward() computes, e.g. a parameter.
+    pub fn new(value: Collective<E>) -> Self {
+        Self::leaf(value, true)
+    }
+
+    /// A leaf without a gradient, e.g. the inputs or targets of a model.
+    pub fn constant(value: Collective<E>) -> Self {
+        Self::leaf(value, false)
+    }
+
+    fn leaf(value: Collective<E>, requires_grad: bool) -> Self {
+        Variable {
+            node: Rc::new(Node {
+                value: RefCell::new(value),
+                grad: RefCell::new(None),
+                requires_grad,
+                operation: None,
+            }),
+        }
+    }
+
+    /// The value of the Variable.
+    pub fn value(&self) -> Ref<'_, Collective<E>> {
+        self.node.value.borrow()
+    }
+
+    /// The value of the Variable, to update a parameter in place. Operations already recorded keep the value they saw.
+    pub fn value_mut(&self) -> RefMut<'_, Collective<E>> {
+        self.node.value.borrow_mut()
+    }
+
+    /// The single element of the value.
+    ///
+    /// # Panics
+    /// If the value does not have exactly one element.
+    pub fn item(&self) -> E {
+        match self.value().data.as_deref() {
+            Some([value]) => *value,
+            _ => panic!("Variable::item(): The value has {} elements, not one", self.len()),
+        }
+    }
+
+    /// The gradient accumulated by backward(), None before the first backward() or after `zero_grad()`.
+    pub fn grad(&self) -> Option<Collective<E>> {
+        self.node.grad.borrow().clone()
+    }
+
+    /// Clears the accumulated gradient.
+    pub fn zero_grad(&self) {
+        *self.node.grad.borrow_mut() = None;
+    }
+
+    /// Whether backward() computes a gradient for this Variable.
+    pub fn requires_grad(&self) -> bool {
+        self.node.requires_grad
+    }
+
+    /// Whether the Variable was created rather than computed by an operation.
+    pub fn is_leaf(&self) -> bool {
+        self.node.operation.is_none()
+    }
+
+    /// A constant with a copy of the value, gradients do not flow back through it.
+    pub fn detach(&self) -> Variable<E> {
+        Variable::constant(self.value().clone())
+    }
+
+    fn len(&self) -> usize {
+        self.value().data.as_ref().map_or(0, |data| data.len())
+    }
+
+    /// Computes the gradient of this single-valued Variable (a loss) with respect to every leaf it was computed from,
+    /// adding it to their gradients.
+    ///
+    /// # Panics
+    /// If the value does not have exactly one element, or the Variable needs no gradient.
+    pub fn backward(&self) {
+        if self.len() != 1 {
+            panic!(
+                "Variable::backward(): The value has {} elements, use backward_with() to pass the gradient of a value that is not a single element",
+                self.len()
+            );
+        }
+
+        let seed = self.value().map(|_| E::one());
+        self.backward_with(&seed);
+    }
+
+    /// Computes the gradients of the leaves given `gradient`, the gradient of the loss with respect to this Variable,
+    /// adding them to the gradients of the leaves.
+    ///
+    /// # Panics
+    /// If `gradient` does not have the shape of the value, or the Variable needs no gradient.
+    pub fn backward_with(&self, gradient: &Collective<E>) {
+        if !self.node.requires_grad {
+            panic!("Variable::backward(): The Variable needs no gradient, it was computed from constants only or inside no_grad()");
+        }
+
+        let extents = self.value().extents();
+        if gradient.extents() != extents {
+            panic!(
+                "Variable::backward(): The gradient of shape {:?} does not match the value of shape {:?}",
+                gradient.extents(),
+                extents
+            );
+        }
+
+        let mut gradients: HashMap<*const Node<E>, Collective<E>> = HashMap::new();
+        gradients.insert(Rc::as_ptr(&self.node), gradient.clone());
+
+        for variable in self.topological_order() {
+            // Not on a path from a leaf that needs a gradient
+            let gradient = match gradients.remove(&Rc::as_ptr(&variable.node)) {
+                Some(gradient) => gradient,
+                None => continue,
+            };
+
+            let operation = match &variable.node.operation {
+                Some(operation) => operation,
+                None => {
+                    let gradient = gradient.with_extents(&variable.value().extents());
+                    let mut grad = variable.node.grad.borrow_mut();
+
+                    *grad = Some(match grad.take() {
+                        Some(accumulated) => accumulated.zip_with(&gradient, |a, g| a + g),
+                        None => gradient,
+                    });
+                    continue;
+                }
+            };
+
+            for (input, input_gradient) in operation.inputs.iter().zip((operation.backward)(&gradient)) {
+                if !input.node.requires_grad {
+                    continue;
+                }
+
+                let key = Rc::as_ptr(&input.node);
+                let summed = match gradients.remove(&key) {
+                    Some(other) => other.zip_with(&input_gradient, |a, g| a + g),
+                    None => input_gradient,
+                };
+                gradients.insert(key, summed);
+            }
+        }
+    }
+
+    /*
+       Every Variable this one was computed from, each after all the Variables computed from it, starting with this one.
+       Depth first without recursion, the tape of a long loop would overflow the stack.
+    */
+    fn topological_order(&self) -> Vec<Variable<E>> {
+        let mut visited = HashSet::new();
+        let mut order = Vec::new();
+        let mut stack = vec![(self.clone(), false)];
+
+        while let Some((variable, expanded)) = stack.pop() {
+            if expanded {
+                order.push(variable);
+                continue;
+            }
+
+            if !visited.insert(Rc::as_ptr(&variable.node)) {
+                continue;
+            }
+
+            stack.push((variable.clone(), true));
+            if let Some(operation) = &variable.node.operation {
+                for input in operation.inputs.iter().filter(|input| input.node.requires_grad) {
+                    if !visited.contains(&Rc::as_ptr(&input.node)) {
+                        stack.push((input.clone(), false));
+                    }
+                }
+            }
+        }
+
+        order.reverse();
+        order
+    }
+
+    /*
+       The result of an operation, recorded on the tape when an input needs a gradient and recording is enabled.
+    */
+    fn record<F>(value: Collective<E>, name: &'static str, inputs: Vec<Variable<E>>, backward: F) -> Variable<E>
+    where
+        F: Fn(&Collective<E>) -> Vec<Collective<E>> + 'static,
+    {
+        if !is_grad_enabled() || !inputs.iter().any(|input| input.node.requires_grad) {
+            return Variable::constant(value);
+        }
+
+        Variable {
+            node: Rc::new(Node {
+                value: RefCell::new(value),
+                grad: RefCell::new(None),
+                requires_grad: true,
+                operation: Some(Operation {
+                    name,
+                    inputs,
+                    backward: Box::new(backward),
+                }),
+            }),
+        }
+    }
+
+    /*
+       An element-wise operation of two broadcast inputs, `derivatives(a, b)` gives d/da and d/db of f(a, b).
+    */
+    fn binary(
+        &self,
+        other: &Variable<E>,
+        name: &'static str,
+        f: fn(E, E) -> E,
+        derivatives: fn(f64, f64) -> (f64, f64),
+    ) -> Variable<E> {
+        let (a, b) = (self.value().clone(), other.value().clone());
+        let value = a.zip_with(&b, f);
+
+        Self::record(value, name, vec![self.clone(), other.clone()], move |gradient| {
+            let (a_extents, b_extents) = (a.extents(), b.extents());
+            let partials = a.zip_with(&b, |x, y| derivatives(x.to_f64(), y.to_f64()));
+
+            let da = gradient.zip_with(&partials, |g, (d, _)| E::from_f64(g.to_f64() * d));
+            let db = gradient.zip_with(&partials, |g, (_, d)| E::from_f64(g.to_f64() * d));
+
+            vec![sum_to(&da, &a_extents), sum_to(&db, &b_extents)]
+        })
+    }
+
+    /*
+       An element-wise operation of one input, `derivative(x, y)` gives dy/dx at the input x with the result y.
+    */
+    fn unary(&self, name: &'static str, f: impl Fn(E) -> E, derivative: impl Fn(f64, f64) -> f64 + 'static) -> Variable<E> {
+        let x = self.value().clone();
+        let y = x.map(f);
+        let result = y.clone();
+
+        Self::record(result, name, vec![self.clone()], move |gradient| {
+            let partials = x.zip_with(&y, |x, y| derivative(x.to_f64(), y.to_f64()));
+
+            vec![gradient.zip_with(&partials, |g, d| E::from_f64(g.to_f64() * d))]
+        })
+    }
+
+    /// Adds `scalar` to every element.
+    pub fn add_scalar(&self, scalar: E) -> Variable<E> {
+        self.unary("add_scalar", move |x| x + scalar, |_, _| 1.0)
+    }
+
+    /// Multiplies every element by `scalar`.
+    pub fn mul_scalar(&self, scalar: E) -> Variable<E> {
+        let s = scalar.to_f64();
+
+        self.unary("mul_scalar", move |x| x * scalar, move |_, _| s)
+    }
+
+    /// Raises every element to the power `exponent`.
+    pub fn powf(&self, exponent: E) -> Variable<E> {
+        let p = exponent.to_f64();
+
+        self.unary("powf", move |x| x.powf(exponent), move |x, _| p * x.powf(p - 1.0))
+    }
+
+    /// e raised to the power of every element.
+    pub fn exp(&self) -> Variable<E> {
+        self.unary("exp", E::exp, |_, y| y)
+    }
+
+    /// The natural logarithm of every element.
+    pub fn ln(&self) -> Variable<E> {
+        self.unary("ln", E::ln, |x, _| 1.0 / x)
+    }
+
+    /// max(0, x) of every element.
+    pub fn relu(&self) -> Variable<E> {
+        self.activation(Activation::ReLU)
+    }
+
+    /// `1 / (1 + e^-x)` of every element.
+    pub fn sigmoid(&self) -> Variable<E> {
+        self.activation(Activation::Sigmoid)
+    }
+
+    /// The hyperbolic tangent of every element.
+    pub fn tanh(&self) -> Variable<E> {
+        self.activation(Activation::Tanh)
+    }
+
+    /// Applies `activation` to every element, its gradient is that of `Activation::derivative`.
+    pub fn activation(&self, activation: Activation) -> Variable<E> {
+        let x = self.value().clone();
+
+        Self::record(activation.apply(&x), "activation", vec![self.clone()], move |gradient| {
+            vec![gradient.zip_with(&activation.derivative(&x), |g, d| g * d)]
+        })
+    }
+
+    /// The matrix product of the innermost two axes, see `Collective::matmul`.
+    ///
+    /// # Panics
+    /// If the shapes are not aligned.
+    pub fn matmul(&self, other: &Variable<E>) -> Variable<E> {
+        let (a, b) = (self.value().clone(), other.value().clone());
+        let value = a.matmul(&b);
+
+        Self::record(value, "matmul", vec![self.clone(), other.clone()], move |gradient| {
+            let da = gradient.matmul(&b.transpose());
+
+            let b_extents = b.extents();
+            let db = if b_extents.len() == 2 {
+                // Every matrix of `a` was multiplied by `b`, their contributions add up: stack the rows and take A^T G once
+                let rows = a.extents().iter().rev().skip(1).product::<usize>();
+                let (columns, outputs) = (b_extents[0], b_extents[1]);
+
+                a.with_extents(&[rows, columns]).transpose().matmul(&gradient.with_extents(&[rows, outputs]))
+            } else {
+                a.transpose().matmul(gradient)
+            };
+
+            vec![da, db]
+        })
+    }
+
+    /// Swaps the innermost two axes.
+    pub fn transpose(&self) -> Variable<E> {
+        Self::record(self.value().transpose(), "transpose", vec![self.clone()], |gradient| vec![gradient.transpose()])
+    }
+
+    /// The sum along `axis`, or of all the elements for `None`.
+    ///
+    /// # Panics
+    /// If `axis` is not an axis of the value.
+    pub fn sum(&self, axis: Option<usize>) -> Variable<E> {
+        self.reduction("sum", axis, false)
+    }
+
+    /// The mean along `axis`, or of all the elements for `None`.
+    ///
+    /// # Panics
+    /// If `axis` is not an axis of the value.
+    pub fn mean(&self, axis: Option<usize>) -> Variable<E> {
+        self.reduction("mean", axis, true)
+    }
+
+    fn reduction(&self, name: &'static str, axis: Option<usize>, mean: bool) -> Variable<E> {
+        let extents = self.value().extents();
+
+        if let Some(axis) = axis.filter(|&axis| axis >= extents.len()) {
+            panic!("Variable::{}(): Axis {} is out of range for a Variable of {} axes", name, axis, extents.len());
+        }
+
+        let count = match axis {
+            Some(axis) => extents[axis],
+            None => extents.iter().product(),
+        };
+        let scale = if mean { 1.0 / count as f64 } else { 1.0 };
+
+        let value = self
+            .value()
+            .fold(axis, 0.0f64, |sum, x| sum + x.to_f64())
+            .map(|sum| E::from_f64(sum * scale));
+
+        Self::record(value, name, vec![self.clone()], move |gradient| {
+            // The gradient of the reduced axis spread over its elements, a 1 x 1 gradient broadcasts as it is
+            let kept = match axis {
+                Some(axis) => {
+                    let mut kept = extents.clone();
+                    kept[axis] = 1;
+                    gradient.with_extents(&kept)
+                }
+                None => gradient.clone(),
+            };
+
+            vec![broadcast_to(&kept, &extents).map(|g| E::from_f64(g.to_f64() * scale))]
+        })
+    }
+
+    /// The same elements in the shape of `extents`, from the outermost axis to the innermost.
+    ///
+    /// # Panics
+    /// If `extents` does not hold the number of elements of the value.
+    pub fn reshape(&self, extents: &[usize]) -> Variable<E> {
+        let original = self.value().extents();
+
+        if extents.iter().product::<usize>() != self.len() || extents.contains(&0) {
+            panic!("Variable::reshape(): Cannot reshape {:?} into {:?}", original, extents);
+        }
+
+        Self::record(self.value().with_extents(extents), "reshape", vec![self.clone()], move |gradient| {
+            vec![gradient.with_extents(&original)]
+        })
+    }
+
+    /// The elements at `start..end` along `axis`, the axis keeps its place with an extent of `end - start`.
+    ///
+    /// # Panics
+    /// If `axis` is not an axis of the value, or the range is empty or beyond its extent.
+    pub fn slice(&self, axis: usize, start: usize, end: usize) -> Variable<E> {
+        let extents = self.value().extents();
+
+        if axis >= extents.len() {
+            panic!("Variable::slice(): Axis {} is out of range for a Variable of {} axes", axis, extents.len());
+        }
+
+        if start >= end || end > extents[axis] {
+            panic!(
+                "Variable::slice(): Range {}..{} is empty or out of bounds for axis {} of extent {}",
+                start, end, axis, extents[axis]
+            );
+        }
+
+        let value = slice_axis(&self.value(), axis, start, end);
+
+        Self::record(value, "slice", vec![self.clone()], move |gradient| {
+            vec![unslice_axis(gradient, &extents, axis, start)]
+        })
+    }
+}
+
+// Fluent arithmetic on references: &a + &b
+macro_rules! binary_operator {
+    ($trait:ident, $method:ident, $name:literal, $f:expr, $derivatives:expr) => {
+        impl<E: Float + 'static> ops::$trait<&Variable<E>> for &Variable<E> {
+            type Output = Variable<E>;
+
+            fn $method(self, other: &Variable<E>) -> Variable<E> {
+                self.binary(other, $name, $f, $derivatives)
+            }
+        }
+    };
+}
+
+binary_operator!(Add, add, "add", |a, b| a + b, |_, _| (1.0, 1.0));
+binary_operator!(Sub, sub, "sub", |a, b| a - b, |_, _| (1.0, -1.0));
+binary_operator!(Mul, mul, "mul", |a, b| a * b, |a, b| (b, a));
+binary_operator!(Div, div, "div", |a, b| a / b, |a, b| (1.0 / b, -a / (b * b)));
+
+impl<E: Float + 'static> ops::Neg for &Variable<E> {
+    type Output = Variable<E>;
+
+    fn neg(self) -> Variable<E> {
+        self.mul_scalar(-E::one())
+    }
+}
+
+impl<E: Float> fmt::Debug for Variable<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Variable({:?}", self.node.value.borrow())?;
+
+        match &self.node.operation {
+            Some(operation) => write!(f, ", grad_fn={})", operation.name),
+            None if self.node.requires_grad => write!(f, ", requires_grad=true)"),
+            None => write!(f, ")"),
+        }
+    }
+}
+
+/*
+   Sums a gradient of the broadcast shape back to the shape of the input that was broadcast, over the axes it was repeated along.
+*/
+fn sum_to<E: Float>(gradient: &Collective<E>, extents: &[usize]) -> Collective<E> {
+    let gradient_extents = gradient.extents();
+
+    let data = match &gradient.data {
+        Some(data) if gradient_extents != extents => data,
+        _ => return gradient.clone(),
+    };
+
+    let target_strides = broadcast_strides(extents, &gradient_extents);
+    let gradient_strides = strides(&gradient_extents);
+
+    let mut sums = vec![0.0f64; extents.iter().product()];
+    for (index, value) in data.iter().enumerate() {
+        let offset: usize = (0..gradient_extents.len())
+            .map(|axis| index / gradient_strides[axis] % gradient_extents[axis] * target_strides[axis])
+            .sum();
+
+        sums[offset] += value.to_f64();
+    }
+
+    Collective {
+        data: Some(sums.into_iter().map(E::from_f64).collect::<Vec<E>>().into_boxed_slice()),
+        shape: Some(Box::new(dimensions_from_extents(extents))),
+    }
+}
+
+// Repeats the gradient along the axes where it has extent 1, to the given extents
+fn broadcast_to<E: Float>(gradient: &Collective<E>, extents: &[usize]) -> Collective<E> {
+    let template = Collective {
+        data: Some(vec![E::zero(); extents.iter().product()].into_boxed_slice()),
+        shape: Some(Box::new(dimensions_from_extents(extents))),
+    };
+
+    template.zip_with(gradient, |_, g| g)
+}
+
+// The elements at start..end along the axis
+fn slice_axis<E: Float>(collective: &Collective<E>, axis: usize, start: usize, end: usize) -> Collective<E> {
+    let data = match &collective.data {
+        Some(data) => data,
+        None => return Collective { data: None, shape: None },
+    };
+
+    let mut extents = collective.extents();
+    let inner = strides(&extents)[axis];
+    let outer: usize = extents[..axis].iter().product();
+    let length = extents[axis];
+
+    let mut result = Vec::with_capacity(outer * (end - start) * inner);
+    for block in 0..outer {
+        let base = block * length * inner;
+        result.extend_from_slice(&data[base + start * inner..base + end * inner]);
+    }
+
+    extents[axis] = end - start;
+
+    Collective {
+        data: Some(result.into_boxed_slice()),
+        shape: Some(Box::new(dimensions_from_extents(&extents))),
+    }
+}
+
+// The gradient of a slice put back in place, zeros everywhere else
+fn unslice_axis<E: Float>(gradient: &Collective<E>, extents: &[usize], axis: usize, start: usize) -> Collective<E> {
+    let data = match &gradient.data {
+        Some(data) => data,
+        None => return Collective { data: None, shape: None },
+    };
+
+    let inner = strides(extents)[axis];
+    let outer: usize = extents[..axis].iter().product();
+    let block = data.len() / outer;
+
+    let mut result = vec![E::zero(); extents.iter().product()];
+    for (index, chunk) in data.chunks(block).enumerate() {
+        let base = index * extents[axis] * inner + start * inner;
+        result[base..base + block].copy_from_slice(chunk);
+    }
+
+    Collective {
+        data: Some(result.into_boxed_slice()),
+        shape: Some(Box::new(dimensions_from_extents(extents))),
+    }
+}
+
+/*
+   Options of check_gradients(), the step of the central differences and how close they must come to backward().
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradCheckOptions {
+    pub epsilon: f64,
+    pub tolerance: CloseOptions,
+}
+
+impl GradCheckOptions {
+    pub const fn new() -> Self {
+        Self {
+            epsilon: 1e-6,
+            tolerance: CloseOptions {
+                rtol: 1e-4,
+                atol: 1e-6,
+                equal_nan: false,
+            },
+        }
+    }
+
+    // Fluent setters
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    pub fn with_tolerance(mut self, tolerance: CloseOptions) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+}
+
+impl Default for GradCheckOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Central differences of the sum of the elements of `f(inputs)` with respect to every element of every input.
+///
+/// # Returns
+/// One gradient per input, of its shape. `f` is evaluated inside `no_grad`.
+pub fn numerical_gradient<E, F>(f: F, inputs: &[Collective<E>], epsilon: f64) -> Vec<Collective<E>>
+where
+    E: Float + 'static,
+    F: Fn(&[Variable<E>]) -> Variable<E>,
+{
+    let evaluate = |inputs: &[Collective<E>]| -> f64 {
+        let variables: Vec<Variable<E>> = inputs.iter().cloned().map(Variable::constant).collect();
+        let output = f(&variables);
+        let sum = output.value().iter().map(|value| value.to_f64()).sum();
+
+        sum
+    };
+
+    no_grad(|| {
+        let mut perturbed = inputs.to_vec();
+
+        (0..inputs.len())
+            .map(|input| {
+                let mut gradient = inputs[input].map(|_| E::zero());
+
+                for index in 0..gradient.iter().count() {
+                    let original = inputs[input][index];
+
+                    perturbed[input][index] = E::from_f64(original.to_f64() + epsilon);
+                    let plus = evaluate(&perturbed);
+
+                    perturbed[input][index] = E::from_f64(original.to_f64() - epsilon);
+                    let minus = evaluate(&perturbed);
+
+                    perturbed[input][index] = original;
+                    gradient[index] = E::from_f64((plus - minus) / (2.0 * epsilon));
+                }
+
+                gradient
+            })
+            .collect()
+    })
+}
+
+/// Compares the gradients backward() computes for `f` at `inputs` with `numerical_gradient`.
+/// A Variable output that is not a single value is summed, as if `f` ended in `sum(None)`.
+///
+/// # Returns
+/// An error naming the first input whose gradients differ and where, see `assert_allclose!`.
+pub fn check_gradients<E, F>(f: F, inputs: &[Collective<E>], options: &GradCheckOptions) -> Result<(), String>
+where
+    E: Float + 'static,
+    F: Fn(&[Variable<E>]) -> Variable<E>,
+{
+    let variables: Vec<Variable<E>> = inputs.iter().cloned().map(Variable::new).collect();
+
+    let output = f(&variables);
+    if output.requires_grad() {
+        let ones = output.value().map(|_| E::one());
+        output.backward_with(&ones);
+    }
+
+    let numerical = numerical_gradient(&f, inputs, options.epsilon);
+
+    for (index, (variable, expected)) in variables.iter().zip(numerical.iter()).enumerate() {
+        // An input the output does not depend on has a zero gradient
+        let analytical = variable.grad().unwrap_or_else(|| expected.map(|_| E::zero()));
+
+        check_allclose(&analytical, expected, &options.tolerance).map_err(|message| format!("Gradient of input {}: {}", index, message))?;
+    }
+
+    Ok(())
+}
@@ -4,7 +4,11 @@
  */
 
 use super::dimensions::Dimensions;
-use crate::header::Axis;
+use crate::header::{Axis, Rounding, Saturation};
+use crate::npy::shape_to_dimensions;
+use crate::num::{round_integer_to_precision, Bounded, Num};
+use crate::print;
+use std::fmt;
 use std::ops::{Index, IndexMut};
 
 pub struct Collective<E = f64> {
@@ -36,6 +40,95 @@ impl<E> IndexMut<usize> for Collective<E> {
     }
 }
 
+impl<E> Collective<E> {
+    /*
+       The extents of the shape from the outermost axis to the innermost, as in Dimensions::to_vec().
+       Data without a shape, or with a shape that does not match it, is treated as a single row of all its elements.
+    */
+    pub(crate) fn extents(&self) -> Vec<usize> {
+        let len = self.data.as_ref().map_or(0, |data| data.len());
+
+        match &self.shape {
+            Some(shape) if shape.get_n() == len => shape.to_vec().iter().map(|&extent| extent as usize).collect(),
+            _ => vec![1, len],
+        }
+    }
+}
+
+impl<E: Clone> Collective<E> {
+    /*
+       A copy of the data with the shape of the given extents, which must hold the same number of elements.
+    */
+    pub(crate) fn with_extents(&self, extents: &[usize]) -> Collective<E> {
+        debug_assert_eq!(extents.iter().product::<usize>(), self.data.as_ref().map_or(0, |data| data.len()));
+
+        Collective {
+            data: self.data.clone(),
+            shape: self.data.as_ref().filter(|data| !data.is_empty()).map(|_| Box::new(dimensions_from_extents(extents))),
+        }
+    }
+}
+
+/*
+   Row-major strides of the given extents, the number of elements between consecutive indices along each axis.
+*/
+pub(crate) fn strides(extents: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; extents.len()];
+
+    for axis in (0..extents.len().saturating_sub(1)).rev() {
+        strides[axis] = strides[axis + 1] * extents[axis + 1];
+    }
+
+    strides
+}
+
+/*
+   The Dimensions of the given extents, see npy::shape_to_dimensions() (a single axis becomes the 1-D shape [1, n]).
+   Only called with the extents of data that is allocated, which are never zero.
+*/
+pub(crate) fn dimensions_from_extents(extents: &[usize]) -> Dimensions {
+    shape_to_dimensions(extents).expect("extents of a non-empty Collective are never zero")
+}
+
+// Cloning copies the data, the shape is cloned the way astype() clones it
+impl<E: Clone> Clone for Collective<E> {
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+            shape: self.shape.clone(),
+        }
+    }
+}
+
+// Two Collectives are equal when their shapes have the same extents and their data is equal element by element (NaN is never equal, use allclose() for that)
+impl<E: PartialEq> PartialEq for Collective<E> {
+    fn eq(&self, other: &Self) -> bool {
+        let shape = |collective: &Self| collective.shape.as_ref().map(|shape| shape.to_vec());
+
+        shape(self) == shape(other) && self.data == other.data
+    }
+}
+
+// {:?} prints the elements like Display (see print.rs) but comma separated, followed by the shape: Collective([[1., 2.], [3., 4.]], shape=[2, 2])
+impl<E: print::PrintElement> fmt::Debug for Collective<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const PREFIX: &str = "Collective(";
+
+        let options = print::get_printoptions();
+        let elements = print::format_collective(self, &options, ", ", PREFIX.len());
+
+        let shape = match &self.shape {
+            Some(shape) => format!(
+                "[{}]",
+                shape.to_vec().iter().map(|extent| (*extent as usize).to_string()).collect::<Vec<String>>().join(", ")
+            ),
+            None => "None".to_string(),
+        };
+
+        write!(f, "{}{}, shape={})", PREFIX, elements, shape)
+    }
+}
+
 impl<E> Collective<E>
 where
     E: Default + Copy,
@@ -63,10 +156,7 @@ where
        where shape or data might be determined at different stages of computation.
     */
     pub fn new(data: Option<Box<[E]>>, shape: Option<Box<Dimensions>>) -> Self {
-        Self {
-            data: data,
-            shape: shape,
-        }
+        Self { data, shape }
     }
 
     /*
@@ -130,6 +220,7 @@ where
     /// # Returns
     /// A new `Collective<E>` instance. If the original data is `None`, it returns an
     /// empty `Collective` with `None` for both data and shape.
+    #[allow(clippy::borrowed_box)] // Part of the public API, callers pass the shape of another Collective
     pub fn get_slice(
         &self,
         start: f64,
@@ -142,17 +233,17 @@ where
             // Using `if let` is a safe way to handle the Option without unwrapping
             if let Some(data) = &self.data {
                 // Check for valid slice range to prevent panics.
-                if start > end || end > self.data.as_ref().unwrap().len() as f64 {
+                if start > end || end > data.len() as f64 {
                     panic!(
                         "Slice indices out of bounds. start: {}, end: {}, len: {}",
                         start,
                         end,
-                        self.data.as_ref().unwrap().len()
+                        data.len()
                     );
                 }
 
                 /*
-                 *   - self.data.as_ref().unwrap() - Gets a reference to the original Box<[E]>
+                 *   - data - A reference to the original Box<[E]>
                  *   - [(start as usize)..(end as usize)] - Creates a slice view of the specified range
                  *   - to_vec() - Creates an owned Vec<E> from the slice and copies the data
                  *       - Creates a new Vec<E> on the heap
@@ -163,10 +254,9 @@ where
                  *       - Moves all elements from the vector into this new box
                  *       - Returns the new box
                  */
-                let new_buffer = self.data.as_ref().unwrap()[(start as usize)..(end as usize)]
+                let new_buffer = data[(start as usize)..(end as usize)]
                     .to_vec()
                     .into_boxed_slice();
-                let new_size = new_buffer.len();
 
                 // Return the new Collective with the copied data and cloned shape.
                 //
@@ -287,3 +377,122 @@ where
         }
     }
 }
+
+impl<E> Collective<E>
+where
+    E: Num,
+{
+    /// Converts every element to the element type `T` and returns the result as a new `Collective<T>`.
+    ///
+    /// Values are rounded to nearest (ties to even) and clamped to the range of `T`,
+    /// which is the safest choice for keeping f32 master weights and computing in bf16/f16.
+    /// Use `astype` to pick other rounding and saturation modes.
+    ///
+    /// # Returns
+    /// A new `Collective<T>` with a clone of the shape. If the original data is `None`, so is the data of the result.
+    pub fn cast<T>(&self) -> Collective<T>
+    where
+        T: Num + Bounded,
+    {
+        self.astype::<T>(Rounding::NearestEven, Saturation::Saturate)
+    }
+
+    /// Converts every element to the element type `T` with the given rounding and saturation modes.
+    ///
+    /// # Arguments
+    /// * `rounding` - How values between two representable values of `T` are rounded (see `Rounding`).
+    /// * `saturation` - What happens to values outside the range of `T` (see `Saturation`).
+    ///
+    /// # How it works:
+    /// - Integer to integer conversions are exact, they go through `i128`.
+    /// - Everything else goes through `f64`, which holds every f32, f16 and bf16 value exactly.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use numrs::{collective::Collective, Dimensions, Rounding, Saturation};
+    /// let weights = Collective::new(
+    ///     Some(vec![2.5f32, -3.7, 300.0].into_boxed_slice()),
+    ///     Some(Box::new(Dimensions::new(3.0, 1.0))),
+    /// );
+    ///
+    /// let pixels = weights.astype::<u8>(Rounding::NearestEven, Saturation::Saturate);
+    /// assert_eq!(&pixels.data.unwrap()[..], &[2, 0, 255]);
+    ///
+    /// let labels = weights.astype::<i32>(Rounding::Truncate, Saturation::Wrap);
+    /// assert_eq!(&labels.data.unwrap()[..], &[2, -3, 300]);
+    /// ```
+    pub fn astype<T>(&self, rounding: Rounding, saturation: Saturation) -> Collective<T>
+    where
+        T: Num + Bounded,
+    {
+        let data = self.data.as_ref().map(|data| {
+            data.iter()
+                .map(|&value| cast_element::<E, T>(value, rounding, saturation))
+                .collect::<Vec<T>>()
+                .into_boxed_slice()
+        });
+
+        Collective {
+            data,
+            shape: self.shape.clone(),
+        }
+    }
+}
+
+/*
+   Converts a single element from `E` to `T`, see `Collective::astype`.
+*/
+pub(crate) fn cast_element<E, T>(value: E, rounding: Rounding, saturation: Saturation) -> T
+where
+    E: Num,
+    T: Num + Bounded,
+{
+    if E::IS_INTEGER && T::IS_INTEGER {
+        let value = value.to_i128();
+
+        return match saturation {
+            Saturation::Saturate => {
+                T::from_i128(value.clamp(T::min_value().to_i128(), T::max_value().to_i128()))
+            }
+            Saturation::Wrap => T::from_i128(value),
+        };
+    }
+
+    if T::IS_INTEGER {
+        let value = value.to_f64();
+
+        if value.is_nan() {
+            return T::zero();
+        }
+
+        let value = match rounding {
+            Rounding::NearestEven => value.round_ties_even(),
+            Rounding::Truncate => value.trunc(),
+        };
+
+        return match saturation {
+            Saturation::Saturate => T::from_f64(value), // `as` saturates
+            Saturation::Wrap => T::from_i128(value as i128),
+        };
+    }
+
+    // Integers are rounded to the precision of T from their exact value, `to_f64()` would round large ones a first time
+    let value = if E::IS_INTEGER {
+        round_integer_to_precision(value.to_i128(), T::MANTISSA_DIGITS, rounding)
+    } else {
+        value.to_f64()
+    };
+
+    // Infinities are not out of range for a float target, only finite values that overflow it are clamped
+    let value = match saturation {
+        Saturation::Saturate if value.is_finite() => {
+            value.clamp(T::min_value().to_f64(), T::max_value().to_f64())
+        }
+        _ => value,
+    };
+
+    match rounding {
+        Rounding::NearestEven => T::from_f64(value),
+        Rounding::Truncate => T::from_f64_truncated(value),
+    }
+}
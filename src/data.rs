@@ -0,0 +1,335 @@
+/*
+ * Numrs/src/data.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Mini-batches
+   ------------
+   A `DataLoader` cuts a dataset, a Collective of features and one of labels with a sample per index of their leading axis,
+   into mini-batches, instead of calling `Collective::get_slice` with hand computed start and end offsets:
+
+       let mut loader = DataLoader::new(&images, &labels, 64)   // images [60000, 28, 28], labels [60000]
+           .with_shuffle(true)
+           .with_drop_last(true)
+           .with_prefetch(2);
+
+       for epoch in 0..epochs {
+           for (x, y) in loader.epoch() {                         // x [64, 28, 28], y [1, 64]
+               // ... forward, backward, optimizer step
+           }
+       }
+
+   - Every call of `epoch()` goes through the whole dataset once. With shuffling the samples are visited in a new random
+     order each epoch, `with_seed` makes the orders reproducible (epoch k is shuffled with a generator seeded from seed + k).
+   - The last batch holds the remaining samples and may be smaller, unless drop_last discards it.
+   - With prefetch n > 0 a background thread gathers up to n batches ahead of the training loop, so copying the samples
+     overlaps the computation on the previous batch. Batches arrive in the same order as without it. Dropping the epoch
+     early stops the thread, and a panic of the thread is raised again by the epoch instead of ending it early.
+
+   Labels and features hold the same number of samples along their leading axis, a batch of [n, ...] is [batch, ...].
+   1-D data (one class or one value per sample, e.g. from `Tensor::randint` or an IDX label file) has the Dimensions [1, n],
+   it is taken to be n samples and its batches are 1-D again, [1, batch]. The same goes for axes added to it, the one-hot
+   labels [1, n, classes] of `labels::one_hot` give batches [1, batch, classes]. [1, n, ...] is only read as a single
+   sample when the other side holds a single sample too, e.g. the features [1, 784] of one image with its label [1, 1].
+
+   The DataLoader keeps its own copy of the data, the Collectives it was created from can be dropped.
+*/
+
+use super::collective::{dimensions_from_extents, Collective};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::{
+    sync::{mpsc, Arc},
+    thread,
+};
+
+/*
+   The elements of one side of the dataset (features or labels) and how to shape a batch of them.
+*/
+#[derive(Debug, Clone)]
+struct Samples<T> {
+    data: Arc<[T]>,
+    // The extents of a single sample, the axes after the one the samples are split along
+    sample_extents: Vec<usize>,
+    // 1-D data [1, n, ...], split along the second axis, batches are [1, batch, ...]
+    one_dimensional: bool,
+}
+
+impl<T: Copy> Samples<T> {
+    fn sample_len(&self) -> usize {
+        self.sample_extents.iter().product()
+    }
+
+    // The elements of the samples at `indices`, in that order
+    fn gather(&self, indices: &[usize]) -> Vec<T> {
+        let len = self.sample_len();
+        let mut batch = Vec::with_capacity(indices.len() * len);
+
+        for &index in indices {
+            batch.extend_from_slice(&self.data[index * len..(index + 1) * len]);
+        }
+
+        batch
+    }
+
+    fn batch(&self, data: Vec<T>, size: usize) -> Collective<T> {
+        let mut extents = if self.one_dimensional { vec![1, size] } else { vec![size] };
+        extents.extend_from_slice(&self.sample_extents);
+
+        Collective {
+            data: Some(data.into_boxed_slice()),
+            shape: Some(Box::new(dimensions_from_extents(&extents))),
+        }
+    }
+}
+
+/*
+   Mini-batches of (features, labels), see the top of this file.
+*/
+#[derive(Debug, Clone)]
+pub struct DataLoader<E, L> {
+    features: Samples<E>,
+    labels: Samples<L>,
+    samples: usize,
+    batch_size: usize,
+    shuffle: bool,
+    drop_last: bool,
+    prefetch: usize,
+    seed: Option<u64>,
+    // The number of epochs started, for the seeded shuffles
+    epochs: u64,
+}
+
+impl<E, L> DataLoader<E, L>
+where
+    E: Copy + Send + Sync + 'static,
+    L: Copy + Send + Sync + 'static,
+{
+    /// A DataLoader of batches of `batch_size` samples, in order, without prefetching.
+    ///
+    /// # Panics
+    /// If `batch_size` is 0, either Collective has no data allocated or a shape that does not match its elements, or the
+    /// features and the labels do not hold the same number of samples.
+    pub fn new(features: &Collective<E>, labels: &Collective<L>, batch_size: usize) -> Self {
+        if batch_size == 0 {
+            panic!("DataLoader::new(): batch_size is 0");
+        }
+
+        let (feature_data, label_data) = match (&features.data, &labels.data) {
+            (Some(features), Some(labels)) if !features.is_empty() && !labels.is_empty() => (features, labels),
+            _ => panic!("DataLoader::new(): Collective data is not allocated"),
+        };
+
+        let feature_extents = features.extents();
+        let label_extents = labels.extents();
+
+        // extents() would read a shape that does not describe the data as 1-D data, and make up the samples
+        let sides = [
+            ("Features", features.shape.as_ref(), feature_data.len()),
+            ("Labels", labels.shape.as_ref(), label_data.len()),
+        ];
+
+        for (side, shape, len) in sides {
+            if let Some(shape) = shape.filter(|shape| shape.get_n() != len) {
+                panic!(
+                    "DataLoader::new(): {} of shape {:?} do not match the {} elements of their data",
+                    side,
+                    shape.to_vec().iter().map(|&extent| extent as usize).collect::<Vec<usize>>(),
+                    len
+                );
+            }
+        }
+
+        // The first reading of the two shapes that agrees on the number of samples, 1-D before single samples
+        let (samples, features_one_dimensional, labels_one_dimensional) = sample_counts(&feature_extents)
+            .into_iter()
+            .flat_map(|features| sample_counts(&label_extents).into_iter().map(move |labels| (features, labels)))
+            .find(|((feature_samples, _), (label_samples, _))| feature_samples == label_samples)
+            .map(|((samples, features), (_, labels))| (samples, features, labels))
+            .unwrap_or_else(|| {
+                panic!(
+                    "DataLoader::new(): Features of shape {:?} and labels of shape {:?} do not hold the same number of samples",
+                    feature_extents, label_extents
+                )
+            });
+
+        Self {
+            features: Samples {
+                data: Arc::from(&feature_data[..]),
+                sample_extents: feature_extents[if features_one_dimensional { 2 } else { 1 }..].to_vec(),
+                one_dimensional: features_one_dimensional,
+            },
+            labels: Samples {
+                data: Arc::from(&label_data[..]),
+                sample_extents: label_extents[if labels_one_dimensional { 2 } else { 1 }..].to_vec(),
+                one_dimensional: labels_one_dimensional,
+            },
+            samples,
+            batch_size,
+            shuffle: false,
+            drop_last: false,
+            prefetch: 0,
+            seed: None,
+            epochs: 0,
+        }
+    }
+
+    // Fluent setters
+    pub fn with_shuffle(mut self, shuffle: bool) -> Self {
+        self.shuffle = shuffle;
+        self
+    }
+
+    pub fn with_drop_last(mut self, drop_last: bool) -> Self {
+        self.drop_last = drop_last;
+        self
+    }
+
+    // The number of batches the background thread may gather ahead, 0 gathers every batch when it is asked for
+    pub fn with_prefetch(mut self, prefetch: usize) -> Self {
+        self.prefetch = prefetch;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// The number of samples in the dataset.
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// The number of batches of an epoch.
+    pub fn len(&self) -> usize {
+        if self.drop_last {
+            self.samples / self.batch_size
+        } else {
+            self.samples.div_ceil(self.batch_size)
+        }
+    }
+
+    /// Whether an epoch has no batches, only with drop_last and fewer samples than a batch.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Starts an epoch, an iterator over its batches of (features, labels).
+    pub fn epoch(&mut self) -> Epoch<E, L> {
+        let mut order: Vec<usize> = (0..self.samples).collect();
+
+        if self.shuffle {
+            match self.seed {
+                Some(seed) => order.shuffle(&mut StdRng::seed_from_u64(seed.wrapping_add(self.epochs))),
+                None => order.shuffle(&mut rand::thread_rng()),
+            }
+        }
+
+        self.epochs += 1;
+        order.truncate(self.len() * self.batch_size);
+
+        let batches: Vec<Vec<usize>> = order.chunks(self.batch_size).map(|chunk| chunk.to_vec()).collect();
+        let (features, labels) = (self.features.clone(), self.labels.clone());
+
+        let source = if self.prefetch == 0 {
+            Source::Inline(batches.into_iter())
+        } else {
+            // Collectives are not Send (their Dimensions are linked through Rc), the thread sends the gathered elements
+            let (sender, receiver) = mpsc::sync_channel(self.prefetch);
+            let (thread_features, thread_labels) = (features.clone(), labels.clone());
+
+            let worker = thread::spawn(move || {
+                for indices in batches {
+                    let batch = (thread_features.gather(&indices), thread_labels.gather(&indices), indices.len());
+
+                    // The epoch was dropped
+                    if sender.send(batch).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            Source::Prefetched {
+                receiver: Some(receiver),
+                worker: Some(worker),
+            }
+        };
+
+        Epoch { features, labels, source }
+    }
+}
+
+// The numbers of samples `extents` can hold and whether they are 1-D, [1, n, ...] is n 1-D samples or a single sample
+fn sample_counts(extents: &[usize]) -> Vec<(usize, bool)> {
+    if extents[0] == 1 && extents.len() > 1 {
+        vec![(extents[1], true), (1, false)]
+    } else {
+        vec![(extents[0], false)]
+    }
+}
+
+// The gathered features and labels of a batch and its number of samples, as sent by the prefetch thread
+type Gathered<E, L> = (Vec<E>, Vec<L>, usize);
+
+enum Source<E, L> {
+    Inline(std::vec::IntoIter<Vec<usize>>),
+    Prefetched {
+        receiver: Option<mpsc::Receiver<Gathered<E, L>>>,
+        worker: Option<thread::JoinHandle<()>>,
+    },
+}
+
+/*
+   The batches of one epoch, see DataLoader::epoch().
+*/
+pub struct Epoch<E, L> {
+    features: Samples<E>,
+    labels: Samples<L>,
+    source: Source<E, L>,
+}
+
+impl<E: Copy, L: Copy> Iterator for Epoch<E, L> {
+    type Item = (Collective<E>, Collective<L>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (features, labels, size) = match &mut self.source {
+            Source::Inline(batches) => {
+                let indices = batches.next()?;
+
+                (self.features.gather(&indices), self.labels.gather(&indices), indices.len())
+            }
+            Source::Prefetched { receiver, worker } => match receiver.as_ref()?.recv() {
+                Ok(batch) => batch,
+                // The worker has finished the epoch, or panicked before it could
+                Err(_) => {
+                    drop(receiver.take());
+
+                    if let Some(Err(panic)) = worker.take().map(thread::JoinHandle::join) {
+                        std::panic::resume_unwind(panic);
+                    }
+
+                    return None;
+                }
+            },
+        };
+
+        Some((self.features.batch(features, size), self.labels.batch(labels, size)))
+    }
+}
+
+impl<E, L> Drop for Epoch<E, L> {
+    fn drop(&mut self) {
+        if let Source::Prefetched { receiver, worker } = &mut self.source {
+            // Without a receiver the worker's next send fails and it returns
+            drop(receiver.take());
+
+            // A panic of the worker is not lost, unless the epoch is dropped while unwinding (a second panic would abort)
+            if let Some(Err(panic)) = worker.take().map(thread::JoinHandle::join) {
+                if !thread::panicking() {
+                    std::panic::resume_unwind(panic);
+                }
+            }
+        }
+    }
+}
@@ -118,7 +118,7 @@ impl Dimensions {
         while let Some(current_rc) = current_opt {
             let current = current_rc.borrow();
 
-            n = current.columns as f64;
+            n = current.columns;
 
             current_opt = current.next.clone();
         }
@@ -160,7 +160,7 @@ impl Dimensions {
     /// ```
     /// Meaning there are 12 rows total (each having 5 columns).
     pub fn rows(&self) -> f64 {
-        (self.get_number_of_inner_arrays() * self.get_number_of_innermost_arrays())
+        self.get_number_of_inner_arrays() * self.get_number_of_innermost_arrays()
     }
 
     pub fn next(&self) -> Option<Rc<RefCell<Dimensions>>> {
@@ -204,7 +204,7 @@ impl Dimensions {
 
         n *= last_link_columns;
 
-        return (n as usize);
+        n as usize
     }
 
     /// Calculates the number of "inner arrays" in a multidimensional array structure.
@@ -245,7 +245,7 @@ impl Dimensions {
         while let Some(current_rc) = current_opt {
             let current = current_rc.borrow();
 
-            if !current.next.is_none() {
+            if current.next.is_some() {
                 n *= current.rows;
             }
 
@@ -402,8 +402,8 @@ impl Dimensions {
         let mut prev = head.clone();
 
         // Create intermediate nodes (indices 1 .. len-2)
-        for i in 1..vec.len() - 2 {
-            let curr = Rc::new(RefCell::new(Dimensions::new(0.0, vec[i]))); // columns = 0.0, rows = vec[i]
+        for &rows in &vec[1..vec.len() - 2] {
+            let curr = Rc::new(RefCell::new(Dimensions::new(0.0, rows))); // columns = 0.0, rows = rows
             prev.borrow_mut().set_next(Some(curr.clone()));
             curr.borrow_mut().set_prev(Some(prev.clone()));
             prev = curr;
@@ -491,12 +491,14 @@ impl Dimensions {
 ///
 /// # Usage:
 /// ```rust
-/// let dims = Dimensions::new(10, 5);
+/// # use std::{cell::RefCell, rc::Rc};
+/// # use numrs::Dimensions;
+/// let dims = Dimensions::new(5.0, 10.0);
 /// println!("{}", dims); // Prints: "10 × 5"
 ///
-/// let complex_dims = Dimensions::new(0, 3)
+/// let complex_dims = Dimensions::new(0.0, 3.0)
 ///     .with_next(Rc::new(RefCell::new(
-///         Dimensions::new(10, 5)
+///         Dimensions::new(5.0, 10.0)
 ///     )));
 /// println!("{}", complex_dims); // Prints: "3 → 10 × 5"
 /// ```
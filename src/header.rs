@@ -40,4 +40,61 @@ pub enum Axis {
         Results in one-dimensional output regardless of input shape.
      */
     None = 2,    // Flatten all dimensions to scalar
-}
\ No newline at end of file
+}
+
+/*
+    Axis is the convention of the 2-D APIs, get_slice() and the scalers of preprocessing.rs, which see a Collective as a
+    (rows x columns) matrix. It cannot name the third axis of a [batch, n, d] Collective, so the n-dimensional APIs
+    (fold/reduce, axis_iter, softmax, argmax, the autograd reductions) take the numeric axis instead, NumPy's way:
+    a usize counted from the outermost axis (0) in the order of Dimensions::to_vec(), and for reductions an
+    Option<usize> where None combines every element, like NumPy's axis=None. New APIs use the numeric axis.
+*/
+impl Axis {
+    /// The numeric axis of this Axis for a 2-D Collective, Rows is 0, Columns is 1 and None is None.
+    pub fn index(self) -> Option<usize> {
+        match self {
+            Axis::Rows => Some(0),
+            Axis::Columns => Some(1),
+            Axis::None => None,
+        }
+    }
+}
+
+/*
+    How a value that falls between two representable values of the target type is rounded,
+    when a Collective is converted from one element type to another (see `Collective::astype`).
+    It applies to float to integer conversions and to narrowing float conversions (f64 -> f32 -> bf16/f16).
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /*
+        Round to the nearest representable value, on a tie pick the one with an even last digit (IEEE 754 default).
+        2.5 -> 2, 3.5 -> 4, and an f32 mantissa is rounded the same way when it is squeezed into a bf16.
+    */
+    NearestEven,
+
+    /*
+        Drop whatever does not fit, which rounds toward zero.
+        2.7 -> 2, -2.7 -> -2. This is what an `as` cast does for float to integer conversions.
+    */
+    Truncate,
+}
+
+/*
+    What happens to a value that is outside the range of the target type during a conversion (see `Collective::astype`).
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Saturation {
+    /*
+        Clamp to the smallest or largest finite value of the target type.
+        300 -> 255 for u8, 1e6 -> 65504 for f16, -inf -> i32::MIN, NaN -> 0 for integer targets.
+        Float targets keep infinities and NaN, -inf stays -inf for f16.
+    */
+    Saturate,
+
+    /*
+        Let the value overflow the way the target type naturally does.
+        Integers wrap around (300 -> 44 for u8), floats become infinity (1e6 -> inf for f16), NaN -> 0 for integer targets.
+    */
+    Wrap,
+}
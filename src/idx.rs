@@ -0,0 +1,238 @@
+/*
+ * Numrs/src/idx.rs
+ * Q@khaa.pk
+ */
+
+/*
+   IDX files (MNIST, Fashion-MNIST)
+   --------------------------------
+   MNIST and Fashion-MNIST are distributed as IDX files, usually gzipped (train-images-idx3-ubyte.gz, train-labels-idx1-ubyte.gz).
+   The format is a 4 byte magic number, the extents of the dimensions and the raw data, everything big endian:
+
+       0x00 0x00                      2 zero bytes
+       dtype                          0x08 u8, 0x09 i8, 0x0B i16, 0x0C i32, 0x0D f32, 0x0E f64
+       number of dimensions           1 byte
+       extents                        one u32 per dimension
+       data                           extents.product() elements in C (row-major) order
+
+   The extents become a `Dimensions` chain the same way .npy shapes do (see npy.rs). The training images, 60000 x 28 x 28,
+   load as 60000 blocks of 28 rows of 28 columns, the labels (a 1-D file of 60000 entries) as 1 row of 60000 columns.
+
+       let images = Collective::<u8>::load_idx("train-images-idx3-ubyte.gz")?;
+       let pixels = Tensor::normalized(&images, 255.0f32, 0.0);
+
+   Files starting with the gzip magic bytes are decompressed while they are read, whatever their name.
+   `save_idx` compresses when the file name ends in ".gz".
+*/
+
+use super::{
+    collective::Collective,
+    npy::{data_size, read_data, shape_to_dimensions, NpyElement},
+};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/*
+   An element type IDX files can hold. The byte order of IDX data is always big endian.
+*/
+pub trait IdxElement: NpyElement {
+    /// The dtype byte of the magic number.
+    const CODE: u8;
+}
+
+macro_rules! impl_idx_element {
+    ($($t:ty => $code:expr),*) => {
+        $(
+            impl IdxElement for $t {
+                const CODE: u8 = $code;
+            }
+        )*
+    };
+}
+
+impl_idx_element!(u8 => 0x08, i8 => 0x09, i16 => 0x0B, i32 => 0x0C, f32 => 0x0D, f64 => 0x0E);
+
+/*
+   The magic number and extents of an IDX file. `read_header` returns it without loading the data,
+   which tells which element type to load a file with.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdxHeader {
+    pub code: u8,
+    pub shape: Vec<usize>,
+}
+
+impl IdxHeader {
+    // Number of elements, the product of the extents
+    pub fn get_n(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    // Name of the dtype, "u8", "i8", "i16", "i32", "f32" or "f64"
+    pub fn dtype(&self) -> Option<&'static str> {
+        match self.code {
+            0x08 => Some("u8"),
+            0x09 => Some("i8"),
+            0x0B => Some("i16"),
+            0x0C => Some("i32"),
+            0x0D => Some("f32"),
+            0x0E => Some("f64"),
+            _ => None,
+        }
+    }
+}
+
+impl<E> Collective<E>
+where
+    E: IdxElement,
+{
+    /// Loads an IDX file, gzipped or not.
+    ///
+    /// # Errors
+    /// `InvalidData` if the file is not a valid IDX file, or its dtype is not the dtype of `E`.
+    pub fn load_idx<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read_idx(&mut decompressed(path)?)
+    }
+
+    /// Saves the Collective as an IDX file, gzipped if the file name ends in ".gz".
+    pub fn save_idx<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let gzip = path.as_ref().extension().is_some_and(|extension| extension == "gz");
+        let file = BufWriter::new(File::create(path)?);
+
+        if gzip {
+            let mut encoder = GzEncoder::new(file, Compression::default());
+            self.write_idx(&mut encoder)?;
+            encoder.finish()?.flush()
+        } else {
+            let mut writer = file;
+            self.write_idx(&mut writer)?;
+            writer.flush()
+        }
+    }
+
+    /// Reads a Collective in IDX format from any (uncompressed) reader.
+    pub fn read_idx<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let header = read_header_from(reader)?;
+
+        if header.code != E::CODE {
+            return Err(invalid_data(format!(
+                "dtype 0x{:02X} ({}) does not match the element type (0x{:02X}), load it as the matching type and use astype to convert",
+                header.code,
+                header.dtype().unwrap_or("unknown"),
+                E::CODE
+            )));
+        }
+
+        let dimensions = shape_to_dimensions(&header.shape)?;
+
+        let bytes = read_data(reader, data_size(&header.shape, E::SIZE)?)?;
+
+        let elements: Vec<E> = bytes.chunks_exact(E::SIZE).map(E::from_be_slice).collect();
+
+        Ok(Collective::new(
+            Some(elements.into_boxed_slice()),
+            Some(Box::new(dimensions)),
+        ))
+    }
+
+    /// Writes the Collective in IDX format to any writer.
+    pub fn write_idx<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let (data, shape) = match (&self.data, &self.shape) {
+            (Some(data), Some(shape)) => (data, shape),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Collective::write_idx(): Collective data or shape is not allocated",
+                ))
+            }
+        };
+
+        let shape = shape.to_vec();
+
+        if shape.len() > u8::MAX as usize || shape.iter().any(|&extent| extent > u32::MAX as f64) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Collective::write_idx(): IDX files have at most 255 dimensions with extents below 2^32",
+            ));
+        }
+
+        let extents: Vec<usize> = shape.iter().map(|&extent| extent as usize).collect();
+
+        if extents.iter().try_fold(1usize, |n, &extent| n.checked_mul(extent)) != Some(data.len()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Collective::write_idx(): Shape {:?} does not match the {} elements of the data",
+                    extents,
+                    data.len()
+                ),
+            ));
+        }
+
+        let mut bytes = Vec::with_capacity(4 + shape.len() * 4 + data.len() * E::SIZE);
+        bytes.extend_from_slice(&[0, 0, E::CODE, shape.len() as u8]);
+
+        for extent in extents {
+            bytes.extend_from_slice(&(extent as u32).to_be_bytes());
+        }
+
+        for &element in data.iter() {
+            element.extend_be_bytes(&mut bytes);
+        }
+
+        writer.write_all(&bytes)
+    }
+}
+
+/// Reads only the header of an IDX file, gzipped or not.
+pub fn read_header<P: AsRef<Path>>(path: P) -> io::Result<IdxHeader> {
+    read_header_from(&mut decompressed(path)?)
+}
+
+/// Reads the header from a reader positioned at the start of an IDX file, leaving it positioned at the first data byte.
+pub fn read_header_from<R: Read>(reader: &mut R) -> io::Result<IdxHeader> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+
+    if magic[0] != 0 || magic[1] != 0 {
+        return Err(invalid_data("not an IDX file, the magic number must start with two zero bytes".to_string()));
+    }
+
+    let mut header = IdxHeader {
+        code: magic[2],
+        shape: Vec::with_capacity(magic[3] as usize),
+    };
+
+    if header.dtype().is_none() {
+        return Err(invalid_data(format!("unknown IDX dtype 0x{:02X}", header.code)));
+    }
+
+    for _ in 0..magic[3] {
+        let mut extent = [0u8; 4];
+        reader.read_exact(&mut extent)?;
+        header.shape.push(u32::from_be_bytes(extent) as usize);
+    }
+
+    Ok(header)
+}
+
+// Opens a file, decompressing it on the fly if it starts with the gzip magic bytes
+fn decompressed<P: AsRef<Path>>(path: P) -> io::Result<Box<dyn Read>> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        Ok(Box::new(GzDecoder::new(reader)))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
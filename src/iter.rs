@@ -0,0 +1,219 @@
+/*
+ * Numrs/src/iter.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Iterating over Collectives
+   --------------------------
+   The elements of a Collective are stored in row-major order, the last axis of the `Dimensions` chain changes fastest.
+   `iter()` and `iter_mut()` walk them in that order, `indexed_iter()` also yields the coordinates of every element,
+   one index per axis from the outermost to the innermost (the order of `Dimensions::to_vec()`):
+
+       for (coordinates, value) in collective.indexed_iter() {
+           // coordinates == [row, column] for 2-D data
+       }
+
+   `axis_iter(axis)` yields the sub-arrays along an axis, each with that axis removed, e.g. the rows of a matrix
+   (`axis_iter(0)`), its columns (`axis_iter(1)`) or the samples of a batch. A Collective owns its data, so every sub-array
+   is a Collective of its own holding a copy of the elements. Sub-arrays of a single axis get the 1-D shape [1, n].
+
+   A Collective can be collected from an iterator, the result is a 1-D Collective of shape [1, n]:
+
+       let squares: Collective<i32> = (1..=4).map(|i| i * i).collect();
+
+   Unallocated Collectives iterate over nothing.
+*/
+
+use super::{
+    collective::{dimensions_from_extents, strides, Collective},
+    dimensions::Dimensions,
+};
+
+impl<E> Collective<E> {
+    /// Iterates over the elements in row-major order.
+    pub fn iter(&self) -> std::slice::Iter<'_, E> {
+        self.data.as_deref().unwrap_or(&[]).iter()
+    }
+
+    /// Iterates over mutable references to the elements in row-major order.
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, E> {
+        self.data.as_deref_mut().unwrap_or(&mut []).iter_mut()
+    }
+
+    /// Iterates over the elements in row-major order together with their coordinates, outermost axis first.
+    pub fn indexed_iter(&self) -> IndexedIter<'_, E> {
+        let extents = self.extents();
+
+        IndexedIter {
+            elements: self.iter(),
+            coordinates: vec![0; extents.len()],
+            extents,
+        }
+    }
+
+    /// Iterates over the sub-arrays along `axis` (0 is the outermost axis), each a copy with that axis removed.
+    ///
+    /// # Panics
+    /// If `axis` is not an axis of the Collective.
+    pub fn axis_iter(&self, axis: usize) -> AxisIter<'_, E> {
+        let extents = self.extents();
+
+        if axis >= extents.len() {
+            panic!(
+                "Collective::axis_iter(): Axis {} is out of range for a Collective of {} axes",
+                axis,
+                extents.len()
+            );
+        }
+
+        AxisIter {
+            data: self.data.as_deref().unwrap_or(&[]),
+            strides: strides(&extents),
+            extents,
+            axis,
+            position: 0,
+        }
+    }
+}
+
+/*
+   Iterator returned by Collective::indexed_iter().
+*/
+pub struct IndexedIter<'a, E> {
+    elements: std::slice::Iter<'a, E>,
+    extents: Vec<usize>,
+    // Coordinates of the next element
+    coordinates: Vec<usize>,
+}
+
+impl<'a, E> Iterator for IndexedIter<'a, E> {
+    type Item = (Vec<usize>, &'a E);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let element = self.elements.next()?;
+        let coordinates = self.coordinates.clone();
+
+        // Advance like an odometer, the innermost axis first
+        for axis in (0..self.extents.len()).rev() {
+            self.coordinates[axis] += 1;
+
+            if self.coordinates[axis] < self.extents[axis] {
+                break;
+            }
+
+            self.coordinates[axis] = 0;
+        }
+
+        Some((coordinates, element))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.elements.size_hint()
+    }
+}
+
+impl<E> ExactSizeIterator for IndexedIter<'_, E> {}
+
+/*
+   Iterator returned by Collective::axis_iter().
+*/
+pub struct AxisIter<'a, E> {
+    data: &'a [E],
+    extents: Vec<usize>,
+    strides: Vec<usize>,
+    axis: usize,
+    // Index along the axis of the next sub-array
+    position: usize,
+}
+
+impl<E: Copy> Iterator for AxisIter<'_, E> {
+    type Item = Collective<E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() || self.position >= self.extents[self.axis] {
+            return None;
+        }
+
+        // The sub-array is `outer` blocks of `inner` contiguous elements, one block per index of the axes before `axis`
+        let inner = self.strides[self.axis];
+        let outer: usize = self.extents[..self.axis].iter().product();
+        let block = self.extents[self.axis] * inner;
+
+        let mut data = Vec::with_capacity(outer * inner);
+        for index in 0..outer {
+            let start = index * block + self.position * inner;
+            data.extend_from_slice(&self.data[start..start + inner]);
+        }
+
+        self.position += 1;
+
+        let extents: Vec<usize> = self
+            .extents
+            .iter()
+            .enumerate()
+            .filter(|&(axis, _)| axis != self.axis)
+            .map(|(_, &extent)| extent)
+            .collect();
+
+        Some(Collective {
+            data: Some(data.into_boxed_slice()),
+            shape: Some(Box::new(dimensions_from_extents(&extents))),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = if self.data.is_empty() {
+            0
+        } else {
+            self.extents[self.axis] - self.position
+        };
+
+        (remaining, Some(remaining))
+    }
+}
+
+impl<E: Copy> ExactSizeIterator for AxisIter<'_, E> {}
+
+impl<E> IntoIterator for Collective<E> {
+    type Item = E;
+    type IntoIter = std::vec::IntoIter<E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.map(Vec::from).unwrap_or_default().into_iter()
+    }
+}
+
+impl<'a, E> IntoIterator for &'a Collective<E> {
+    type Item = &'a E;
+    type IntoIter = std::slice::Iter<'a, E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, E> IntoIterator for &'a mut Collective<E> {
+    type Item = &'a mut E;
+    type IntoIter = std::slice::IterMut<'a, E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+// Collects into a 1-D Collective of shape [1, n], an empty iterator gives an unallocated Collective
+impl<E> FromIterator<E> for Collective<E> {
+    fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Self {
+        let data: Vec<E> = iter.into_iter().collect();
+
+        if data.is_empty() {
+            return Collective { data: None, shape: None };
+        }
+
+        Collective {
+            shape: Some(Box::new(Dimensions::new(data.len() as f64, 1.0))),
+            data: Some(data.into_boxed_slice()),
+        }
+    }
+}
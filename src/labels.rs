@@ -0,0 +1,158 @@
+/*
+ * Numrs/src/labels.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Class labels
+   ------------
+   `Tensor::randint` (and most datasets) give class labels as a `Collective<i32>`, one class index per sample.
+   Losses like `mse` or a softmax output layer want them as one-hot targets instead, a vector per sample that is 1 at the
+   index of its class and 0 everywhere else:
+
+       let labels = Tensor::randint(0, 3, Dimensions::new(4.0, 1.0));    // [1, 4], e.g. [[2, 0, 1, 2]]
+       let targets: Collective<f32> = labels::one_hot(&labels, 3);        // [1, 4, 3]
+       let predicted = labels::from_one_hot(&targets);                    // [1, 4], the labels again
+
+   - one_hot(labels, num_classes)    appends a trailing axis of `num_classes`, labels of shape [a, b] give [a, b, num_classes]
+   - from_one_hot(one_hot)           the inverse, the index of the largest element along the last axis, which also turns
+                                     predicted probabilities (or logits) into labels
+   - argmax(axis)                    that index along any axis (0 is the outermost, the order of `Dimensions::to_vec()`),
+                                     `None` gives the index of the largest element of the flattened Collective, the first one
+                                     on ties. NaN counts as the largest value, like in NumPy.
+   - bincount(labels, min_length)    how often each of 0, 1, ... occurs, at least `min_length` counts
+   - unique_with_counts(labels)      the distinct labels in ascending order and how often each occurs
+
+   The last two are meant for reporting the class balance of a dataset, or of a split of it:
+
+       let counts = labels::bincount(&train_labels, 10);
+       for (class, count) in counts.iter().enumerate() {
+           println!("class {}: {} samples", class, count);
+       }
+*/
+
+use super::{
+    collective::{dimensions_from_extents, Collective},
+    num::{Float, Num},
+};
+
+impl<E> Collective<E>
+where
+    E: Num,
+{
+    /// The index of the largest element along `axis`, or of the flattened Collective for `None`.
+    /// The first index wins on ties and NaN counts as the largest value.
+    ///
+    /// # Returns
+    /// A new Collective with `axis` removed, or of shape 1 x 1 for `None`. If the Collective has no data allocated, neither has the result.
+    ///
+    /// # Panics
+    /// If `axis` is not an axis of the Collective.
+    pub fn argmax(&self, axis: Option<usize>) -> Collective<i32> {
+        self.lanes("argmax", axis, |lane| {
+            let mut best: Option<(usize, E)> = None;
+
+            for (index, value) in lane.enumerate() {
+                best = match best {
+                    // A NaN is never replaced, and replaces everything else
+                    Some((_, maximum)) if is_nan(maximum) => best,
+                    Some((_, maximum)) if !is_nan(value) && value <= maximum => best,
+                    _ => Some((index, value)),
+                };
+            }
+
+            best.map_or(0, |(index, _)| index as i32)
+        })
+    }
+}
+
+// Only NaN is unordered with itself, this works for the integer element types too
+fn is_nan<E: PartialOrd>(value: E) -> bool {
+    value.partial_cmp(&value).is_none()
+}
+
+/// One-hot encodes the class `labels`, adding a trailing axis of `num_classes`.
+///
+/// # Returns
+/// A new Collective of 0s with a 1 at the class of every label. If `labels` has no data allocated, neither has the result.
+///
+/// # Panics
+/// If a label is negative or not less than `num_classes`.
+pub fn one_hot<E: Float>(labels: &Collective<i32>, num_classes: usize) -> Collective<E> {
+    let data = match &labels.data {
+        Some(data) if !data.is_empty() => data,
+        _ => return Collective { data: None, shape: None },
+    };
+
+    let mut encoded = vec![E::zero(); data.len() * num_classes];
+    for (sample, &label) in data.iter().enumerate() {
+        if label < 0 || label as usize >= num_classes {
+            panic!("labels::one_hot(): Label {} is out of range for {} classes", label, num_classes);
+        }
+
+        encoded[sample * num_classes + label as usize] = E::one();
+    }
+
+    let mut extents = labels.extents();
+    extents.push(num_classes);
+
+    Collective {
+        data: Some(encoded.into_boxed_slice()),
+        shape: Some(Box::new(dimensions_from_extents(&extents))),
+    }
+}
+
+/// The class labels of one-hot vectors (or of probabilities and logits), the index of the largest element along the last axis.
+///
+/// # Returns
+/// A new Collective with the last axis removed. If `one_hot` has no data allocated, neither has the result.
+pub fn from_one_hot<E: Num>(one_hot: &Collective<E>) -> Collective<i32> {
+    one_hot.argmax(Some(one_hot.extents().len() - 1))
+}
+
+/// How often each label occurs, the count of label `i` at index `i`.
+///
+/// # Returns
+/// A vector of at least `min_length` counts, longer if there are labels of `min_length` or more.
+///
+/// # Panics
+/// If a label is negative.
+pub fn bincount(labels: &Collective<i32>, min_length: usize) -> Vec<usize> {
+    let data = labels.data.as_deref().unwrap_or(&[]);
+
+    if let Some(&label) = data.iter().find(|&&label| label < 0) {
+        panic!("labels::bincount(): Label {} is negative", label);
+    }
+
+    let length = data.iter().map(|&label| label as usize + 1).max().unwrap_or(0).max(min_length);
+
+    let mut counts = vec![0; length];
+    for &label in data {
+        counts[label as usize] += 1;
+    }
+
+    counts
+}
+
+/// The distinct labels in ascending order and how often each of them occurs.
+///
+/// # Returns
+/// The labels and their counts, empty if `labels` has no data allocated.
+pub fn unique_with_counts(labels: &Collective<i32>) -> (Vec<i32>, Vec<usize>) {
+    let mut sorted = labels.data.as_deref().unwrap_or(&[]).to_vec();
+    sorted.sort_unstable();
+
+    let mut unique: Vec<i32> = Vec::new();
+    let mut counts: Vec<usize> = Vec::new();
+
+    for label in sorted {
+        if unique.last() == Some(&label) {
+            *counts.last_mut().unwrap() += 1;
+        } else {
+            unique.push(label);
+            counts.push(1);
+        }
+    }
+
+    (unique, counts)
+}
@@ -3,16 +3,45 @@
    Q@khaa.pk
 */
 
-#![feature(f16)] // The type `f16` is unstable. To enable this feature, we need to enable the f16 feature at the root level of the crate and install nightely build of rustc
-
+pub mod activations;
+pub mod apply;
+pub mod approx;
+pub mod autograd;
 pub mod collective;
+pub mod data;
 pub mod dimensions;
 pub mod header;
+pub mod idx;
+pub mod iter;
+pub mod labels;
+pub mod linalg;
+pub mod losses;
+pub mod mmap;
+pub mod npy;
+pub mod npz;
+pub mod nn;
 pub mod num;
+pub mod optim;
+pub mod pod;
+pub mod preprocessing;
+pub mod print;
+#[cfg(feature = "safetensors")] // Opt-in, so serde_json is only built for the crates that read .safetensors files
+pub mod safetensors;
+#[cfg(feature = "serde")] // Serialize/Deserialize impls for Dimensions and Collective (see serialize.rs)
+mod serialize;
+pub mod softmax;
+pub mod text;
+pub mod ufunc;
+#[cfg(any(feature = "f16", feature = "bf16"))] // Half-precision support is opt-in, so the core library builds on stable Rust (see precision.rs)
+pub mod precision;
 
 // Re-export main types at crate level for easier importing
 pub use dimensions::Dimensions;
-pub use header::Axis;
+pub use header::{Axis, Rounding, Saturation};
+#[cfg(feature = "bf16")]
+pub use precision::bf16;
+#[cfg(feature = "f16")]
+pub use precision::f16;
 
 //pub use collective::Collective; // Uncomment when you have this module
 
@@ -22,7 +51,7 @@ mod tests {
 
     #[test]
     fn test_basic_functionality() {
-        let dim = Dimensions::new(5, 10);
+        let dim = Dimensions::new(5.0, 10.0);
         assert!(dim.is_valid());
     }
 
@@ -0,0 +1,124 @@
+/*
+ * Numrs/src/linalg.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Matrix products
+   ---------------
+   The innermost two axes of a Collective are its rows and columns, any axes before them count a stack (batch) of matrices:
+
+       let outputs = inputs.matmul(&weights);        // [batch, in] x [in, out] -> [batch, out]
+       let scores = queries.matmul(&keys_t);         // [heads, n, d] x [heads, d, n] -> [heads, n, n]
+       let keys_t = keys.transpose();                // [heads, n, d] -> [heads, d, n]
+
+   `matmul` multiplies [..., m, k] by either a single [k, n] matrix, which every matrix of the stack is multiplied by
+   (the weights of a layer), or by a stack [..., k, n] of the same leading extents, matrix by matrix.
+   A 1-D Collective is the single row [1, n].
+
+   The products are accumulated in f64 whatever the element type.
+*/
+
+use super::{
+    collective::{dimensions_from_extents, Collective},
+    num::Float,
+};
+
+impl<E> Collective<E>
+where
+    E: Float,
+{
+    /// The matrix product of the innermost two axes of `self` and `other`.
+    ///
+    /// # Returns
+    /// A new Collective of shape [..., m, n]. If either Collective has no data allocated, neither has the result.
+    ///
+    /// # Panics
+    /// If the columns of `self` are not the rows of `other`, or `other` is a stack of matrices whose leading extents
+    /// differ from those of `self`.
+    pub fn matmul(&self, other: &Collective<E>) -> Collective<E> {
+        let (left, right) = match (&self.data, &other.data) {
+            (Some(left), Some(right)) if !left.is_empty() && !right.is_empty() => (left, right),
+            _ => return Collective { data: None, shape: None },
+        };
+
+        let (left_extents, right_extents) = (self.extents(), other.extents());
+        let (m, k) = innermost(&left_extents);
+        let (rows, n) = innermost(&right_extents);
+
+        let batch = &left_extents[..left_extents.len() - 2];
+        let shared = right_extents.len() == 2;
+
+        if rows != k || !(shared || right_extents[..right_extents.len() - 2] == *batch) {
+            panic!(
+                "Collective::matmul(): Shapes {:?} and {:?} are not aligned",
+                left_extents, right_extents
+            );
+        }
+
+        let count: usize = batch.iter().product();
+        let mut result = vec![0.0f64; count * m * n];
+
+        for matrix in 0..count {
+            let a = &left[matrix * m * k..(matrix + 1) * m * k];
+            let b = if shared { &right[..] } else { &right[matrix * k * n..(matrix + 1) * k * n] };
+            let c = &mut result[matrix * m * n..(matrix + 1) * m * n];
+
+            // i, p, j order walks both b and c along their rows
+            for i in 0..m {
+                for p in 0..k {
+                    let a_ip = a[i * k + p].to_f64();
+
+                    for j in 0..n {
+                        c[i * n + j] += a_ip * b[p * n + j].to_f64();
+                    }
+                }
+            }
+        }
+
+        let mut extents = batch.to_vec();
+        extents.extend([m, n]);
+
+        Collective {
+            data: Some(result.into_iter().map(E::from_f64).collect::<Vec<E>>().into_boxed_slice()),
+            shape: Some(Box::new(dimensions_from_extents(&extents))),
+        }
+    }
+
+    /// Swaps the innermost two axes, the transpose of every matrix of the stack.
+    ///
+    /// # Returns
+    /// A new Collective of shape [..., n, m]. If the Collective has no data allocated, neither has the result.
+    pub fn transpose(&self) -> Collective<E> {
+        let data = match &self.data {
+            Some(data) if !data.is_empty() => data,
+            _ => return Collective { data: None, shape: None },
+        };
+
+        let extents = self.extents();
+        let (m, n) = innermost(&extents);
+
+        let mut result = Vec::with_capacity(data.len());
+        for matrix in data.chunks(m * n) {
+            for j in 0..n {
+                for i in 0..m {
+                    result.push(matrix[i * n + j]);
+                }
+            }
+        }
+
+        let mut transposed = extents;
+        let axes = transposed.len();
+        transposed.swap(axes - 2, axes - 1);
+
+        Collective {
+            data: Some(result.into_boxed_slice()),
+            shape: Some(Box::new(dimensions_from_extents(&transposed))),
+        }
+    }
+}
+
+// The rows and columns of the innermost matrix, extents always have at least two axes
+fn innermost(extents: &[usize]) -> (usize, usize) {
+    (extents[extents.len() - 2], extents[extents.len() - 1])
+}
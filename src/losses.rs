@@ -0,0 +1,326 @@
+/*
+ * Numrs/src/losses.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Loss functions
+   --------------
+   Every loss returns its value together with its gradient with respect to the input (the logits or predictions),
+   so a training step needs no autograd for the last layer:
+
+       let loss = losses::cross_entropy(&logits, &labels, &CrossEntropyOptions::new().with_label_smoothing(0.1));
+       println!("loss {}", loss.item());
+       // loss.gradient has the shape of the logits, feed it to the backward pass
+
+   The losses of the individual elements (or samples, for cross-entropy) are reduced according to `Reduction`:
+   - Mean, the average (the default), the gradient is scaled by 1 / count
+   - Sum, the total
+   - None, no reduction, the value has one loss per element (per sample), the gradient is that of their sum
+
+   Mean and Sum give a 1 x 1 value.
+
+   - cross_entropy(logits, targets)                 logits [..., classes], integer class targets [...] (e.g. from `Tensor::randint`)
+                                                     Label smoothing mixes the one-hot target with the uniform distribution,
+                                                     targets equal to the ignore index contribute nothing, and are not counted by Mean.
+   - binary_cross_entropy_with_logits(logits, y)    y in [0, 1], computed as max(x, 0) - x * y + ln(1 + e^-|x|)
+   - mse(x, y)                                       (x - y)^2
+   - mae(x, y)                                       |x - y|, the gradient at x == y is 0
+   - huber(x, y, delta)                              0.5 * (x - y)^2 within delta of the target, delta * (|x - y| - 0.5 * delta) beyond
+   - kl_div(x, y)                                    y * (ln y - x), x are log-probabilities and y probabilities (PyTorch's kl_div),
+                                                     0 where y is 0. Mean divides by the number of elements, for the mathematical
+                                                     KL divergence of each sample use Sum and divide by the batch size.
+
+   Everything is computed in f64 and converted back to the element type. The input and target shapes must be equal.
+*/
+
+use super::{
+    activations::sigmoid_f64,
+    collective::{dimensions_from_extents, Collective},
+    num::Float,
+};
+
+/*
+   How the losses of the individual elements are combined.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Reduction {
+    #[default]
+    Mean,
+    Sum,
+    None,
+}
+
+/*
+   The value of a loss and its gradient with respect to the input.
+*/
+pub struct Loss<E> {
+    // 1 x 1 for Reduction::Mean and Reduction::Sum, one loss per element (or sample) for Reduction::None
+    pub value: Collective<E>,
+    // Of the shape of the input
+    pub gradient: Collective<E>,
+}
+
+impl<E: Copy> Loss<E> {
+    /// The reduced loss.
+    ///
+    /// # Panics
+    /// If the loss was not reduced and has more than one element.
+    pub fn item(&self) -> E {
+        match self.value.data.as_deref() {
+            Some([value]) => *value,
+            _ => panic!("Loss::item(): The loss has {} elements, only a reduced loss has a single value", self.value.iter().count()),
+        }
+    }
+}
+
+/*
+   Options of cross_entropy(). The defaults are PyTorch's: mean reduction, no label smoothing, no ignored targets.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrossEntropyOptions {
+    pub reduction: Reduction,
+    // In [0, 1], the weight of the uniform distribution mixed into the one-hot targets
+    pub label_smoothing: f64,
+    // Targets with this value (e.g. the padding of sequences) contribute no loss and no gradient
+    pub ignore_index: Option<i32>,
+}
+
+impl CrossEntropyOptions {
+    pub const fn new() -> Self {
+        Self {
+            reduction: Reduction::Mean,
+            label_smoothing: 0.0,
+            ignore_index: None,
+        }
+    }
+
+    // Fluent setters
+    pub fn with_reduction(mut self, reduction: Reduction) -> Self {
+        self.reduction = reduction;
+        self
+    }
+
+    /// # Panics
+    /// If `label_smoothing` is not in [0, 1].
+    pub fn with_label_smoothing(mut self, label_smoothing: f64) -> Self {
+        if !(0.0..=1.0).contains(&label_smoothing) {
+            panic!("CrossEntropyOptions::with_label_smoothing(): {} is not in [0, 1]", label_smoothing);
+        }
+
+        self.label_smoothing = label_smoothing;
+        self
+    }
+
+    pub fn with_ignore_index(mut self, ignore_index: i32) -> Self {
+        self.ignore_index = Some(ignore_index);
+        self
+    }
+}
+
+impl Default for CrossEntropyOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cross-entropy between the softmax of `logits` along their last axis and the integer class `targets`.
+///
+/// # Returns
+/// The loss and its gradient with respect to the logits, softmax(logits) minus the (smoothed) one-hot targets.
+/// With Reduction::None the value has the shape of the targets.
+///
+/// # Panics
+/// If `targets` does not hold one class per row of `logits`, or a target other than the ignore index is not a class.
+pub fn cross_entropy<E: Float>(logits: &Collective<E>, targets: &Collective<i32>, options: &CrossEntropyOptions) -> Loss<E> {
+    let (logit_data, target_data) = match (&logits.data, &targets.data) {
+        (Some(logits), Some(targets)) => (logits, targets),
+        _ => panic!("losses::cross_entropy(): Collective data is not allocated"),
+    };
+
+    let extents = logits.extents();
+    let classes = *extents.last().unwrap();
+
+    if target_data.len() * classes != logit_data.len() {
+        panic!(
+            "losses::cross_entropy(): Targets of shape {:?} do not hold one class per row of logits of shape {:?}",
+            targets.extents(),
+            extents
+        );
+    }
+
+    let log_probabilities = logits.map(|x| x.to_f64()).log_softmax(extents.len() - 1, None);
+    let log_probabilities = log_probabilities.data.as_ref().unwrap();
+
+    let smoothing = options.label_smoothing;
+    let mut losses = Vec::with_capacity(target_data.len());
+    let mut gradient = vec![0.0f64; logit_data.len()];
+    let mut count = 0;
+
+    for (sample, &target) in target_data.iter().enumerate() {
+        if options.ignore_index == Some(target) {
+            losses.push(0.0);
+            continue;
+        }
+
+        if target < 0 || target as usize >= classes {
+            panic!("losses::cross_entropy(): Target {} is out of range for {} classes", target, classes);
+        }
+
+        count += 1;
+
+        let row = sample * classes..(sample + 1) * classes;
+        let mut loss = 0.0;
+
+        for (class, index) in row.enumerate() {
+            let log_probability = log_probabilities[index];
+            let expected = smoothing / classes as f64 + if class == target as usize { 1.0 - smoothing } else { 0.0 };
+
+            if expected > 0.0 {
+                loss -= expected * log_probability;
+            }
+            gradient[index] = log_probability.exp() - expected;
+        }
+
+        losses.push(loss);
+    }
+
+    reduce(losses, gradient, count, options.reduction, targets, logits)
+}
+
+/// Binary cross-entropy between `sigmoid(logits)` and the `targets` in [0, 1], without computing the sigmoid's logarithm.
+///
+/// # Panics
+/// If the shapes differ.
+pub fn binary_cross_entropy_with_logits<E: Float>(logits: &Collective<E>, targets: &Collective<E>, reduction: Reduction) -> Loss<E> {
+    elementwise("binary_cross_entropy_with_logits", logits, targets, reduction, |x, y| {
+        (x.max(0.0) - x * y + (-x.abs()).exp().ln_1p(), sigmoid_f64(x) - y)
+    })
+}
+
+/// Mean squared error, `(input - target)^2`.
+///
+/// # Panics
+/// If the shapes differ.
+pub fn mse<E: Float>(input: &Collective<E>, target: &Collective<E>, reduction: Reduction) -> Loss<E> {
+    elementwise("mse", input, target, reduction, |x, y| ((x - y) * (x - y), 2.0 * (x - y)))
+}
+
+/// Mean absolute error, `|input - target|`.
+///
+/// # Panics
+/// If the shapes differ.
+pub fn mae<E: Float>(input: &Collective<E>, target: &Collective<E>, reduction: Reduction) -> Loss<E> {
+    elementwise("mae", input, target, reduction, |x, y| {
+        let difference = x - y;
+
+        (difference.abs(), if difference == 0.0 { 0.0 } else { difference.signum() })
+    })
+}
+
+/// Huber loss, quadratic within `delta` of the target and linear beyond.
+///
+/// # Panics
+/// If the shapes differ or `delta` is not positive.
+pub fn huber<E: Float>(input: &Collective<E>, target: &Collective<E>, delta: E, reduction: Reduction) -> Loss<E> {
+    let delta = delta.to_f64();
+
+    if delta.is_nan() || delta <= 0.0 {
+        panic!("losses::huber(): delta {} is not positive", delta);
+    }
+
+    elementwise("huber", input, target, reduction, |x, y| {
+        let difference = x - y;
+
+        if difference.abs() <= delta {
+            (0.5 * difference * difference, difference)
+        } else {
+            (delta * (difference.abs() - 0.5 * delta), delta * difference.signum())
+        }
+    })
+}
+
+/// Kullback-Leibler divergence of the probabilities `target` from the log-probabilities `input`, `target * (ln target - input)`.
+///
+/// # Panics
+/// If the shapes differ.
+pub fn kl_div<E: Float>(input: &Collective<E>, target: &Collective<E>, reduction: Reduction) -> Loss<E> {
+    elementwise("kl_div", input, target, reduction, |x, y| {
+        if y > 0.0 {
+            (y * (y.ln() - x), -y)
+        } else {
+            (0.0, 0.0)
+        }
+    })
+}
+
+/*
+   A loss of one input and one target element, `f` returns the loss and its derivative with respect to the input.
+*/
+fn elementwise<E: Float>(
+    name: &str,
+    input: &Collective<E>,
+    target: &Collective<E>,
+    reduction: Reduction,
+    f: impl Fn(f64, f64) -> (f64, f64),
+) -> Loss<E> {
+    let (input_data, target_data) = match (&input.data, &target.data) {
+        (Some(input), Some(target)) => (input, target),
+        _ => panic!("losses::{}(): Collective data is not allocated", name),
+    };
+
+    if input.extents() != target.extents() {
+        panic!(
+            "losses::{}(): Shapes {:?} and {:?} differ",
+            name,
+            input.extents(),
+            target.extents()
+        );
+    }
+
+    let (losses, gradient): (Vec<f64>, Vec<f64>) = input_data
+        .iter()
+        .zip(target_data.iter())
+        .map(|(&x, &y)| f(x.to_f64(), y.to_f64()))
+        .unzip();
+
+    let count = losses.len();
+
+    reduce(losses, gradient, count, reduction, input, input)
+}
+
+/*
+   Reduces the losses, `count` of which are counted by the mean, and scales the gradient to match.
+   With Reduction::None the losses get the shape of `value_shape`, the gradient always gets that of `gradient_shape`.
+*/
+fn reduce<E: Float, V, G>(
+    losses: Vec<f64>,
+    mut gradient: Vec<f64>,
+    count: usize,
+    reduction: Reduction,
+    value_shape: &Collective<V>,
+    gradient_shape: &Collective<G>,
+) -> Loss<E> {
+    let to_collective = |data: Vec<f64>, extents: Vec<usize>| Collective {
+        data: Some(data.into_iter().map(E::from_f64).collect::<Vec<E>>().into_boxed_slice()),
+        shape: Some(Box::new(dimensions_from_extents(&extents))),
+    };
+
+    let value = match reduction {
+        Reduction::None => to_collective(losses, value_shape.extents()),
+        Reduction::Sum => to_collective(vec![losses.iter().sum()], vec![1, 1]),
+        Reduction::Mean => {
+            // Every target ignored, like PyTorch the mean is NaN and nothing is learned
+            let scale = if count == 0 { f64::NAN } else { 1.0 / count as f64 };
+
+            gradient.iter_mut().for_each(|g| *g = if count == 0 { 0.0 } else { *g * scale });
+
+            to_collective(vec![losses.iter().sum::<f64>() * scale], vec![1, 1])
+        }
+    };
+
+    Loss {
+        value,
+        gradient: to_collective(gradient, gradient_shape.extents()),
+    }
+}
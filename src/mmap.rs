@@ -0,0 +1,271 @@
+/*
+ * Numrs/src/mmap.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Memory-mapped Collectives
+   -------------------------
+   A `Collective` owns its elements in a `Box<[E]>`, so loading a dataset of tens of GB needs tens of GB of RAM.
+   A `MappedCollective` is a read-only view of a file mapped into memory instead. Nothing is read up front, the OS pages the parts
+   of the file that are touched in (and evicts them again under memory pressure), so a training loop can pull mini-batches out of a
+   dataset larger than RAM:
+
+       let images = MappedCollective::<u8>::open_npy("train_images.npy")?;   // shape 60000 x 28 x 28
+       let batch: Collective<u8> = images.batch(0, 64);                       // shape 64 x 28 x 28, only these rows are read
+
+   A 1-D file, e.g. the (60000,) labels next to those images, is batched the same way, by its entries:
+
+       let labels = MappedCollective::<u8>::open_npy("train_labels.npy")?;   // shape 1 x 60000, len() is 60000
+       let batch: Collective<u8> = labels.batch(0, 64);                       // shape 1 x 64
+
+   Two file layouts are supported:
+   - .npy files (see npy.rs) in C order and native byte order, which is what `numpy.save` writes on any x86 or ARM machine.
+     Fortran ordered or byte swapped files cannot be viewed without converting every element, load them with `load_npy`.
+   - Raw binary files holding nothing but native endian elements in C order, possibly after a header of `offset` bytes.
+     The file carries no shape, so it is given explicitly as `Dimensions`. `Collective::save_raw` writes such files.
+
+   The elements are viewed in place, which needs them to be aligned in the file: .npy data starts at a multiple of 64 bytes
+   and the offset into a raw file must be a multiple of the element alignment (its size, for every element type).
+
+   As with any memory map, the file must not be truncated or modified by another process while it is mapped.
+*/
+
+use super::{
+    collective::Collective,
+    dimensions::Dimensions,
+    npy::{self, check_descr, ByteOrder, NpyElement},
+    pod::Pod,
+};
+use memmap2::Mmap;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    marker::PhantomData,
+    mem,
+    ops::Index,
+    path::Path,
+    slice,
+};
+
+pub struct MappedCollective<E> {
+    map: Mmap,
+    // Byte offset of the first element in the mapping
+    offset: usize,
+    shape: Dimensions,
+    // Dimensions have at least two axes, a 1-D file of n entries is [1, n] and its leading axis is the n entries
+    one_dimensional: bool,
+    element: PhantomData<E>,
+}
+
+impl<E> MappedCollective<E>
+where
+    E: NpyElement,
+{
+    /// Maps an .npy file.
+    ///
+    /// # Errors
+    /// `InvalidData` if the file is not a valid .npy file, its dtype is not the dtype of `E`, it is Fortran ordered or byte swapped,
+    /// or it is shorter than its header says.
+    pub fn open_npy<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let map = map(path)?;
+
+        let mut reader = &map[..];
+        let header = npy::read_header_from(&mut reader)?;
+        let offset = map.len() - reader.len();
+
+        if check_descr::<E>(&header.descr)? != ByteOrder::NATIVE && E::SIZE > 1 {
+            return Err(invalid_data(format!(
+                "dtype '{}' is not in native byte order and cannot be mapped, use Collective::load_npy",
+                header.descr
+            )));
+        }
+
+        if header.fortran_order {
+            return Err(invalid_data(
+                "Fortran ordered data cannot be mapped, use Collective::load_npy".to_string(),
+            ));
+        }
+
+        Self::from_map(map, offset, npy::shape_to_dimensions(&header.shape)?, header.shape.len() == 1)
+    }
+}
+
+impl<E> MappedCollective<E>
+where
+    E: Pod,
+{
+    /// Maps a raw binary file of native endian elements in C order, starting `offset` bytes into the file.
+    /// A `shape` of [1, n] is taken to be n entries (e.g. labels), as for 1-D .npy files.
+    ///
+    /// # Errors
+    /// `InvalidInput` if `offset` is not a multiple of the element alignment, `InvalidData` if the file is too short for `shape`.
+    pub fn open_raw<P: AsRef<Path>>(path: P, shape: Dimensions, offset: usize) -> io::Result<Self> {
+        if !offset.is_multiple_of(mem::align_of::<E>()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "MappedCollective::open_raw(): offset {} is not a multiple of the element alignment {}",
+                    offset,
+                    mem::align_of::<E>()
+                ),
+            ));
+        }
+
+        let extents = shape.to_vec();
+        let one_dimensional = extents.len() == 2 && extents[0] == 1.0;
+
+        Self::from_map(map(path)?, offset, shape, one_dimensional)
+    }
+
+    fn from_map(map: Mmap, offset: usize, shape: Dimensions, one_dimensional: bool) -> io::Result<Self> {
+        // The shape comes from the file header, its size may not even fit in a usize
+        let needed = shape.get_n().saturating_mul(E::SIZE);
+
+        if map.len() < offset || map.len() - offset < needed {
+            return Err(invalid_data(format!(
+                "file has {} bytes of data, shape {:?} needs {}",
+                map.len().saturating_sub(offset),
+                shape.to_vec(),
+                needed
+            )));
+        }
+
+        // The mapping itself is page aligned, so this only fails for an .npy header of unusual length
+        if !(map.as_ptr() as usize + offset).is_multiple_of(mem::align_of::<E>()) {
+            return Err(invalid_data("data is not aligned to the element size".to_string()));
+        }
+
+        Ok(Self {
+            map,
+            offset,
+            shape,
+            one_dimensional,
+            element: PhantomData,
+        })
+    }
+
+    pub fn shape(&self) -> &Dimensions {
+        &self.shape
+    }
+
+    // Total number of elements
+    pub fn get_n(&self) -> usize {
+        self.shape.get_n()
+    }
+
+    // Extent of the leading (outermost) axis, e.g. the number of samples, every element for a 1-D file
+    pub fn len(&self) -> usize {
+        if self.one_dimensional {
+            self.get_n()
+        } else {
+            self.shape.to_vec()[0] as usize
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.get_n() == 0
+    }
+
+    /// All elements, viewed in place.
+    pub fn as_slice(&self) -> &[E] {
+        let start = self.map[self.offset..].as_ptr() as *const E;
+
+        // SAFETY: `from_map` checked that the mapping holds get_n() elements after `offset` and that they are aligned.
+        // `Pod` is sealed and only implemented for plain old data, so any bytes are valid elements.
+        // The slice borrows `self`, which keeps the mapping alive.
+        unsafe { slice::from_raw_parts(start, self.get_n()) }
+    }
+
+    /// Copies entries `start..end` of the leading axis into an owned Collective, e.g. a mini-batch of samples.
+    /// Only the pages holding these entries are read from the file.
+    ///
+    /// # Panics
+    /// If `start..end` is empty or extends past `len()`.
+    pub fn batch(&self, start: usize, end: usize) -> Collective<E> {
+        if start >= end || end > self.len() {
+            panic!(
+                "MappedCollective::batch(): Range {}..{} is empty or out of bounds for a leading axis of {}",
+                start,
+                end,
+                self.len()
+            );
+        }
+
+        let stride = self.get_n() / self.len();
+
+        let shape = if self.one_dimensional {
+            Dimensions::new((end - start) as f64, 1.0)
+        } else {
+            let mut extents = self.shape.to_vec();
+            extents[0] = (end - start) as f64;
+
+            Dimensions::from_vec(extents)
+        };
+
+        Collective::new(
+            Some(self.as_slice()[start * stride..end * stride].to_vec().into_boxed_slice()),
+            Some(Box::new(shape)),
+        )
+    }
+
+    /// Copies every element into an owned Collective.
+    pub fn to_collective(&self) -> Collective<E> {
+        Collective::new(
+            Some(self.as_slice().to_vec().into_boxed_slice()),
+            Some(Box::new(self.shape.clone())),
+        )
+    }
+}
+
+impl<E> Index<usize> for MappedCollective<E>
+where
+    E: Pod,
+{
+    type Output = E;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.as_slice()[index]
+    }
+}
+
+impl<E> Collective<E>
+where
+    E: Pod,
+{
+    /// Saves the elements as a raw binary file, native endian and in C order, with no header.
+    /// The shape is not saved, it has to be passed to `MappedCollective::open_raw`.
+    pub fn save_raw<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let data = self.data.as_ref().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Collective::save_raw(): Collective data is not allocated",
+            )
+        })?;
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        let mut buffer = Vec::with_capacity(data.len() * E::SIZE);
+
+        for &element in data.iter() {
+            match ByteOrder::NATIVE {
+                ByteOrder::LittleEndian => element.extend_le_bytes(&mut buffer),
+                ByteOrder::BigEndian => element.extend_be_bytes(&mut buffer),
+            }
+        }
+
+        writer.write_all(&buffer)?;
+        writer.flush()
+    }
+}
+
+fn map<P: AsRef<Path>>(path: P) -> io::Result<Mmap> {
+    let file = File::open(path)?;
+
+    // SAFETY: The mapping is read-only. Like every memory map it is only sound while no other process truncates or modifies
+    // the file, which is documented at the top of this module.
+    unsafe { Mmap::map(&file) }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,689 @@
+/*
+ * Numrs/src/nn.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Neural network layers
+   ---------------------
+   A `Layer` maps an input Collective to an output Collective (`forward`) and, given the gradient of the loss with respect to
+   that output, computes the gradient with respect to its input (`backward`), adding the gradients of its own weights to
+   what it holds. The gradients are written by hand, without the tape of autograd.rs, which keeps a training step to the
+   allocations of the layers themselves:
+
+       let mut model = Sequential::new()
+           .with(Linear::<f32>::new(784, 128))
+           .with(ActivationLayer::new(Activation::ReLU))
+           .with(Dropout::new(0.1))
+           .with(Linear::new(128, 10));
+
+       let mut optimizer = Adam::new(1e-3);
+
+       for (x, y) in loader.epoch() {
+           let logits = model.forward(&x);
+           let loss = losses::cross_entropy(&logits, &y, &CrossEntropyOptions::new());
+
+           model.zero_grad();
+           model.backward(&loss.gradient);
+           nn::step(&mut model, &mut optimizer);
+       }
+
+       model.set_training(false);   // Dropout passes everything through for evaluation
+
+   - Linear(in, out)              y = x W + b, x [..., in], W [in, out], b [1, out]
+   - Embedding(count, dim)        looks up row i of the weights [count, dim] for every index i, indices [...] -> [..., dim]
+   - LayerNorm(features)          normalizes every vector along the last axis to zero mean and unit variance,
+                                  then scales and shifts it by the learned gain (ones) and bias (zeros)
+   - Dropout(p)                   zeroes elements with probability p while training and scales the rest by 1 / (1 - p)
+   - ActivationLayer(activation)  an element-wise function of activations.rs
+   - Sequential                   the layers one after the other
+
+   The weights are created with the `Tensor` constructors of num.rs: the weights of Linear are drawn from Tensor::randn
+   and mapped to U(-1/sqrt(in), 1/sqrt(in)) (PyTorch's range), its bias starts at Tensor::zeros, embeddings are drawn
+   from U(-1, 1) and LayerNorm starts at Tensor::ones and Tensor::zeros.
+
+   backward() uses what the last forward() remembered, so every forward() of a training step must be followed by its
+   backward() before the next forward(). Gradients accumulate over backward() calls until `zero_grad()`.
+*/
+
+use super::{
+    activations::Activation,
+    collective::{dimensions_from_extents, Collective},
+    dimensions::Dimensions,
+    num::{Float, Tensor},
+    optim::Optimizer,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/*
+   A learned weight and its accumulated gradient, of the same shape.
+*/
+pub struct Parameter<'a, E> {
+    pub value: &'a mut Collective<E>,
+    pub gradient: &'a mut Collective<E>,
+}
+
+/*
+   A differentiable building block of a model, see the top of this file.
+*/
+pub trait Layer<E: Float> {
+    /// Computes the output of the layer, remembering what backward() needs.
+    fn forward(&mut self, input: &Collective<E>) -> Collective<E>;
+
+    /// Adds the gradients of the weights to their accumulated gradients, given `gradient`, the gradient of the loss with
+    /// respect to the output of the last forward().
+    ///
+    /// # Returns
+    /// The gradient of the loss with respect to the input of the last forward().
+    ///
+    /// # Panics
+    /// If forward() has not been called, or `gradient` does not have the shape of its output.
+    fn backward(&mut self, gradient: &Collective<E>) -> Collective<E>;
+
+    /// The weights of the layer with their gradients, in the same order every time.
+    fn parameters(&mut self) -> Vec<Parameter<'_, E>> {
+        Vec::new()
+    }
+
+    /// Sets the accumulated gradients to zero.
+    fn zero_grad(&mut self) {
+        for parameter in self.parameters() {
+            parameter.gradient.map_inplace(|g| *g = E::zero());
+        }
+    }
+
+    /// Switches between training and evaluation, only layers like Dropout behave differently.
+    fn set_training(&mut self, _training: bool) {}
+}
+
+/// Updates every parameter of `layer` with its accumulated gradient.
+pub fn step<E, L, O>(layer: &mut L, optimizer: &mut O)
+where
+    E: Float + 'static,
+    L: Layer<E> + ?Sized,
+    O: Optimizer<E> + ?Sized,
+{
+    for (index, parameter) in layer.parameters().into_iter().enumerate() {
+        optimizer.update(index, parameter.value, parameter.gradient);
+    }
+}
+
+/*
+   A fully connected layer, y = x W + b.
+*/
+pub struct Linear<E> {
+    // [in, out]
+    pub weight: Collective<E>,
+    // [1, out]
+    pub bias: Option<Collective<E>>,
+    weight_gradient: Collective<E>,
+    bias_gradient: Option<Collective<E>>,
+    // The input of the last forward()
+    input: Option<Collective<E>>,
+}
+
+impl<E: Float> Linear<E> {
+    /// A layer of `in_features` inputs and `out_features` outputs, with a bias.
+    ///
+    /// # Panics
+    /// If either number of features is 0.
+    pub fn new(in_features: usize, out_features: usize) -> Self {
+        if in_features == 0 || out_features == 0 {
+            panic!("Linear::new(): {} x {} weights have no elements", in_features, out_features);
+        }
+
+        let like = || Dimensions::new(out_features as f64, in_features as f64);
+        let bound = 1.0 / (in_features as f64).sqrt();
+
+        Self {
+            weight: uniform(like(), bound),
+            bias: Some(Tensor::zeros(Dimensions::new(out_features as f64, 1.0))),
+            weight_gradient: Tensor::zeros(like()),
+            bias_gradient: Some(Tensor::zeros(Dimensions::new(out_features as f64, 1.0))),
+            input: None,
+        }
+    }
+
+    // Fluent setters
+    pub fn with_bias(mut self, bias: bool) -> Self {
+        if !bias {
+            self.bias = None;
+            self.bias_gradient = None;
+        }
+
+        self
+    }
+
+    fn features(&self) -> (usize, usize) {
+        let extents = self.weight.extents();
+
+        (extents[0], extents[1])
+    }
+}
+
+impl<E: Float> Layer<E> for Linear<E> {
+    fn forward(&mut self, input: &Collective<E>) -> Collective<E> {
+        let (in_features, _) = self.features();
+        let extents = input.extents();
+
+        if extents[extents.len() - 1] != in_features {
+            panic!("Linear::forward(): Input of shape {:?} does not have {} features", extents, in_features);
+        }
+
+        let output = input.matmul(&self.weight);
+        self.input = Some(input.clone());
+
+        match &self.bias {
+            Some(bias) => output.zip_with(bias, |y, b| y + b),
+            None => output,
+        }
+    }
+
+    fn backward(&mut self, gradient: &Collective<E>) -> Collective<E> {
+        let input = match &self.input {
+            Some(input) => input,
+            None => panic!("Linear::backward(): forward() has not been called"),
+        };
+
+        let (in_features, out_features) = self.features();
+        let rows = input.extents().iter().rev().skip(1).product::<usize>();
+
+        check_gradient_shape("Linear", gradient, &output_extents(input, out_features));
+
+        // Every row of the input contributes to the gradients of the shared weights
+        let gradient_rows = gradient.with_extents(&[rows, out_features]);
+        accumulate(
+            &mut self.weight_gradient,
+            &input.with_extents(&[rows, in_features]).transpose().matmul(&gradient_rows),
+        );
+
+        if let Some(bias_gradient) = &mut self.bias_gradient {
+            let column_sums = gradient_rows.fold(Some(0), 0.0f64, |sum, g| sum + g.to_f64()).map(E::from_f64);
+            accumulate(bias_gradient, &column_sums);
+        }
+
+        gradient.matmul(&self.weight.transpose())
+    }
+
+    fn parameters(&mut self) -> Vec<Parameter<'_, E>> {
+        let mut parameters = vec![Parameter {
+            value: &mut self.weight,
+            gradient: &mut self.weight_gradient,
+        }];
+
+        if let (Some(value), Some(gradient)) = (&mut self.bias, &mut self.bias_gradient) {
+            parameters.push(Parameter { value, gradient });
+        }
+
+        parameters
+    }
+}
+
+/*
+   A lookup table of `count` vectors of `dim` elements, e.g. the token embeddings of a language model.
+*/
+pub struct Embedding<E> {
+    // [count, dim]
+    pub weight: Collective<E>,
+    weight_gradient: Collective<E>,
+    // The indices of the last forward() and the shape of its input
+    indices: Option<(Vec<usize>, Vec<usize>)>,
+}
+
+impl<E: Float> Embedding<E> {
+    /// A table of `count` embeddings of `dim` elements.
+    ///
+    /// # Panics
+    /// If `count` or `dim` is 0.
+    pub fn new(count: usize, dim: usize) -> Self {
+        if count == 0 || dim == 0 {
+            panic!("Embedding::new(): {} x {} weights have no elements", count, dim);
+        }
+
+        let like = || Dimensions::new(dim as f64, count as f64);
+
+        Self {
+            weight: uniform(like(), 1.0),
+            weight_gradient: Tensor::zeros(like()),
+            indices: None,
+        }
+    }
+
+    /// The embeddings of `indices`, of shape [..., dim] for indices [...].
+    ///
+    /// # Panics
+    /// If an index is negative or not less than the number of embeddings.
+    pub fn lookup(&mut self, indices: &Collective<i32>) -> Collective<E> {
+        let (count, dim) = (self.weight.extents()[0], self.weight.extents()[1]);
+        let weights = self.weight.data.as_deref().unwrap();
+
+        let mut rows = Vec::new();
+        let mut output = Vec::new();
+
+        for &index in indices.data.as_deref().unwrap_or(&[]) {
+            if index < 0 || index as usize >= count {
+                panic!("Embedding::lookup(): Index {} is out of range for {} embeddings", index, count);
+            }
+
+            let row = index as usize;
+            rows.push(row);
+            output.extend_from_slice(&weights[row * dim..(row + 1) * dim]);
+        }
+
+        if rows.is_empty() {
+            return Collective { data: None, shape: None };
+        }
+
+        let mut extents = indices.extents();
+        self.indices = Some((rows, extents.clone()));
+        extents.push(dim);
+
+        Collective {
+            data: Some(output.into_boxed_slice()),
+            shape: Some(Box::new(dimensions_from_extents(&extents))),
+        }
+    }
+}
+
+impl<E: Float> Layer<E> for Embedding<E> {
+    /// The input holds the indices as whole numbers, see `lookup`.
+    ///
+    /// # Panics
+    /// If an element of the input is not a valid index.
+    fn forward(&mut self, input: &Collective<E>) -> Collective<E> {
+        let indices = input.map(|x| {
+            let x = x.to_f64();
+
+            if x.fract() != 0.0 || x < i32::MIN as f64 || x > i32::MAX as f64 {
+                panic!("Embedding::forward(): {} is not an index", x);
+            }
+
+            x as i32
+        });
+
+        self.lookup(&indices)
+    }
+
+    /// Adds the gradient of every looked up vector to its row of the weight gradient.
+    ///
+    /// # Returns
+    /// Zeros of the shape of the indices, indices have no gradient.
+    fn backward(&mut self, gradient: &Collective<E>) -> Collective<E> {
+        let (rows, extents) = match &self.indices {
+            Some(indices) => indices,
+            None => panic!("Embedding::backward(): forward() has not been called"),
+        };
+
+        let dim = self.weight.extents()[1];
+        let mut output_extents = extents.clone();
+        output_extents.push(dim);
+
+        check_gradient_shape("Embedding", gradient, &output_extents);
+
+        let weight_gradient = self.weight_gradient.data.as_deref_mut().unwrap();
+        for (&row, chunk) in rows.iter().zip(gradient.data.as_deref().unwrap().chunks(dim)) {
+            for (accumulated, &g) in weight_gradient[row * dim..(row + 1) * dim].iter_mut().zip(chunk) {
+                *accumulated = *accumulated + g;
+            }
+        }
+
+        zeros(extents)
+    }
+
+    fn parameters(&mut self) -> Vec<Parameter<'_, E>> {
+        vec![Parameter {
+            value: &mut self.weight,
+            gradient: &mut self.weight_gradient,
+        }]
+    }
+}
+
+/*
+   Layer normalization over the last axis (Ba et al.), y = (x - mean) / sqrt(variance + epsilon) * gain + bias.
+*/
+pub struct LayerNorm<E> {
+    // [1, features]
+    pub gain: Collective<E>,
+    pub bias: Collective<E>,
+    pub epsilon: f64,
+    gain_gradient: Collective<E>,
+    bias_gradient: Collective<E>,
+    // The normalized input, 1 / sqrt(variance + epsilon) of every vector and the input shape of the last forward()
+    normalized: Option<(Vec<f64>, Vec<f64>, Vec<usize>)>,
+}
+
+impl<E: Float> LayerNorm<E> {
+    /// Normalizes vectors of `features` elements, with epsilon 1e-5.
+    ///
+    /// # Panics
+    /// If `features` is 0.
+    pub fn new(features: usize) -> Self {
+        if features == 0 {
+            panic!("LayerNorm::new(): features is 0");
+        }
+
+        let like = || Dimensions::new(features as f64, 1.0);
+
+        Self {
+            gain: Tensor::ones(like()),
+            bias: Tensor::zeros(like()),
+            epsilon: 1e-5,
+            gain_gradient: Tensor::zeros(like()),
+            bias_gradient: Tensor::zeros(like()),
+            normalized: None,
+        }
+    }
+
+    // Fluent setters
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+}
+
+impl<E: Float> Layer<E> for LayerNorm<E> {
+    fn forward(&mut self, input: &Collective<E>) -> Collective<E> {
+        let features = self.gain.extents()[1];
+        let extents = input.extents();
+
+        let data = match &input.data {
+            Some(data) if extents[extents.len() - 1] == features => data,
+            _ => panic!("LayerNorm::forward(): Input of shape {:?} does not have {} features", extents, features),
+        };
+
+        let (gain, bias) = (self.gain.data.as_deref().unwrap(), self.bias.data.as_deref().unwrap());
+
+        let mut normalized = Vec::with_capacity(data.len());
+        let mut inverse_deviations = Vec::with_capacity(data.len() / features);
+        let mut output = Vec::with_capacity(data.len());
+
+        for vector in data.chunks(features) {
+            let mean = vector.iter().map(|x| x.to_f64()).sum::<f64>() / features as f64;
+            let variance = vector.iter().map(|x| (x.to_f64() - mean).powi(2)).sum::<f64>() / features as f64;
+            let inverse_deviation = 1.0 / (variance + self.epsilon).sqrt();
+
+            for (index, x) in vector.iter().enumerate() {
+                let x_hat = (x.to_f64() - mean) * inverse_deviation;
+
+                normalized.push(x_hat);
+                output.push(E::from_f64(x_hat * gain[index].to_f64() + bias[index].to_f64()));
+            }
+
+            inverse_deviations.push(inverse_deviation);
+        }
+
+        self.normalized = Some((normalized, inverse_deviations, extents.clone()));
+
+        Collective {
+            data: Some(output.into_boxed_slice()),
+            shape: Some(Box::new(dimensions_from_extents(&extents))),
+        }
+    }
+
+    /*
+       With g the gradient of the output and d = g * gain the gradient of the normalized vector x_hat, per vector of n elements:
+       dx = (d - mean(d) - x_hat * mean(d * x_hat)) / sqrt(variance + epsilon)
+    */
+    fn backward(&mut self, gradient: &Collective<E>) -> Collective<E> {
+        let (normalized, inverse_deviations, extents) = match &self.normalized {
+            Some(normalized) => normalized,
+            None => panic!("LayerNorm::backward(): forward() has not been called"),
+        };
+
+        check_gradient_shape("LayerNorm", gradient, extents);
+
+        let features = self.gain.extents()[1];
+        let gain = self.gain.data.as_deref().unwrap();
+        let gain_gradient = self.gain_gradient.data.as_deref_mut().unwrap();
+        let bias_gradient = self.bias_gradient.data.as_deref_mut().unwrap();
+
+        let mut input_gradient = Vec::with_capacity(normalized.len());
+
+        let vectors = gradient.data.as_deref().unwrap().chunks(features).zip(normalized.chunks(features));
+        for ((g, x_hat), &inverse_deviation) in vectors.zip(inverse_deviations.iter()) {
+            let d: Vec<f64> = g.iter().zip(gain.iter()).map(|(g, gain)| g.to_f64() * gain.to_f64()).collect();
+
+            let mean_d = d.iter().sum::<f64>() / features as f64;
+            let mean_d_x_hat = d.iter().zip(x_hat.iter()).map(|(d, x)| d * x).sum::<f64>() / features as f64;
+
+            for index in 0..features {
+                let g = g[index].to_f64();
+
+                gain_gradient[index] = E::from_f64(gain_gradient[index].to_f64() + g * x_hat[index]);
+                bias_gradient[index] = E::from_f64(bias_gradient[index].to_f64() + g);
+
+                input_gradient.push(E::from_f64((d[index] - mean_d - x_hat[index] * mean_d_x_hat) * inverse_deviation));
+            }
+        }
+
+        Collective {
+            data: Some(input_gradient.into_boxed_slice()),
+            shape: Some(Box::new(dimensions_from_extents(extents))),
+        }
+    }
+
+    fn parameters(&mut self) -> Vec<Parameter<'_, E>> {
+        vec![
+            Parameter {
+                value: &mut self.gain,
+                gradient: &mut self.gain_gradient,
+            },
+            Parameter {
+                value: &mut self.bias,
+                gradient: &mut self.bias_gradient,
+            },
+        ]
+    }
+}
+
+/*
+   Inverted dropout, the expected value of every element is the same in training and evaluation.
+*/
+pub struct Dropout {
+    pub probability: f64,
+    training: bool,
+    rng: StdRng,
+    // The scale of every element in the last forward(), 0 or 1 / (1 - p), None if everything was passed through
+    mask: Option<Vec<f64>>,
+}
+
+impl Dropout {
+    /// Zeroes elements with `probability` while training, which is the initial mode.
+    ///
+    /// # Panics
+    /// If `probability` is not in [0, 1).
+    pub fn new(probability: f64) -> Self {
+        if !(0.0..1.0).contains(&probability) {
+            panic!("Dropout::new(): probability {} is not in [0, 1)", probability);
+        }
+
+        Self {
+            probability,
+            training: true,
+            rng: StdRng::from_entropy(),
+            mask: None,
+        }
+    }
+
+    // Fluent setters
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+}
+
+impl<E: Float> Layer<E> for Dropout {
+    fn forward(&mut self, input: &Collective<E>) -> Collective<E> {
+        if !self.training || self.probability == 0.0 {
+            self.mask = None;
+            return input.clone();
+        }
+
+        let scale = 1.0 / (1.0 - self.probability);
+        let mask: Vec<f64> = (0..input.iter().count())
+            .map(|_| if self.rng.gen::<f64>() < self.probability { 0.0 } else { scale })
+            .collect();
+
+        let mut output = input.clone();
+        for (x, &m) in output.iter_mut().zip(mask.iter()) {
+            *x = E::from_f64(x.to_f64() * m);
+        }
+
+        self.mask = Some(mask);
+        output
+    }
+
+    fn backward(&mut self, gradient: &Collective<E>) -> Collective<E> {
+        let mut input_gradient = gradient.clone();
+
+        if let Some(mask) = &self.mask {
+            if mask.len() != input_gradient.iter().count() {
+                panic!(
+                    "Dropout::backward(): The gradient has {} elements, the output of forward() had {}",
+                    input_gradient.iter().count(),
+                    mask.len()
+                );
+            }
+
+            for (g, &m) in input_gradient.iter_mut().zip(mask.iter()) {
+                *g = E::from_f64(g.to_f64() * m);
+            }
+        }
+
+        input_gradient
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.training = training;
+    }
+}
+
+/*
+   An activation function as a layer, its gradient is that of `Activation::derivative`.
+*/
+pub struct ActivationLayer<E> {
+    pub activation: Activation,
+    input: Option<Collective<E>>,
+}
+
+impl<E: Float> ActivationLayer<E> {
+    pub fn new(activation: Activation) -> Self {
+        Self { activation, input: None }
+    }
+}
+
+impl<E: Float> Layer<E> for ActivationLayer<E> {
+    fn forward(&mut self, input: &Collective<E>) -> Collective<E> {
+        self.input = Some(input.clone());
+
+        self.activation.apply(input)
+    }
+
+    fn backward(&mut self, gradient: &Collective<E>) -> Collective<E> {
+        let input = match &self.input {
+            Some(input) => input,
+            None => panic!("ActivationLayer::backward(): forward() has not been called"),
+        };
+
+        check_gradient_shape("ActivationLayer", gradient, &input.extents());
+
+        gradient.zip_with(&self.activation.derivative(input), |g, d| g * d)
+    }
+}
+
+/*
+   Layers applied one after the other, backward() goes through them in reverse.
+*/
+pub struct Sequential<E> {
+    pub layers: Vec<Box<dyn Layer<E>>>,
+}
+
+impl<E: Float> Sequential<E> {
+    pub fn new() -> Self {
+        Self { layers: Vec::new() }
+    }
+
+    // Fluent setters
+    pub fn with(mut self, layer: impl Layer<E> + 'static) -> Self {
+        self.layers.push(Box::new(layer));
+        self
+    }
+}
+
+impl<E: Float> Default for Sequential<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: Float> Layer<E> for Sequential<E> {
+    fn forward(&mut self, input: &Collective<E>) -> Collective<E> {
+        let mut output = input.clone();
+
+        for layer in self.layers.iter_mut() {
+            output = layer.forward(&output);
+        }
+
+        output
+    }
+
+    fn backward(&mut self, gradient: &Collective<E>) -> Collective<E> {
+        let mut gradient = gradient.clone();
+
+        for layer in self.layers.iter_mut().rev() {
+            gradient = layer.backward(&gradient);
+        }
+
+        gradient
+    }
+
+    fn parameters(&mut self) -> Vec<Parameter<'_, E>> {
+        self.layers.iter_mut().flat_map(|layer| layer.parameters()).collect()
+    }
+
+    fn zero_grad(&mut self) {
+        self.layers.iter_mut().for_each(|layer| layer.zero_grad());
+    }
+
+    fn set_training(&mut self, training: bool) {
+        self.layers.iter_mut().for_each(|layer| layer.set_training(training));
+    }
+}
+
+// U(-bound, bound) from the U(0, 1) of Tensor::randn
+fn uniform<E: Float>(like: Dimensions, bound: f64) -> Collective<E> {
+    Tensor::randn::<f64>(like).map(|u| E::from_f64((2.0 * u - 1.0) * bound))
+}
+
+fn zeros<E: Float>(extents: &[usize]) -> Collective<E> {
+    Collective {
+        data: Some(vec![E::zero(); extents.iter().product()].into_boxed_slice()),
+        shape: Some(Box::new(dimensions_from_extents(extents))),
+    }
+}
+
+// The shape of the output of a Linear layer of `out_features` for `input`
+fn output_extents<E>(input: &Collective<E>, out_features: usize) -> Vec<usize> {
+    let mut extents = input.extents();
+    let last = extents.len() - 1;
+    extents[last] = out_features;
+
+    extents
+}
+
+fn accumulate<E: Float>(accumulated: &mut Collective<E>, delta: &Collective<E>) {
+    for (a, &d) in accumulated.iter_mut().zip(delta.iter()) {
+        *a = *a + d;
+    }
+}
+
+fn check_gradient_shape<E>(layer: &str, gradient: &Collective<E>, extents: &[usize]) {
+    if gradient.data.is_none() || gradient.extents() != extents {
+        panic!(
+            "{}::backward(): The gradient of shape {:?} does not match the output of shape {:?}",
+            layer,
+            gradient.extents(),
+            extents
+        );
+    }
+}
@@ -0,0 +1,582 @@
+/*
+ * Numrs/src/npy.rs
+ * Q@khaa.pk
+ */
+
+/*
+   NumPy .npy files
+   ----------------
+   The .npy format is what `numpy.save`/`numpy.load` use, which makes it the simplest way to move a Collective between a Python
+   preprocessing step and Rust training code. A file is a small header followed by the raw element bytes:
+
+       \x93NUMPY                      6 bytes magic string
+       major, minor                   1 byte each, version 1.0, 2.0 or 3.0
+       header_len                     u16 little endian for 1.0, u32 little endian for 2.0 and 3.0
+       header                         a Python dict literal, padded with spaces and terminated by '\n' so the data is 64 byte aligned
+                                      {'descr': '<f8', 'fortran_order': False, 'shape': (3, 4), }
+       data                           shape.product() elements, in C (row-major) or Fortran (column-major) order
+
+   Version 3.0 only differs from 2.0 in that the header is utf8 instead of latin1.
+   `descr` is the byte order ('<' little endian, '>' big endian, '|' not applicable, '=' native), the kind ('i' signed integer,
+   'u' unsigned integer, 'f' float) and the size in bytes, e.g. '<f8' is a little endian f64 and '|u1' is a u8.
+
+   Shapes map onto `Dimensions` the same way `Dimensions::from_vec`/`to_vec` do. Dimensions always have at least rows and columns,
+   so a 1-D array of n elements loads as 1 row of n columns (and is saved back as shape (1, n)), a 0-D array as 1 x 1.
+   Axes of length zero cannot be represented by Dimensions and are rejected.
+
+   Every element type with an .npy dtype is supported: i8 .. i64, u8 .. u64, isize, usize, f32, f64 and f16 (with the `f16` feature).
+   NumPy has no 128 bit integers and no bfloat16, so i128, u128 and bf16 Collectives cannot be saved as .npy.
+*/
+
+use super::{collective::Collective, dimensions::Dimensions, pod::Pod};
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+const MAGIC: &[u8; 6] = b"\x93NUMPY";
+
+// The total length of magic, version, header_len and header is padded to a multiple of this, so the data is aligned
+const ALIGNMENT: usize = 64;
+
+/*
+   An element type that has a NumPy dtype. The byte conversions come from `Pod` (see pod.rs).
+*/
+pub trait NpyElement: Pod {
+    /// 'i' signed integer, 'u' unsigned integer, 'f' float.
+    const KIND: char;
+}
+
+macro_rules! impl_npy_element {
+    ($($t:ty => $kind:expr),*) => {
+        $(
+            impl NpyElement for $t {
+                const KIND: char = $kind;
+            }
+        )*
+    };
+}
+
+impl_npy_element!(
+    i8 => 'i', i16 => 'i', i32 => 'i', i64 => 'i', isize => 'i',
+    u8 => 'u', u16 => 'u', u32 => 'u', u64 => 'u', usize => 'u',
+    f32 => 'f', f64 => 'f'
+);
+
+#[cfg(feature = "f16")]
+impl_npy_element!(half::f16 => 'f');
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    LittleEndian,
+    BigEndian,
+}
+
+impl ByteOrder {
+    #[cfg(target_endian = "little")]
+    pub const NATIVE: ByteOrder = ByteOrder::LittleEndian;
+    #[cfg(target_endian = "big")]
+    pub const NATIVE: ByteOrder = ByteOrder::BigEndian;
+}
+
+/*
+   The parsed header of an .npy file. `read_header` returns it without loading the data,
+   which tells which element type to load a file with.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NpyHeader {
+    pub version: (u8, u8),
+    pub descr: String,
+    pub fortran_order: bool,
+    pub shape: Vec<usize>,
+}
+
+impl NpyHeader {
+    // Number of elements, the product of the shape (1 for a 0-D array)
+    pub fn get_n(&self) -> usize {
+        self.shape.iter().product()
+    }
+}
+
+/*
+   How `Collective::write_npy`/`save_npy_with` write a file. The default is what `numpy.save` writes:
+   little endian, C order and the lowest version the header fits in (1.0 unless the header is longer than 65535 bytes).
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct NpyOptions {
+    byte_order: ByteOrder,
+    fortran_order: bool,
+    version: Option<(u8, u8)>,
+}
+
+impl NpyOptions {
+    pub fn new() -> Self {
+        Self {
+            byte_order: ByteOrder::LittleEndian,
+            fortran_order: false,
+            version: None,
+        }
+    }
+
+    // Fluent setters
+    pub fn with_byte_order(mut self, byte_order: ByteOrder) -> Self {
+        self.byte_order = byte_order;
+        self
+    }
+
+    pub fn with_fortran_order(mut self, fortran_order: bool) -> Self {
+        self.fortran_order = fortran_order;
+        self
+    }
+
+    // One of (1, 0), (2, 0) or (3, 0)
+    pub fn with_version(mut self, major: u8, minor: u8) -> Self {
+        if !matches!((major, minor), (1, 0) | (2, 0) | (3, 0)) {
+            panic!("NpyOptions::with_version(): Unsupported .npy version {}.{}", major, minor);
+        }
+
+        self.version = Some((major, minor));
+        self
+    }
+}
+
+impl Default for NpyOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> Collective<E>
+where
+    E: NpyElement,
+{
+    /// Saves the Collective as an .npy file, the same way `numpy.save` would.
+    pub fn save_npy<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.save_npy_with(path, NpyOptions::new())
+    }
+
+    /// Saves the Collective as an .npy file, with the byte order, memory order and version given by `options`.
+    pub fn save_npy_with<P: AsRef<Path>>(&self, path: P, options: NpyOptions) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_npy(&mut writer, options)?;
+        writer.flush()
+    }
+
+    /// Loads an .npy file written by `numpy.save` (or `save_npy`).
+    ///
+    /// # Errors
+    /// `InvalidData` if the file is not a valid .npy file, or its dtype is not the dtype of `E`.
+    /// `read_header` tells the dtype of a file before loading it.
+    pub fn load_npy<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read_npy(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// Writes the Collective in .npy format to any writer, e.g. an entry of an .npz archive.
+    pub fn write_npy<W: Write>(&self, writer: &mut W, options: NpyOptions) -> io::Result<()> {
+        let (data, shape) = match (&self.data, &self.shape) {
+            (Some(data), Some(shape)) => (data, shape),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Collective::write_npy(): Collective data or shape is not allocated",
+                ))
+            }
+        };
+
+        let shape: Vec<usize> = shape.to_vec().iter().map(|&extent| extent as usize).collect();
+
+        if shape.iter().product::<usize>() != data.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Collective::write_npy(): Shape {:?} does not match the {} elements of the data",
+                    shape,
+                    data.len()
+                ),
+            ));
+        }
+
+        let header = NpyHeader {
+            version: options.version.unwrap_or((1, 0)),
+            descr: descr::<E>(options.byte_order),
+            fortran_order: options.fortran_order,
+            shape,
+        };
+
+        write_header(writer, &header, options.version.is_none())?;
+
+        let ordered;
+        let elements: &[E] = if options.fortran_order {
+            ordered = reorder(data, &header.shape, false);
+            &ordered
+        } else {
+            data
+        };
+
+        let mut buffer = Vec::with_capacity(elements.len() * E::SIZE);
+        for &element in elements {
+            match options.byte_order {
+                ByteOrder::LittleEndian => element.extend_le_bytes(&mut buffer),
+                ByteOrder::BigEndian => element.extend_be_bytes(&mut buffer),
+            }
+        }
+
+        writer.write_all(&buffer)
+    }
+
+    /// Reads a Collective in .npy format from any reader, e.g. an entry of an .npz archive.
+    pub fn read_npy<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let header = read_header_from(reader)?;
+        let byte_order = check_descr::<E>(&header.descr)?;
+        let dimensions = shape_to_dimensions(&header.shape)?;
+        let bytes = read_data(reader, data_size(&header.shape, E::SIZE)?)?;
+
+        let elements: Vec<E> = bytes
+            .chunks_exact(E::SIZE)
+            .map(|chunk| match byte_order {
+                ByteOrder::LittleEndian => E::from_le_slice(chunk),
+                ByteOrder::BigEndian => E::from_be_slice(chunk),
+            })
+            .collect();
+
+        let elements = if header.fortran_order {
+            reorder(&elements, &header.shape, true)
+        } else {
+            elements
+        };
+
+        Ok(Collective::new(
+            Some(elements.into_boxed_slice()),
+            Some(Box::new(dimensions)),
+        ))
+    }
+}
+
+/// Reads only the header of an .npy file.
+pub fn read_header<P: AsRef<Path>>(path: P) -> io::Result<NpyHeader> {
+    read_header_from(&mut BufReader::new(File::open(path)?))
+}
+
+/// Reads the header from a reader positioned at the start of an .npy file, leaving it positioned at the first data byte.
+pub fn read_header_from<R: Read>(reader: &mut R) -> io::Result<NpyHeader> {
+    let mut preamble = [0u8; 8];
+    reader.read_exact(&mut preamble)?;
+
+    if &preamble[..6] != MAGIC {
+        return Err(invalid_data("not an .npy file, magic string is missing".to_string()));
+    }
+
+    let version = (preamble[6], preamble[7]);
+
+    let header_len = match version {
+        (1, 0) => {
+            let mut len = [0u8; 2];
+            reader.read_exact(&mut len)?;
+            u16::from_le_bytes(len) as usize
+        }
+        (2, 0) | (3, 0) => {
+            let mut len = [0u8; 4];
+            reader.read_exact(&mut len)?;
+            u32::from_le_bytes(len) as usize
+        }
+        _ => {
+            return Err(invalid_data(format!(
+                "unsupported .npy version {}.{}",
+                version.0, version.1
+            )))
+        }
+    };
+
+    let mut raw = vec![0u8; header_len];
+    reader.read_exact(&mut raw)?;
+
+    // 1.0 and 2.0 headers are latin1, every latin1 byte is the char with the same code point
+    let text: String = if version == (3, 0) {
+        String::from_utf8(raw).map_err(|_| invalid_data("header is not valid utf8".to_string()))?
+    } else {
+        raw.iter().map(|&byte| byte as char).collect()
+    };
+
+    parse_header_dict(&text, version)
+}
+
+fn write_header<W: Write>(writer: &mut W, header: &NpyHeader, pick_version: bool) -> io::Result<()> {
+    let shape = match header.shape.len() {
+        1 => format!("({},)", header.shape[0]),
+        _ => format!(
+            "({})",
+            header
+                .shape
+                .iter()
+                .map(|extent| extent.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+    };
+
+    let dict = format!(
+        "{{'descr': '{}', 'fortran_order': {}, 'shape': {}, }}",
+        header.descr,
+        if header.fortran_order { "True" } else { "False" },
+        shape
+    );
+
+    let mut version = header.version;
+    if pick_version && padded_len(dict.len(), 2) > u16::MAX as usize {
+        version = (2, 0);
+    }
+
+    let len_size = if version == (1, 0) { 2 } else { 4 };
+    let total = padded_len(dict.len(), len_size);
+    let header_len = total - MAGIC.len() - 2 - len_size;
+
+    if version == (1, 0) && header_len > u16::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "header is too long for .npy version 1.0",
+        ));
+    }
+
+    let mut bytes = Vec::with_capacity(total);
+    bytes.extend_from_slice(MAGIC);
+    bytes.push(version.0);
+    bytes.push(version.1);
+
+    if len_size == 2 {
+        bytes.extend_from_slice(&(header_len as u16).to_le_bytes());
+    } else {
+        bytes.extend_from_slice(&(header_len as u32).to_le_bytes());
+    }
+
+    bytes.extend_from_slice(dict.as_bytes());
+    bytes.resize(total - 1, b' ');
+    bytes.push(b'\n');
+
+    writer.write_all(&bytes)
+}
+
+// Length of magic + version + header_len + dict + '\n', rounded up to the alignment
+fn padded_len(dict_len: usize, len_size: usize) -> usize {
+    let unpadded = MAGIC.len() + 2 + len_size + dict_len + 1;
+
+    unpadded.div_ceil(ALIGNMENT) * ALIGNMENT
+}
+
+fn descr<E: NpyElement>(byte_order: ByteOrder) -> String {
+    let order = if E::SIZE == 1 {
+        '|'
+    } else {
+        match byte_order {
+            ByteOrder::LittleEndian => '<',
+            ByteOrder::BigEndian => '>',
+        }
+    };
+
+    format!("{}{}{}", order, E::KIND, E::SIZE)
+}
+
+/*
+   Checks that a descr string describes `E` and returns the byte order of the data.
+*/
+pub(crate) fn check_descr<E: NpyElement>(descr: &str) -> io::Result<ByteOrder> {
+    let mut chars = descr.chars();
+
+    let byte_order = match chars.next() {
+        Some('<') => ByteOrder::LittleEndian,
+        Some('>') => ByteOrder::BigEndian,
+        Some('|') | Some('=') => ByteOrder::NATIVE,
+        _ => return Err(invalid_data(format!("unsupported dtype '{}'", descr))),
+    };
+
+    let kind_and_size = chars.as_str();
+
+    if kind_and_size != format!("{}{}", E::KIND, E::SIZE) {
+        return Err(invalid_data(format!(
+            "dtype '{}' does not match the element type ({}{}), load it as the matching type and use astype to convert",
+            descr,
+            E::KIND,
+            E::SIZE
+        )));
+    }
+
+    Ok(byte_order)
+}
+
+pub(crate) fn shape_to_dimensions(shape: &[usize]) -> io::Result<Dimensions> {
+    if shape.contains(&0) {
+        return Err(invalid_data(format!(
+            "shape {:?} has an axis of length zero, which Dimensions cannot represent",
+            shape
+        )));
+    }
+
+    Ok(match shape.len() {
+        0 => Dimensions::new(1.0, 1.0),
+        1 => Dimensions::new(shape[0] as f64, 1.0),
+        _ => Dimensions::from_vec(shape.iter().map(|&extent| extent as f64).collect()),
+    })
+}
+
+/*
+   The number of bytes of data the (untrusted) shape of a header describes, an error if that does not fit in a usize.
+*/
+pub(crate) fn data_size(shape: &[usize], element_size: usize) -> io::Result<usize> {
+    shape
+        .iter()
+        .try_fold(element_size, |size, &extent| size.checked_mul(extent))
+        .ok_or_else(|| invalid_data(format!("shape {:?} describes more data than fits in memory", shape)))
+}
+
+/*
+   Reads the `size` bytes of data that follow a header. The buffer only grows with the bytes the reader actually holds,
+   so a crafted header that claims more data than the file has fails with UnexpectedEof instead of allocating it up front.
+*/
+pub(crate) fn read_data<R: Read>(reader: &mut R, size: usize) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(size as u64).read_to_end(&mut bytes)?;
+
+    if bytes.len() < size {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("the header describes {} bytes of data, only {} follow it", size, bytes.len()),
+        ));
+    }
+
+    Ok(bytes)
+}
+
+/*
+   Converts between C order (last index changes fastest) and Fortran order (first index changes fastest).
+   `from_fortran` is true to turn Fortran ordered elements into C order, false for the other direction.
+*/
+fn reorder<E: Copy>(elements: &[E], shape: &[usize], from_fortran: bool) -> Vec<E> {
+    let mut reordered = elements.to_vec();
+    let mut index = vec![0usize; shape.len()];
+
+    for c_offset in 0..elements.len() {
+        // Fortran offset of the multi-index the C offset points at
+        let mut fortran_offset = 0;
+        let mut stride = 1;
+        for (axis, &extent) in shape.iter().enumerate() {
+            fortran_offset += index[axis] * stride;
+            stride *= extent;
+        }
+
+        if from_fortran {
+            reordered[c_offset] = elements[fortran_offset];
+        } else {
+            reordered[fortran_offset] = elements[c_offset];
+        }
+
+        // Advance the multi-index in C order
+        for axis in (0..shape.len()).rev() {
+            index[axis] += 1;
+            if index[axis] < shape[axis] {
+                break;
+            }
+            index[axis] = 0;
+        }
+    }
+
+    reordered
+}
+
+/*
+   A small parser for the header dict, which is a Python literal such as
+   {'descr': '<f8', 'fortran_order': False, 'shape': (3, 4), }
+*/
+fn parse_header_dict(text: &str, version: (u8, u8)) -> io::Result<NpyHeader> {
+    let body = text
+        .trim()
+        .strip_prefix('{')
+        .and_then(|rest| rest.strip_suffix('}'))
+        .ok_or_else(|| invalid_data(format!("header is not a dict: {}", text.trim())))?;
+
+    let mut descr = None;
+    let mut fortran_order = None;
+    let mut shape = None;
+
+    let mut rest = body.trim_start();
+    while !rest.is_empty() {
+        let (key, after_key) = parse_quoted(rest)?;
+        let after_colon = after_key
+            .trim_start()
+            .strip_prefix(':')
+            .ok_or_else(|| invalid_data(format!("expected ':' after '{}'", key)))?
+            .trim_start();
+
+        let after_value = match key.as_str() {
+            "descr" => {
+                let (value, after) = parse_quoted(after_colon)?;
+                descr = Some(value);
+                after
+            }
+            "fortran_order" => {
+                if let Some(after) = after_colon.strip_prefix("True") {
+                    fortran_order = Some(true);
+                    after
+                } else if let Some(after) = after_colon.strip_prefix("False") {
+                    fortran_order = Some(false);
+                    after
+                } else {
+                    return Err(invalid_data("fortran_order must be True or False".to_string()));
+                }
+            }
+            "shape" => {
+                let inner = after_colon
+                    .strip_prefix('(')
+                    .ok_or_else(|| invalid_data("shape must be a tuple".to_string()))?;
+                let end = inner
+                    .find(')')
+                    .ok_or_else(|| invalid_data("shape tuple is not closed".to_string()))?;
+
+                shape = Some(
+                    inner[..end]
+                        .split(',')
+                        .map(|extent| extent.trim())
+                        .filter(|extent| !extent.is_empty())
+                        .map(|extent| {
+                            extent
+                                .trim_end_matches('L') // Python 2 long suffix
+                                .parse::<usize>()
+                                .map_err(|_| invalid_data(format!("invalid shape extent '{}'", extent)))
+                        })
+                        .collect::<io::Result<Vec<usize>>>()?,
+                );
+                &inner[end + 1..]
+            }
+            _ => return Err(invalid_data(format!("unknown header key '{}'", key))),
+        };
+
+        let after_value = after_value.trim_start();
+        rest = after_value.strip_prefix(',').unwrap_or(after_value).trim_start();
+    }
+
+    Ok(NpyHeader {
+        version,
+        descr: descr.ok_or_else(|| invalid_data("header has no 'descr'".to_string()))?,
+        fortran_order: fortran_order
+            .ok_or_else(|| invalid_data("header has no 'fortran_order'".to_string()))?,
+        shape: shape.ok_or_else(|| invalid_data("header has no 'shape'".to_string()))?,
+    })
+}
+
+// Parses a single or double quoted string at the start of `text`, returns it and the text after the closing quote
+fn parse_quoted(text: &str) -> io::Result<(String, &str)> {
+    let quote = match text.chars().next() {
+        Some(quote @ ('\'' | '"')) => quote,
+        _ => return Err(invalid_data(format!("expected a quoted string at: {}", text))),
+    };
+
+    let inner = &text[1..];
+    let end = inner
+        .find(quote)
+        .ok_or_else(|| invalid_data("unterminated string in header".to_string()))?;
+
+    Ok((inner[..end].to_string(), &inner[end + 1..]))
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
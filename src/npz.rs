@@ -0,0 +1,209 @@
+/*
+ * Numrs/src/npz.rs
+ * Q@khaa.pk
+ */
+
+/*
+   NumPy .npz archives
+   -------------------
+   An .npz file is what `numpy.savez`/`numpy.savez_compressed` write: a zip archive holding one .npy file (see npy.rs) per array,
+   the entry "dense1_weights.npy" holds the array named "dense1_weights". `numpy.savez` stores the entries as they are,
+   `numpy.savez_compressed` deflates them. Both kinds are read the same way.
+
+   Checkpoints hold dozens of weight tensors and can be large, so `NpzArchive` only reads the zip's central directory when it is
+   opened. An entry is decompressed and parsed when it is asked for, pulling a single layer out of a checkpoint does not load the rest.
+
+       let mut archive = NpzArchive::open("checkpoint.npz")?;
+       let weights: Collective<f32> = archive.load("dense1_weights")?;
+
+   `NpzWriter` adds Collectives one at a time, so the entries of one archive can have different element types.
+   `save_npz`/`save_npz_compressed` save a whole map of Collectives of one element type.
+*/
+
+use super::{
+    collective::Collective,
+    npy::{self, NpyElement, NpyHeader, NpyOptions},
+};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Seek, Write},
+    path::Path,
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+const EXTENSION: &str = ".npy";
+
+/*
+   An .npz archive opened for reading.
+*/
+pub struct NpzArchive<R: Read + Seek> {
+    archive: ZipArchive<R>,
+}
+
+impl NpzArchive<BufReader<File>> {
+    /// Opens an .npz file. Only the zip directory is read, entries are loaded by `load`.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> NpzArchive<R> {
+    pub fn new(reader: R) -> io::Result<Self> {
+        Ok(Self {
+            archive: ZipArchive::new(reader)?,
+        })
+    }
+
+    /// Names of the arrays in the archive, in the order they were written, without the ".npy" extension.
+    pub fn names(&self) -> Vec<String> {
+        (0..self.archive.len())
+            .filter_map(|index| self.archive.name_for_index(index))
+            .map(|name| name.strip_suffix(EXTENSION).unwrap_or(name).to_string())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.archive.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.archive.is_empty()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entry_name(name).is_some()
+    }
+
+    /// Reads only the .npy header of an entry, which tells its element type and shape.
+    pub fn header(&mut self, name: &str) -> io::Result<NpyHeader> {
+        let entry = self.entry_name(name).ok_or_else(|| not_found(name))?;
+
+        npy::read_header_from(&mut self.archive.by_name(&entry)?)
+    }
+
+    /// Decompresses and loads a single entry.
+    ///
+    /// # Errors
+    /// `NotFound` if the archive has no array named `name`, `InvalidData` if the entry is not a valid .npy file
+    /// or its dtype is not the dtype of `E`.
+    pub fn load<E: NpyElement>(&mut self, name: &str) -> io::Result<Collective<E>> {
+        let entry = self.entry_name(name).ok_or_else(|| not_found(name))?;
+
+        Collective::read_npy(&mut self.archive.by_name(&entry)?)
+    }
+
+    /// Loads every entry, all entries must have the element type `E`.
+    pub fn load_all<E: NpyElement>(&mut self) -> io::Result<BTreeMap<String, Collective<E>>> {
+        let mut collectives = BTreeMap::new();
+
+        for name in self.names() {
+            let collective = self.load(&name)?;
+            collectives.insert(name, collective);
+        }
+
+        Ok(collectives)
+    }
+
+    // Entries are normally "name.npy", archives written by other tools may leave the extension off
+    fn entry_name(&self, name: &str) -> Option<String> {
+        let with_extension = format!("{}{}", name, EXTENSION);
+
+        let found = [with_extension.as_str(), name]
+            .into_iter()
+            .find(|candidate| self.archive.index_for_name(candidate).is_some())
+            .map(|candidate| candidate.to_string());
+
+        found
+    }
+}
+
+/*
+   Writes an .npz archive, one Collective at a time. `finish` must be called to write the zip directory.
+*/
+pub struct NpzWriter<W: Write + Seek> {
+    writer: ZipWriter<W>,
+    compressed: bool,
+}
+
+impl NpzWriter<BufWriter<File>> {
+    /// Creates an .npz file, `compressed` deflates the entries like `numpy.savez_compressed`.
+    pub fn create<P: AsRef<Path>>(path: P, compressed: bool) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?), compressed))
+    }
+}
+
+impl<W: Write + Seek> NpzWriter<W> {
+    pub fn new(writer: W, compressed: bool) -> Self {
+        Self {
+            writer: ZipWriter::new(writer),
+            compressed,
+        }
+    }
+
+    /// Adds a Collective as the entry "`name`.npy".
+    pub fn add<E: NpyElement>(&mut self, name: &str, collective: &Collective<E>) -> io::Result<()> {
+        let mut buffer = Vec::new();
+        collective.write_npy(&mut buffer, NpyOptions::new())?;
+
+        let method = if self.compressed {
+            CompressionMethod::Deflated
+        } else {
+            CompressionMethod::Stored
+        };
+
+        // Entries of 4 GiB or more need the zip64 extensions
+        let options = SimpleFileOptions::default()
+            .compression_method(method)
+            .large_file(buffer.len() as u64 >= u32::MAX as u64);
+
+        self.writer.start_file(format!("{}{}", name, EXTENSION), options)?;
+        self.writer.write_all(&buffer)
+    }
+
+    /// Writes the zip directory and returns the underlying writer.
+    pub fn finish(self) -> io::Result<W> {
+        let mut writer = self.writer.finish()?;
+        writer.flush()?;
+
+        Ok(writer)
+    }
+}
+
+/// Saves named Collectives as an uncompressed .npz archive, like `numpy.savez`.
+pub fn save_npz<E: NpyElement, P: AsRef<Path>>(path: P, collectives: &BTreeMap<String, Collective<E>>) -> io::Result<()> {
+    save(path, collectives, false)
+}
+
+/// Saves named Collectives as a deflate-compressed .npz archive, like `numpy.savez_compressed`.
+pub fn save_npz_compressed<E: NpyElement, P: AsRef<Path>>(
+    path: P,
+    collectives: &BTreeMap<String, Collective<E>>,
+) -> io::Result<()> {
+    save(path, collectives, true)
+}
+
+/// Loads every array of an .npz archive, all arrays must have the element type `E`.
+pub fn load_npz<E: NpyElement, P: AsRef<Path>>(path: P) -> io::Result<BTreeMap<String, Collective<E>>> {
+    NpzArchive::open(path)?.load_all()
+}
+
+fn save<E: NpyElement, P: AsRef<Path>>(
+    path: P,
+    collectives: &BTreeMap<String, Collective<E>>,
+    compressed: bool,
+) -> io::Result<()> {
+    let mut writer = NpzWriter::create(path, compressed)?;
+
+    for (name, collective) in collectives {
+        writer.add(name, collective)?;
+    }
+
+    writer.finish()?;
+
+    Ok(())
+}
+
+fn not_found(name: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("NpzArchive: no array named '{}'", name))
+}
@@ -12,64 +12,541 @@
 
    - Pros: Zero extra memory allocation. Best for large batches.
    - Cons: Destructive. If the model needs the raw u8 values later for debugging or visualization, they are gone.
+
+   When the raw values have to survive, `Tensor::normalized` leaves its input untouched and returns a new float Collective,
+   and `Tensor::denormalize` maps normalized data (or model outputs) back to pixel values.
+   The u8 pixels themselves usually come from MNIST/Fashion-MNIST IDX files, see `Collective::load_idx` in idx.rs.
 */
 
-use super::{collective::Collective, dimensions::Dimensions};
+use super::{
+    collective::{cast_element, Collective},
+    dimensions::Dimensions,
+    header::{Rounding, Saturation},
+};
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
 
 pub struct Tensor;
 
-pub trait One {
+/*
+   Numeric trait hierarchy
+   -----------------------
+   Every element type a `Collective` can hold is described by a small set of traits, from the most general to the most specific:
+
+   - `Zero`, `One`  : The additive and multiplicative identities.
+   - `NumCast`      : Conversion to and from `f64` and `i128`, with the same semantics as an `as` cast (float to integer truncates and saturates, NaN becomes 0).
+                      `Collective::cast` builds on it to convert between any two element types.
+   - `Num`          : Everything an element of a numeric `Collective` must support (copy, compare, print, +, -, *, /, %).
+   - `Signed`       : A `Num` that can be negated (signed integers and floats).
+   - `Bounded`      : A type with a smallest and a largest representable value.
+   - `FloatType`    : Marker for floating point element types.
+   - `Float`        : A `FloatType` that is also `Signed` and `Bounded`, plus the usual floating point functions.
+   - `Dtype`        : The name of the element type ("u8", "f32", "bf16", ...), e.g. to tag serialized Collectives with their element type.
+
+   All of them are implemented for every integer primitive (i8 .. i128, isize, u8 .. u128, usize) and every float primitive (f32, f64),
+   so generic code such as `Tensor::ones::<u8>` or `Tensor::zeros::<f32>` works for any of them.
+   The reduced precision `f16` and `bf16` types get the same impls in `precision.rs` when the `f16`/`bf16` features are enabled.
+*/
+
+pub trait Zero: Sized {
+    fn zero() -> Self;
+
+    fn is_zero(&self) -> bool;
+}
+
+pub trait One: Sized {
     fn one() -> Self;
 }
 
-impl One for i32 {
-    fn one() -> Self {
-        1
-    }
+pub trait NumCast: Sized {
+    /// `true` for the integer types, `false` for the floating point types.
+    const IS_INTEGER: bool;
+
+    /// The significant binary digits of a float type, its `MANTISSA_DIGITS` (the implicit bit included), 0 for integer types.
+    const MANTISSA_DIGITS: u32;
+
+    /// Rounds to nearest (ties to even) for float types, truncates toward zero and saturates for integer types.
+    fn from_f64(val: f64) -> Self;
+
+    /// Always rounds toward zero, for float types the result is the representable value closest to `val` in the direction of zero.
+    fn from_f64_truncated(val: f64) -> Self;
+
+    fn to_f64(self) -> f64;
+
+    /// Wraps around (two's complement) for integer types, rounds to nearest for float types.
+    fn from_i128(val: i128) -> Self;
+
+    /// Exact for every integer type except `u128` values above `i128::MAX`, which saturate to `i128::MAX`.
+    fn to_i128(self) -> i128;
 }
 
-impl One for f64 {
-    fn one() -> Self {
-        1.0
-    }
+pub trait Num:
+    Copy
+    + Default
+    + PartialEq
+    + PartialOrd
+    + fmt::Debug
+    + fmt::Display
+    + Zero
+    + One
+    + NumCast
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+{
 }
-// Add more impls as needed (u32, usize, etc.)
 
-pub trait Zero {
-    fn zero() -> Self;
+pub trait Signed: Num + Neg<Output = Self> {
+    fn abs(self) -> Self;
+
+    /// Returns `-1`, `0` or `1` for integers; for floats it follows `f64::signum` (`1.0` for `+0.0`, `-1.0` for `-0.0`, NaN for NaN).
+    fn signum(self) -> Self;
+
+    fn is_positive(self) -> bool;
+
+    fn is_negative(self) -> bool;
 }
 
-impl Zero for i32 {
-    fn zero() -> Self {
-        0
-    }
+pub trait Bounded {
+    fn min_value() -> Self;
+
+    fn max_value() -> Self;
 }
 
-impl Zero for f64 {
-    fn zero() -> Self {
-        0.0
-    }
+pub trait Dtype {
+    const NAME: &'static str;
 }
-// Add more impls as needed (u32, usize, etc.)
 
-pub trait FloatType: Copy + Sized {
-    fn from_f64(val: f64) -> Self;
+pub trait FloatType: NumCast + Copy + Sized {}
+
+pub trait Float: FloatType + Signed + Bounded {
+    fn nan() -> Self;
+    fn infinity() -> Self;
+    fn neg_infinity() -> Self;
+    fn epsilon() -> Self;
+
+    fn is_nan(self) -> bool;
+    fn is_infinite(self) -> bool;
+    fn is_finite(self) -> bool;
+
+    fn sqrt(self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn log2(self) -> Self;
+    fn log10(self) -> Self;
+    /// ln(1 + x), accurate for x close to zero.
+    fn ln_1p(self) -> Self;
+    /// e^x - 1, accurate for x close to zero.
+    fn exp_m1(self) -> Self;
+    fn cbrt(self) -> Self;
+    fn powf(self, n: Self) -> Self;
+    fn powi(self, n: i32) -> Self;
+
+    fn floor(self) -> Self;
+    fn ceil(self) -> Self;
+    fn round(self) -> Self;
+    /// Rounds halves to the even integer, like NumPy's `round` and `Rounding::NearestEven`.
+    fn round_ties_even(self) -> Self;
+    fn trunc(self) -> Self;
+
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn asin(self) -> Self;
+    fn acos(self) -> Self;
+    fn atan(self) -> Self;
+
+    fn sinh(self) -> Self;
+    fn cosh(self) -> Self;
+    fn tanh(self) -> Self;
+    fn asinh(self) -> Self;
+    fn acosh(self) -> Self;
+    fn atanh(self) -> Self;
+
+    fn max(self, other: Self) -> Self;
+    fn min(self, other: Self) -> Self;
+}
+
+macro_rules! impl_num_common {
+    ($zero:expr, $one:expr; $($t:ty),*) => {
+        $(
+            impl Zero for $t {
+                fn zero() -> Self {
+                    $zero
+                }
+
+                fn is_zero(&self) -> bool {
+                    *self == $zero
+                }
+            }
+
+            impl One for $t {
+                fn one() -> Self {
+                    $one
+                }
+            }
+
+            impl Bounded for $t {
+                fn min_value() -> Self {
+                    <$t>::MIN
+                }
+
+                fn max_value() -> Self {
+                    <$t>::MAX
+                }
+            }
+
+            impl Num for $t {}
+        )*
+    };
+}
+
+impl_num_common!(0, 1; i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_num_common!(0.0, 1.0; f32, f64);
+
+macro_rules! impl_num_cast_int {
+    ($($t:ty),*) => {
+        $(
+            impl NumCast for $t {
+                const IS_INTEGER: bool = true;
+                const MANTISSA_DIGITS: u32 = 0;
+
+                fn from_f64(val: f64) -> Self {
+                    val as $t
+                }
+
+                fn from_f64_truncated(val: f64) -> Self {
+                    val as $t
+                }
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                fn from_i128(val: i128) -> Self {
+                    val as $t
+                }
+
+                fn to_i128(self) -> i128 {
+                    i128::try_from(self).unwrap_or(i128::MAX)
+                }
+            }
+        )*
+    };
+}
+
+impl_num_cast_int!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+macro_rules! impl_num_cast_float {
+    ($($t:ty),*) => {
+        $(
+            impl NumCast for $t {
+                const IS_INTEGER: bool = false;
+                const MANTISSA_DIGITS: u32 = <$t>::MANTISSA_DIGITS;
+
+                fn from_f64(val: f64) -> Self {
+                    val as $t
+                }
+
+                fn from_f64_truncated(val: f64) -> Self {
+                    truncate_to_precision(val, <$t>::MANTISSA_DIGITS, <$t>::MIN_EXP) as $t
+                }
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                fn from_i128(val: i128) -> Self {
+                    val as $t
+                }
+
+                fn to_i128(self) -> i128 {
+                    self as i128
+                }
+            }
+        )*
+    };
 }
 
-impl FloatType for f32 {
-    fn from_f64(val: f64) -> Self {
-        val as f32
+impl_num_cast_float!(f32, f64);
+
+macro_rules! impl_dtype {
+    ($($t:ty),*) => {
+        $(
+            impl Dtype for $t {
+                const NAME: &'static str = stringify!($t);
+            }
+        )*
+    };
+}
+
+impl_dtype!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+/*
+   Rounds `val` toward zero, to the precision of a narrower float type described by its `MANTISSA_DIGITS` and `MIN_EXP` constants.
+   The result is exactly representable in that type (unless it is out of its range), so converting it with a round-to-nearest
+   cast (`as`, `f16::from_f64`) no longer rounds, which turns that cast into a truncating one.
+
+   The spacing between neighbouring values (the quantum) is 2^(exponent - mantissa_digits + 1) for normal numbers and never drops
+   below 2^(min_exp - mantissa_digits), the spacing of the subnormal numbers. Dividing and multiplying by a power of two is exact.
+*/
+pub(crate) fn truncate_to_precision(val: f64, mantissa_digits: u32, min_exp: i32) -> f64 {
+    if !val.is_finite() || val == 0.0 {
+        return val;
     }
+
+    let biased_exponent = ((val.to_bits() >> 52) & 0x7ff) as i32;
+    let exponent = biased_exponent - 1023; // f64 subnormals end up far below min_exp, the quantum then is the subnormal one
+    let quantum_exponent = (exponent - mantissa_digits as i32 + 1).max(min_exp - mantissa_digits as i32);
+
+    // Build the power of two from its bits, 2f64.powi() would underflow to zero for the f64 subnormal range
+    let quantum = if quantum_exponent >= -1022 {
+        f64::from_bits(((quantum_exponent + 1023) as u64) << 52)
+    } else {
+        f64::from_bits(1u64 << (quantum_exponent + 1074))
+    };
+
+    (val / quantum).trunc() * quantum
 }
 
-impl FloatType for f16 {
-    fn from_f64(val: f64) -> Self {
-        val as f16
+/*
+   Rounds the integer `val` to `mantissa_digits` significant bits (at most 53), once, straight from its exact value.
+   Going through `val as f64` first would round twice for magnitudes above 2^53: to nearest it can land on the wrong side of a tie,
+   and toward zero the f64 step can round up (2^60 - 1 becomes 2^60), away from zero. The result is exact in f64 and in the
+   narrower float type (unless it is out of its range), so converting it with `from_f64` does not round again.
+*/
+pub(crate) fn round_integer_to_precision(val: i128, mantissa_digits: u32, rounding: Rounding) -> f64 {
+    let magnitude = val.unsigned_abs();
+    let bits = u128::BITS - magnitude.leading_zeros();
+
+    let rounded = if bits <= mantissa_digits {
+        magnitude
+    } else {
+        let shift = bits - mantissa_digits;
+        let kept = magnitude >> shift;
+        let rest = magnitude & ((1u128 << shift) - 1);
+        let half = 1u128 << (shift - 1);
+
+        let kept = match rounding {
+            Rounding::NearestEven if rest > half || (rest == half && kept & 1 == 1) => kept + 1,
+            _ => kept,
+        };
+
+        // The magnitude is at most 2^127, rounding up cannot overflow
+        kept << shift
+    };
+
+    if val < 0 {
+        -(rounded as f64)
+    } else {
+        rounded as f64
     }
 }
 
+macro_rules! impl_signed_int {
+    ($($t:ty),*) => {
+        $(
+            impl Signed for $t {
+                fn abs(self) -> Self {
+                    <$t>::abs(self)
+                }
+
+                fn signum(self) -> Self {
+                    <$t>::signum(self)
+                }
+
+                fn is_positive(self) -> bool {
+                    <$t>::is_positive(self)
+                }
+
+                fn is_negative(self) -> bool {
+                    <$t>::is_negative(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_signed_int!(i8, i16, i32, i64, i128, isize);
+
+macro_rules! impl_float {
+    ($($t:ty),*) => {
+        $(
+            impl Signed for $t {
+                fn abs(self) -> Self {
+                    <$t>::abs(self)
+                }
+
+                fn signum(self) -> Self {
+                    <$t>::signum(self)
+                }
+
+                fn is_positive(self) -> bool {
+                    self > 0.0
+                }
+
+                fn is_negative(self) -> bool {
+                    self < 0.0
+                }
+            }
+
+            impl FloatType for $t {}
+
+            impl Float for $t {
+                fn nan() -> Self {
+                    <$t>::NAN
+                }
+
+                fn infinity() -> Self {
+                    <$t>::INFINITY
+                }
+
+                fn neg_infinity() -> Self {
+                    <$t>::NEG_INFINITY
+                }
+
+                fn epsilon() -> Self {
+                    <$t>::EPSILON
+                }
+
+                fn is_nan(self) -> bool {
+                    <$t>::is_nan(self)
+                }
+
+                fn is_infinite(self) -> bool {
+                    <$t>::is_infinite(self)
+                }
+
+                fn is_finite(self) -> bool {
+                    <$t>::is_finite(self)
+                }
+
+                fn sqrt(self) -> Self {
+                    <$t>::sqrt(self)
+                }
+
+                fn exp(self) -> Self {
+                    <$t>::exp(self)
+                }
+
+                fn ln(self) -> Self {
+                    <$t>::ln(self)
+                }
+
+                fn log2(self) -> Self {
+                    <$t>::log2(self)
+                }
+
+                fn log10(self) -> Self {
+                    <$t>::log10(self)
+                }
+
+                fn ln_1p(self) -> Self {
+                    <$t>::ln_1p(self)
+                }
+
+                fn exp_m1(self) -> Self {
+                    <$t>::exp_m1(self)
+                }
+
+                fn cbrt(self) -> Self {
+                    <$t>::cbrt(self)
+                }
+
+                fn powf(self, n: Self) -> Self {
+                    <$t>::powf(self, n)
+                }
+
+                fn powi(self, n: i32) -> Self {
+                    <$t>::powi(self, n)
+                }
+
+                fn floor(self) -> Self {
+                    <$t>::floor(self)
+                }
+
+                fn ceil(self) -> Self {
+                    <$t>::ceil(self)
+                }
+
+                fn round(self) -> Self {
+                    <$t>::round(self)
+                }
+
+                fn round_ties_even(self) -> Self {
+                    <$t>::round_ties_even(self)
+                }
+
+                fn trunc(self) -> Self {
+                    <$t>::trunc(self)
+                }
+
+                fn sin(self) -> Self {
+                    <$t>::sin(self)
+                }
+
+                fn cos(self) -> Self {
+                    <$t>::cos(self)
+                }
+
+                fn tan(self) -> Self {
+                    <$t>::tan(self)
+                }
+
+                fn asin(self) -> Self {
+                    <$t>::asin(self)
+                }
+
+                fn acos(self) -> Self {
+                    <$t>::acos(self)
+                }
+
+                fn atan(self) -> Self {
+                    <$t>::atan(self)
+                }
+
+                fn sinh(self) -> Self {
+                    <$t>::sinh(self)
+                }
+
+                fn cosh(self) -> Self {
+                    <$t>::cosh(self)
+                }
+
+                fn tanh(self) -> Self {
+                    <$t>::tanh(self)
+                }
+
+                fn asinh(self) -> Self {
+                    <$t>::asinh(self)
+                }
+
+                fn acosh(self) -> Self {
+                    <$t>::acosh(self)
+                }
+
+                fn atanh(self) -> Self {
+                    <$t>::atanh(self)
+                }
+
+                fn max(self, other: Self) -> Self {
+                    <$t>::max(self, other)
+                }
+
+                fn min(self, other: Self) -> Self {
+                    <$t>::min(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_float!(f32, f64);
+
 impl Tensor {
     /// Creates a new `Collective<E>` filled with ones, based on the shape provided by `Dimensions`.
     ///
@@ -80,7 +557,7 @@ impl Tensor {
     /// # Returns
     ///
     /// * `Option<Collective<E>)` with all elements set to `E::default() or if some other trait is defined`, or with no data if the shape is zero.
-    /*pub fn ones<E: One + Copy>(like: Dimensions) -> Option<Collective<E>> {
+    /*pub fn ones<E: Num>(like: Dimensions) -> Option<Collective<E>> {
         let n = like.get_n();
         if n == 0 {
             // Return an empty shape if the size is zero
@@ -97,8 +574,7 @@ impl Tensor {
             shape: Some(Box::new(like)), // Wrap in Box::new()
         })
     }*/
-
-    pub fn ones<E: One + Copy>(like: Dimensions) -> Collective<E> {
+    pub fn ones<E: Num>(like: Dimensions) -> Collective<E> {
         let n = like.get_n();
         if n == 0 {
             // Return an empty shape if the size is zero
@@ -262,15 +738,15 @@ impl Tensor {
         }
     }
 
-    /// Creates a new `Collective<E>` filled with ones, based on the shape provided by `Dimensions`.
-    ///
-    /// # Arguments
-    ///
-    /// * `like` - A `Dimensions` object specifying the desired shape of the one-filled array.
-    ///
-    /// # Returns
-    ///
-    /// * `Some(Collective<E>)` with all elements set to `E::default() or if some other trait is defined`, or with no data if the shape is zero.
+    // Creates a new `Collective<E>` filled with ones, based on the shape provided by `Dimensions`.
+    //
+    // # Arguments
+    //
+    // * `like` - A `Dimensions` object specifying the desired shape of the one-filled array.
+    //
+    // # Returns
+    //
+    // * `Some(Collective<E>)` with all elements set to `E::default() or if some other trait is defined`, or with no data if the shape is zero.
     /*pub fn ones_old<E: One/*Default*/ + Copy>(like: Dimensions) -> Option<Collective<E>> {
         // You can allocate a Box<[E]> of zeros based on `like` shape
 
@@ -290,15 +766,15 @@ impl Tensor {
         })
     }*/
 
-    /// Creates a new `Collective<E>` filled with zeros, based on the shape provided by `Dimensions`.
-    ///
-    /// # Arguments
-    ///
-    /// * `like` - A `Dimensions` object specifying the desired shape of the zero-filled array.
-    ///
-    /// # Returns
-    ///
-    /// * `Some(Collective<E>)` with all elements set to `E::default()`, or with no data if the shape is zero.
+    // Creates a new `Collective<E>` filled with zeros, based on the shape provided by `Dimensions`.
+    //
+    // # Arguments
+    //
+    // * `like` - A `Dimensions` object specifying the desired shape of the zero-filled array.
+    //
+    // # Returns
+    //
+    // * `Some(Collective<E>)` with all elements set to `E::default()`, or with no data if the shape is zero.
     /*pub fn zeros<E: Zero/*Default*/ + Copy>(like: Dimensions) -> Option<Collective<E>> {
         // You can allocate a Box<[E]> of zeros based on `like` shape
 
@@ -350,7 +826,7 @@ impl Tensor {
      *   biases in the HRM's HLM and LLM modules.
      * - Ensure the `Dimensions` struct is properly configured to match the expected tensor shape for the target use case.
      */
-    /*pub fn zeros<E: Zero + Copy>(like: Dimensions) -> Option<Collective<E>> {
+    /*pub fn zeros<E: Num>(like: Dimensions) -> Option<Collective<E>> {
         let n = like.get_n();
         if n == 0 {
             // Return an empty shape if the size is zero
@@ -368,7 +844,7 @@ impl Tensor {
         })
     }*/
 
-    pub fn zeros<E: Zero + Copy>(like: Dimensions) -> Collective<E> {
+    pub fn zeros<E: Num>(like: Dimensions) -> Collective<E> {
         let n = like.get_n();
         if n == 0 {
             // Return an empty shape if the size is zero
@@ -409,13 +885,85 @@ impl Tensor {
     */
     pub fn normalize<E>(data: &mut Collective<E>, scale_denominator: E, offset: E)
     where
-        E: FloatType + Copy + Default + std::ops::Div<Output = E> + std::ops::Add<Output = E>,
+        E: Float,
     {
-        // We iterate using a simple loop or iter_mut() to modify the values directly
-        for i in 0..data.get_shape().get_n() {
-            data[i] = data[i] / scale_denominator + offset;
-        }
+        // Modify the values directly, see apply.rs
+        data.map_inplace(|value| *value = *value / scale_denominator + offset);
 
         // Return nothing because the caller's data is already updated
     }
+
+    /*
+      The "Non-Destructive" variant of `normalize`.
+      The raw integer data (e.g. u8 pixels) is left untouched and a new float Collective is allocated for the result:
+      $$pixel_{float} = \left(\frac{pixel}{scale\_denominator}\right) + offset$$
+
+      Pass (127.5, -1.0) for the [-1, 1] range or (255.0, 0.0) for the [0, 1] range of u8 data.
+
+      @param
+      - `data`: A reference to a `Collective<I>` holding the raw values, usually an integer type such as u8.
+      - `scale_denominator`: The value by which to divide the data.
+      - `offset`: The value to add to the data after division.
+
+      @returns
+      - `Collective<E>`: A new float Collective with a clone of the shape. If `data` has no data allocated, neither has the result.
+    */
+    pub fn normalized<I, E>(data: &Collective<I>, scale_denominator: E, offset: E) -> Collective<E>
+    where
+        I: Num,
+        E: Float,
+    {
+        let allocation = data.data.as_ref().map(|raw| {
+            raw.iter()
+                .map(|&value| E::from_f64(value.to_f64()) / scale_denominator + offset)
+                .collect::<Vec<E>>()
+                .into_boxed_slice()
+        });
+
+        Collective {
+            data: allocation,
+            shape: data.shape.clone(),
+        }
+    }
+
+    /*
+      The inverse of `normalize`/`normalized`, maps [-1, 1] or [0, 1] data back to pixel values, e.g. to visualize model outputs:
+      $$pixel = (pixel_{float} - offset) \times scale\_denominator$$
+
+      Pass the same `scale_denominator` and `offset` that were used to normalize the data.
+      Results are rounded to the nearest integer (ties to even) and clamped to the range of `I`, so model outputs that
+      overshoot the normalized range (e.g. 1.02) still end up as valid pixels (255 for u8). NaN becomes 0.
+
+      @param
+      - `data`: A reference to a `Collective<E>` containing normalized float data.
+      - `scale_denominator`: The value the data was divided by.
+      - `offset`: The value that was added to the data after division.
+
+      @returns
+      - `Collective<I>`: A new Collective of pixel values with a clone of the shape. If `data` has no data allocated, neither has the result.
+    */
+    pub fn denormalize<E, I>(data: &Collective<E>, scale_denominator: E, offset: E) -> Collective<I>
+    where
+        E: Float,
+        I: Num + Bounded,
+    {
+        let allocation = data.data.as_ref().map(|normalized| {
+            normalized
+                .iter()
+                .map(|&value| {
+                    cast_element::<E, I>(
+                        (value - offset) * scale_denominator,
+                        Rounding::NearestEven,
+                        Saturation::Saturate,
+                    )
+                })
+                .collect::<Vec<I>>()
+                .into_boxed_slice()
+        });
+
+        Collective {
+            data: allocation,
+            shape: data.shape.clone(),
+        }
+    }
 }
@@ -0,0 +1,714 @@
+/*
+ * Numrs/src/optim.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Optimizers
+   ----------
+   An optimizer turns the gradients of a training step into updates of the weights. The weights are updated in place,
+   like `Tensor::normalize` updates pixels in place (see num.rs), no new Collective is allocated per step:
+
+       let mut optimizer = Adam::new(1e-3).with_weight_decay(1e-4);
+
+       for step in 0..steps {
+           // ... forward and backward pass, giving one gradient per weight
+           optim::clip_grad_norm(&mut [&mut weight_gradient, &mut bias_gradient], 1.0);
+           optimizer.step(&mut [&mut weights, &mut bias], &[&weight_gradient, &bias_gradient]);
+       }
+
+   With autograd (see autograd.rs) the gradients are taken from the Variables, and the values updated through them:
+
+       loss.backward();
+       optimizer.step_variables(&[w.clone(), b.clone()]);
+       w.zero_grad();
+       b.zero_grad();
+
+   The parameters are identified by their position, parameter i of every step must be the same weight. Optimizers with
+   state (momentum, moment estimates) create a buffer for it on the first step of a parameter.
+
+   - SGD       p -= lr * g, with optional momentum (b = momentum * b + (1 - dampening) * g) and Nesterov momentum
+   - Adam      m = beta1 * m + (1 - beta1) * g, v = beta2 * v + (1 - beta2) * g^2,
+               p -= lr * m_hat / (sqrt(v_hat) + eps) with the bias corrected m_hat = m / (1 - beta1^t), v_hat = v / (1 - beta2^t)
+   - AdamW     Adam with decoupled weight decay, p -= lr * weight_decay * p before the Adam update
+   - RMSProp   v = alpha * v + (1 - alpha) * g^2, p -= lr * g / (sqrt(v) + eps), optionally centered and with momentum
+
+   Weight decay of SGD, Adam and RMSProp is L2 regularization, weight_decay * p is added to the gradient.
+   The defaults are PyTorch's. The update is computed in f64, the state buffers are kept as f64 whatever the element type.
+
+   Gradient clipping (before the step):
+   - clip_grad_norm(gradients, max_norm)    scales all the gradients together so that their global L2 norm is at most max_norm
+   - clip_grad_value(gradients, limit)      limits every element to [-limit, limit]
+
+   Learning rate schedules give the learning rate of a step (or epoch), set it with `LearningRate::set_learning_rate`:
+
+       let schedule = WarmupSchedule::new(500, CosineSchedule::new(3e-4, 1e-5, 10_000));
+       optimizer.set_learning_rate(schedule.learning_rate(step));
+
+   - StepSchedule      initial * gamma^(step / step_size), e.g. halved every 10 epochs
+   - CosineSchedule    from initial down to minimum along half a cosine over total_steps, minimum after that
+   - WarmupSchedule    rises linearly to the first learning rate of another schedule over warmup_steps, then follows it
+   - a plain f64       a constant learning rate
+*/
+
+use super::{autograd::Variable, collective::Collective, num::Float};
+use std::f64::consts::PI;
+
+/*
+   The learning rate of an optimizer, apart from `Optimizer` so that it does not depend on the element type.
+*/
+pub trait LearningRate {
+    fn learning_rate(&self) -> f64;
+
+    /// # Panics
+    /// If `learning_rate` is negative.
+    fn set_learning_rate(&mut self, learning_rate: f64);
+}
+
+/*
+   An update rule. `update` applies it to one parameter, `step` and `step_variables` to all the parameters of a model.
+*/
+pub trait Optimizer<E: Float + 'static>: LearningRate {
+    /// Updates the parameter at position `index` in place, using and updating its state.
+    ///
+    /// # Panics
+    /// If the gradient and the parameter have a different number of elements, or the parameter at `index` has a different
+    /// number of elements than on earlier steps.
+    fn update(&mut self, index: usize, parameter: &mut Collective<E>, gradient: &Collective<E>);
+
+    /// Updates every parameter with the gradient at the same position.
+    ///
+    /// # Panics
+    /// If there are not as many gradients as parameters, or see `update`.
+    fn step(&mut self, parameters: &mut [&mut Collective<E>], gradients: &[&Collective<E>]) {
+        if parameters.len() != gradients.len() {
+            panic!("Optimizer::step(): {} parameters but {} gradients", parameters.len(), gradients.len());
+        }
+
+        for (index, (parameter, gradient)) in parameters.iter_mut().zip(gradients.iter()).enumerate() {
+            self.update(index, parameter, gradient);
+        }
+    }
+
+    /// Updates the values of the Variables with their accumulated gradients. Variables without a gradient are left as they are.
+    ///
+    /// # Panics
+    /// See `update`.
+    fn step_variables(&mut self, variables: &[Variable<E>]) {
+        for (index, variable) in variables.iter().enumerate() {
+            if let Some(gradient) = variable.grad() {
+                self.update(index, &mut variable.value_mut(), &gradient);
+            }
+        }
+    }
+}
+
+/*
+   Stochastic gradient descent with optional (Nesterov) momentum.
+*/
+#[derive(Debug, Clone)]
+pub struct SGD {
+    learning_rate: f64,
+    momentum: f64,
+    dampening: f64,
+    nesterov: bool,
+    weight_decay: f64,
+    // The momentum buffer of every parameter
+    buffers: Vec<Option<Vec<f64>>>,
+}
+
+impl SGD {
+    /// # Panics
+    /// If `learning_rate` is negative.
+    pub fn new(learning_rate: f64) -> Self {
+        check_learning_rate("SGD::new", learning_rate);
+
+        Self {
+            learning_rate,
+            momentum: 0.0,
+            dampening: 0.0,
+            nesterov: false,
+            weight_decay: 0.0,
+            buffers: Vec::new(),
+        }
+    }
+
+    // Fluent setters
+    /// # Panics
+    /// If `momentum` is negative.
+    pub fn with_momentum(mut self, momentum: f64) -> Self {
+        if momentum.is_nan() || momentum < 0.0 {
+            panic!("SGD::with_momentum(): momentum {} is negative", momentum);
+        }
+
+        self.momentum = momentum;
+        self
+    }
+
+    pub fn with_dampening(mut self, dampening: f64) -> Self {
+        self.dampening = dampening;
+        self
+    }
+
+    pub fn with_nesterov(mut self, nesterov: bool) -> Self {
+        self.nesterov = nesterov;
+        self
+    }
+
+    pub fn with_weight_decay(mut self, weight_decay: f64) -> Self {
+        self.weight_decay = weight_decay;
+        self
+    }
+}
+
+impl LearningRate for SGD {
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        check_learning_rate("SGD::set_learning_rate", learning_rate);
+        self.learning_rate = learning_rate;
+    }
+}
+
+impl<E: Float + 'static> Optimizer<E> for SGD {
+    fn update(&mut self, index: usize, parameter: &mut Collective<E>, gradient: &Collective<E>) {
+        let (parameter, gradient) = elements("SGD", index, parameter, gradient);
+
+        let momentum = self.momentum;
+        // The first step starts the buffer at the gradient, without dampening
+        let first = state(&mut self.buffers, index).is_none();
+        let buffer = state_or_insert("SGD", &mut self.buffers, index, parameter.len(), || vec![0.0; parameter.len()]);
+
+        for ((p, &g), b) in parameter.iter_mut().zip(gradient.iter()).zip(buffer.iter_mut()) {
+            let value = p.to_f64();
+            let mut g = g.to_f64() + self.weight_decay * value;
+
+            if momentum != 0.0 {
+                *b = if first { g } else { momentum * *b + (1.0 - self.dampening) * g };
+                g = if self.nesterov { g + momentum * *b } else { *b };
+            }
+
+            *p = E::from_f64(value - self.learning_rate * g);
+        }
+    }
+}
+
+/*
+   Adam, and with decoupled weight decay AdamW.
+*/
+#[derive(Debug, Clone)]
+pub struct Adam {
+    learning_rate: f64,
+    beta1: f64,
+    beta2: f64,
+    epsilon: f64,
+    weight_decay: f64,
+    // AdamW: the decay is applied to the weights instead of being added to the gradient
+    decoupled: bool,
+    states: Vec<Option<AdamState>>,
+}
+
+#[derive(Debug, Clone)]
+struct AdamState {
+    // The number of updates of the parameter, for the bias correction
+    steps: i32,
+    first_moment: Vec<f64>,
+    second_moment: Vec<f64>,
+}
+
+impl Adam {
+    /// # Panics
+    /// If `learning_rate` is negative.
+    pub fn new(learning_rate: f64) -> Self {
+        check_learning_rate("Adam::new", learning_rate);
+
+        Self {
+            learning_rate,
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1e-8,
+            weight_decay: 0.0,
+            decoupled: false,
+            states: Vec::new(),
+        }
+    }
+
+    // Fluent setters
+    /// # Panics
+    /// If either beta is not in [0, 1).
+    pub fn with_betas(mut self, beta1: f64, beta2: f64) -> Self {
+        check_betas("Adam", beta1, beta2);
+
+        self.beta1 = beta1;
+        self.beta2 = beta2;
+        self
+    }
+
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    pub fn with_weight_decay(mut self, weight_decay: f64) -> Self {
+        self.weight_decay = weight_decay;
+        self
+    }
+}
+
+impl LearningRate for Adam {
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        check_learning_rate("Adam::set_learning_rate", learning_rate);
+        self.learning_rate = learning_rate;
+    }
+}
+
+impl<E: Float + 'static> Optimizer<E> for Adam {
+    fn update(&mut self, index: usize, parameter: &mut Collective<E>, gradient: &Collective<E>) {
+        let name = if self.decoupled { "AdamW" } else { "Adam" };
+        let (parameter, gradient) = elements(name, index, parameter, gradient);
+
+        let state = state_or_insert(name, &mut self.states, index, parameter.len(), || AdamState {
+            steps: 0,
+            first_moment: vec![0.0; parameter.len()],
+            second_moment: vec![0.0; parameter.len()],
+        });
+
+        state.steps += 1;
+        let first_correction = 1.0 - self.beta1.powi(state.steps);
+        let second_correction = 1.0 - self.beta2.powi(state.steps);
+
+        for (index, (p, &g)) in parameter.iter_mut().zip(gradient.iter()).enumerate() {
+            let mut value = p.to_f64();
+            let mut g = g.to_f64();
+
+            if self.decoupled {
+                value -= self.learning_rate * self.weight_decay * value;
+            } else {
+                g += self.weight_decay * value;
+            }
+
+            let m = &mut state.first_moment[index];
+            let v = &mut state.second_moment[index];
+            *m = self.beta1 * *m + (1.0 - self.beta1) * g;
+            *v = self.beta2 * *v + (1.0 - self.beta2) * g * g;
+
+            let step = (*m / first_correction) / ((*v / second_correction).sqrt() + self.epsilon);
+
+            *p = E::from_f64(value - self.learning_rate * step);
+        }
+    }
+}
+
+/*
+   Adam with decoupled weight decay (Loshchilov and Hutter), the decay does not pass through the moment estimates.
+*/
+#[derive(Debug, Clone)]
+pub struct AdamW {
+    adam: Adam,
+}
+
+impl AdamW {
+    /// The weight decay defaults to 0.01.
+    ///
+    /// # Panics
+    /// If `learning_rate` is negative.
+    pub fn new(learning_rate: f64) -> Self {
+        let mut adam = Adam::new(learning_rate).with_weight_decay(0.01);
+        adam.decoupled = true;
+
+        Self { adam }
+    }
+
+    // Fluent setters
+    /// # Panics
+    /// If either beta is not in [0, 1).
+    pub fn with_betas(mut self, beta1: f64, beta2: f64) -> Self {
+        self.adam = self.adam.with_betas(beta1, beta2);
+        self
+    }
+
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.adam = self.adam.with_epsilon(epsilon);
+        self
+    }
+
+    pub fn with_weight_decay(mut self, weight_decay: f64) -> Self {
+        self.adam = self.adam.with_weight_decay(weight_decay);
+        self
+    }
+}
+
+impl LearningRate for AdamW {
+    fn learning_rate(&self) -> f64 {
+        self.adam.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        self.adam.set_learning_rate(learning_rate);
+    }
+}
+
+impl<E: Float + 'static> Optimizer<E> for AdamW {
+    fn update(&mut self, index: usize, parameter: &mut Collective<E>, gradient: &Collective<E>) {
+        self.adam.update(index, parameter, gradient);
+    }
+}
+
+/*
+   RMSProp, optionally centered (the gradient is normalized by an estimate of its variance instead of its second moment)
+   and with momentum.
+*/
+#[derive(Debug, Clone)]
+pub struct RMSProp {
+    learning_rate: f64,
+    alpha: f64,
+    epsilon: f64,
+    momentum: f64,
+    centered: bool,
+    weight_decay: f64,
+    states: Vec<Option<RMSPropState>>,
+}
+
+#[derive(Debug, Clone)]
+struct RMSPropState {
+    square_average: Vec<f64>,
+    // Centered only
+    gradient_average: Vec<f64>,
+    // With momentum only
+    momentum_buffer: Vec<f64>,
+}
+
+impl RMSProp {
+    /// # Panics
+    /// If `learning_rate` is negative.
+    pub fn new(learning_rate: f64) -> Self {
+        check_learning_rate("RMSProp::new", learning_rate);
+
+        Self {
+            learning_rate,
+            alpha: 0.99,
+            epsilon: 1e-8,
+            momentum: 0.0,
+            centered: false,
+            weight_decay: 0.0,
+            states: Vec::new(),
+        }
+    }
+
+    // Fluent setters
+    /// # Panics
+    /// If `alpha` is not in [0, 1).
+    pub fn with_alpha(mut self, alpha: f64) -> Self {
+        if !(0.0..1.0).contains(&alpha) {
+            panic!("RMSProp::with_alpha(): alpha {} is not in [0, 1)", alpha);
+        }
+
+        self.alpha = alpha;
+        self
+    }
+
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    /// # Panics
+    /// If `momentum` is negative.
+    pub fn with_momentum(mut self, momentum: f64) -> Self {
+        if momentum.is_nan() || momentum < 0.0 {
+            panic!("RMSProp::with_momentum(): momentum {} is negative", momentum);
+        }
+
+        self.momentum = momentum;
+        self
+    }
+
+    pub fn with_centered(mut self, centered: bool) -> Self {
+        self.centered = centered;
+        self
+    }
+
+    pub fn with_weight_decay(mut self, weight_decay: f64) -> Self {
+        self.weight_decay = weight_decay;
+        self
+    }
+}
+
+impl LearningRate for RMSProp {
+    fn learning_rate(&self) -> f64 {
+        self.learning_rate
+    }
+
+    fn set_learning_rate(&mut self, learning_rate: f64) {
+        check_learning_rate("RMSProp::set_learning_rate", learning_rate);
+        self.learning_rate = learning_rate;
+    }
+}
+
+impl<E: Float + 'static> Optimizer<E> for RMSProp {
+    fn update(&mut self, index: usize, parameter: &mut Collective<E>, gradient: &Collective<E>) {
+        let (parameter, gradient) = elements("RMSProp", index, parameter, gradient);
+
+        let state = state_or_insert("RMSProp", &mut self.states, index, parameter.len(), || RMSPropState {
+            square_average: vec![0.0; parameter.len()],
+            gradient_average: vec![0.0; parameter.len()],
+            momentum_buffer: vec![0.0; parameter.len()],
+        });
+
+        for (index, (p, &g)) in parameter.iter_mut().zip(gradient.iter()).enumerate() {
+            let value = p.to_f64();
+            let g = g.to_f64() + self.weight_decay * value;
+
+            let v = &mut state.square_average[index];
+            *v = self.alpha * *v + (1.0 - self.alpha) * g * g;
+
+            let mut variance = *v;
+            if self.centered {
+                let mean = &mut state.gradient_average[index];
+                *mean = self.alpha * *mean + (1.0 - self.alpha) * g;
+                variance -= *mean * *mean;
+            }
+
+            let mut step = g / (variance.sqrt() + self.epsilon);
+            if self.momentum > 0.0 {
+                let b = &mut state.momentum_buffer[index];
+                *b = self.momentum * *b + step;
+                step = *b;
+            }
+
+            *p = E::from_f64(value - self.learning_rate * step);
+        }
+    }
+}
+
+/// Scales the gradients so that the L2 norm of all their elements together is at most `max_norm`.
+///
+/// # Returns
+/// The norm before clipping.
+pub fn clip_grad_norm<E: Float>(gradients: &mut [&mut Collective<E>], max_norm: f64) -> f64 {
+    let norm = gradients
+        .iter()
+        .flat_map(|gradient| gradient.iter())
+        .map(|g| g.to_f64() * g.to_f64())
+        .sum::<f64>()
+        .sqrt();
+
+    if norm > max_norm {
+        // The small epsilon is PyTorch's, it keeps the scale finite for a zero max_norm
+        let scale = max_norm / (norm + 1e-6);
+
+        for gradient in gradients.iter_mut() {
+            gradient.map_inplace(|g| *g = E::from_f64(g.to_f64() * scale));
+        }
+    }
+
+    norm
+}
+
+/// Limits every element of the gradients to [-`limit`, `limit`].
+///
+/// # Panics
+/// If `limit` is negative.
+pub fn clip_grad_value<E: Float>(gradients: &mut [&mut Collective<E>], limit: f64) {
+    if limit.is_nan() || limit < 0.0 {
+        panic!("optim::clip_grad_value(): limit {} is negative", limit);
+    }
+
+    let limit = E::from_f64(limit);
+
+    for gradient in gradients.iter_mut() {
+        gradient.clip_inplace(-limit, limit);
+    }
+}
+
+/*
+   The learning rate of every step (or epoch), counted from 0.
+*/
+pub trait Schedule {
+    fn learning_rate(&self, step: usize) -> f64;
+}
+
+// A constant learning rate
+impl Schedule for f64 {
+    fn learning_rate(&self, _step: usize) -> f64 {
+        *self
+    }
+}
+
+/*
+   Multiplies the learning rate by gamma every step_size steps.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepSchedule {
+    pub initial: f64,
+    pub step_size: usize,
+    pub gamma: f64,
+}
+
+impl StepSchedule {
+    /// # Panics
+    /// If `step_size` is 0.
+    pub fn new(initial: f64, step_size: usize, gamma: f64) -> Self {
+        if step_size == 0 {
+            panic!("StepSchedule::new(): step_size is 0");
+        }
+
+        Self { initial, step_size, gamma }
+    }
+}
+
+impl Schedule for StepSchedule {
+    fn learning_rate(&self, step: usize) -> f64 {
+        self.initial * self.gamma.powi((step / self.step_size) as i32)
+    }
+}
+
+/*
+   Cosine annealing from initial to minimum over total_steps, without restarts.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CosineSchedule {
+    pub initial: f64,
+    pub minimum: f64,
+    pub total_steps: usize,
+}
+
+impl CosineSchedule {
+    /// # Panics
+    /// If `total_steps` is 0.
+    pub fn new(initial: f64, minimum: f64, total_steps: usize) -> Self {
+        if total_steps == 0 {
+            panic!("CosineSchedule::new(): total_steps is 0");
+        }
+
+        Self { initial, minimum, total_steps }
+    }
+}
+
+impl Schedule for CosineSchedule {
+    fn learning_rate(&self, step: usize) -> f64 {
+        let progress = step.min(self.total_steps) as f64 / self.total_steps as f64;
+
+        self.minimum + (self.initial - self.minimum) * (1.0 + (PI * progress).cos()) / 2.0
+    }
+}
+
+/*
+   A linear warmup in front of another schedule, which starts (at its step 0) once the warmup is over.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WarmupSchedule<S> {
+    pub warmup_steps: usize,
+    pub schedule: S,
+}
+
+impl<S: Schedule> WarmupSchedule<S> {
+    pub fn new(warmup_steps: usize, schedule: S) -> Self {
+        Self { warmup_steps, schedule }
+    }
+}
+
+impl<S: Schedule> Schedule for WarmupSchedule<S> {
+    fn learning_rate(&self, step: usize) -> f64 {
+        if step < self.warmup_steps {
+            // 1 / warmup_steps of the target on the first step, the full target on the first step after the warmup
+            self.schedule.learning_rate(0) * (step + 1) as f64 / self.warmup_steps as f64
+        } else {
+            self.schedule.learning_rate(step - self.warmup_steps)
+        }
+    }
+}
+
+/*
+   The elements of a parameter and its gradient, checked to be allocated and of the same length.
+*/
+fn elements<'a, E>(name: &str, index: usize, parameter: &'a mut Collective<E>, gradient: &'a Collective<E>) -> (&'a mut [E], &'a [E]) {
+    match (parameter.data.as_deref_mut(), gradient.data.as_deref()) {
+        (Some(parameter), Some(gradient)) if parameter.len() == gradient.len() => (parameter, gradient),
+        (Some(parameter), Some(gradient)) => panic!(
+            "{}::update(): The gradient of parameter {} has {} elements, the parameter has {}",
+            name,
+            index,
+            gradient.len(),
+            parameter.len()
+        ),
+        _ => panic!("{}::update(): Parameter {} or its gradient is not allocated", name, index),
+    }
+}
+
+fn state<S>(states: &mut [Option<S>], index: usize) -> Option<&mut S> {
+    states.get_mut(index).and_then(Option::as_mut)
+}
+
+/*
+   The state of the parameter at `index`, created on its first update. `len` is the number of elements of the parameter,
+   which must not change, `S` is assumed to hold buffers of that length.
+*/
+fn state_or_insert<'a, S: StateLen>(
+    name: &str,
+    states: &'a mut Vec<Option<S>>,
+    index: usize,
+    len: usize,
+    create: impl FnOnce() -> S,
+) -> &'a mut S {
+    if states.len() <= index {
+        states.resize_with(index + 1, || None);
+    }
+
+    let state = states[index].get_or_insert_with(create);
+
+    if state.len() != len {
+        panic!(
+            "{}::update(): Parameter {} has {} elements, its state was created for {}",
+            name,
+            index,
+            len,
+            state.len()
+        );
+    }
+
+    state
+}
+
+// The number of elements the state of a parameter was created for
+trait StateLen {
+    fn len(&self) -> usize;
+}
+
+impl StateLen for Vec<f64> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+}
+
+impl StateLen for AdamState {
+    fn len(&self) -> usize {
+        self.first_moment.len()
+    }
+}
+
+impl StateLen for RMSPropState {
+    fn len(&self) -> usize {
+        self.square_average.len()
+    }
+}
+
+fn check_learning_rate(method: &str, learning_rate: f64) {
+    if learning_rate.is_nan() || learning_rate < 0.0 {
+        panic!("{}(): Learning rate {} is negative", method, learning_rate);
+    }
+}
+
+fn check_betas(name: &str, beta1: f64, beta2: f64) {
+    for beta in [beta1, beta2] {
+        if !(0.0..1.0).contains(&beta) {
+            panic!("{}::with_betas(): beta {} is not in [0, 1)", name, beta);
+        }
+    }
+}
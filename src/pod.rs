@@ -0,0 +1,70 @@
+/*
+ * Numrs/src/pod.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Plain old data element types
+   ----------------------------
+   The file formats (npy.rs, idx.rs, safetensors.rs) all store elements as their raw bytes, in a fixed byte order.
+   `Pod` is the part they share: the size of an element and its conversion from and to little and big endian bytes.
+   Each format adds its own trait on top for its dtype names (`NpyElement`, `IdxElement`, `SafetensorsElement`).
+
+   The trait is sealed and only implemented for the primitive integers and floats (and f16/bf16 with their features).
+   All of them are plain old data, every bit pattern is a valid value, which is what lets `MappedCollective` (see mmap.rs) and
+   `Safetensors::view` view the bytes of a mapped file as elements without copying them.
+*/
+
+use super::num::Num;
+
+pub trait Pod: Num + sealed::Sealed {
+    /// Size of one element in bytes.
+    const SIZE: usize;
+
+    fn from_le_slice(bytes: &[u8]) -> Self;
+
+    fn from_be_slice(bytes: &[u8]) -> Self;
+
+    fn extend_le_bytes(self, buffer: &mut Vec<u8>);
+
+    fn extend_be_bytes(self, buffer: &mut Vec<u8>);
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_pod {
+    ($($t:ty),*) => {
+        $(
+            impl sealed::Sealed for $t {}
+
+            impl Pod for $t {
+                const SIZE: usize = std::mem::size_of::<$t>();
+
+                fn from_le_slice(bytes: &[u8]) -> Self {
+                    <$t>::from_le_bytes(bytes.try_into().unwrap())
+                }
+
+                fn from_be_slice(bytes: &[u8]) -> Self {
+                    <$t>::from_be_bytes(bytes.try_into().unwrap())
+                }
+
+                fn extend_le_bytes(self, buffer: &mut Vec<u8>) {
+                    buffer.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn extend_be_bytes(self, buffer: &mut Vec<u8>) {
+                    buffer.extend_from_slice(&self.to_be_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_pod!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+#[cfg(feature = "f16")]
+impl_pod!(half::f16);
+#[cfg(feature = "bf16")]
+impl_pod!(half::bf16);
@@ -0,0 +1,325 @@
+/*
+ * Numrs/src/precision.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Reduced precision element types
+   -------------------------------
+   Rust's native `f16` is still unstable, depending on it would force every crate that uses Numrs onto a nightly toolchain.
+   Instead, the half-precision types come from the `half` crate, which is a software implementation that builds on stable Rust.
+   Each type is only compiled in when its cargo feature is enabled:
+
+       [dependencies]
+       Numrs = { version = "0.1.0", features = ["f16", "bf16"] }
+
+   - `f16`  : IEEE 754 binary16, 1 sign bit, 5 exponent bits, 10 mantissa bits. More precision, but the range ends at 65504.
+   - `bf16` : bfloat16, 1 sign bit, 8 exponent bits, 7 mantissa bits. The upper half of an f32, same range as f32 with less
+              precision, which is why it is the usual choice for mixed-precision training.
+
+   A reduced precision element type is a storage format, every arithmetic operation is carried out in f32 and rounded back.
+   This module gives these types the same numeric traits (`Num`, `Float`, ...) as the primitive types, so `Tensor::zeros::<f16>`,
+   `Tensor::normalize` and friends work unchanged, and adds fast conversions between reduced precision and f32/f64 Collectives.
+   Conversions between any other pair of element types (f16 <-> bf16, f32 -> bf16 with truncation, ...) go through `Collective::astype`.
+*/
+
+use super::{
+    collective::Collective,
+    header::Rounding,
+    num::{round_integer_to_precision, truncate_to_precision, Bounded, Dtype, Float, FloatType, Num, NumCast, One, Signed, Zero},
+};
+use half::slice::HalfFloatSliceExt;
+
+#[cfg(feature = "bf16")]
+pub use half::bf16;
+#[cfg(feature = "f16")]
+pub use half::f16;
+
+/*
+   Implements the numeric trait hierarchy (see num.rs) for a type from the `half` crate.
+   The type has no `as` casts and no literals, so everything goes through its `from_f32`/`to_f32` conversions.
+*/
+macro_rules! impl_half_float {
+    ($($t:ty),*) => {
+        $(
+            impl Zero for $t {
+                fn zero() -> Self {
+                    <$t>::ZERO
+                }
+
+                fn is_zero(&self) -> bool {
+                    *self == <$t>::ZERO
+                }
+            }
+
+            impl One for $t {
+                fn one() -> Self {
+                    <$t>::ONE
+                }
+            }
+
+            impl NumCast for $t {
+                const IS_INTEGER: bool = false;
+                const MANTISSA_DIGITS: u32 = <$t>::MANTISSA_DIGITS;
+
+                fn from_f64(val: f64) -> Self {
+                    <$t>::from_f64(val)
+                }
+
+                fn from_f64_truncated(val: f64) -> Self {
+                    <$t>::from_f64(truncate_to_precision(val, <$t>::MANTISSA_DIGITS, <$t>::MIN_EXP))
+                }
+
+                fn to_f64(self) -> f64 {
+                    <$t>::to_f64(self)
+                }
+
+                fn from_i128(val: i128) -> Self {
+                    <$t>::from_f64(round_integer_to_precision(val, <$t>::MANTISSA_DIGITS, Rounding::NearestEven))
+                }
+
+                fn to_i128(self) -> i128 {
+                    <$t>::to_f64(self) as i128
+                }
+            }
+
+            impl Bounded for $t {
+                fn min_value() -> Self {
+                    <$t>::MIN
+                }
+
+                fn max_value() -> Self {
+                    <$t>::MAX
+                }
+            }
+
+            impl Num for $t {}
+
+            impl Signed for $t {
+                fn abs(self) -> Self {
+                    <$t>::from_f32(self.to_f32().abs())
+                }
+
+                fn signum(self) -> Self {
+                    <$t>::from_f32(self.to_f32().signum())
+                }
+
+                fn is_positive(self) -> bool {
+                    self > <$t>::ZERO
+                }
+
+                fn is_negative(self) -> bool {
+                    self < <$t>::ZERO
+                }
+            }
+
+            impl FloatType for $t {}
+
+            impl Dtype for $t {
+                const NAME: &'static str = stringify!($t);
+            }
+
+            impl Float for $t {
+                fn nan() -> Self {
+                    <$t>::NAN
+                }
+
+                fn infinity() -> Self {
+                    <$t>::INFINITY
+                }
+
+                fn neg_infinity() -> Self {
+                    <$t>::NEG_INFINITY
+                }
+
+                fn epsilon() -> Self {
+                    <$t>::EPSILON
+                }
+
+                fn is_nan(self) -> bool {
+                    <$t>::is_nan(self)
+                }
+
+                fn is_infinite(self) -> bool {
+                    <$t>::is_infinite(self)
+                }
+
+                fn is_finite(self) -> bool {
+                    <$t>::is_finite(self)
+                }
+
+                fn sqrt(self) -> Self {
+                    <$t>::from_f32(self.to_f32().sqrt())
+                }
+
+                fn exp(self) -> Self {
+                    <$t>::from_f32(self.to_f32().exp())
+                }
+
+                fn ln(self) -> Self {
+                    <$t>::from_f32(self.to_f32().ln())
+                }
+
+                fn log2(self) -> Self {
+                    <$t>::from_f32(self.to_f32().log2())
+                }
+
+                fn log10(self) -> Self {
+                    <$t>::from_f32(self.to_f32().log10())
+                }
+
+                fn ln_1p(self) -> Self {
+                    <$t>::from_f32(self.to_f32().ln_1p())
+                }
+
+                fn exp_m1(self) -> Self {
+                    <$t>::from_f32(self.to_f32().exp_m1())
+                }
+
+                fn cbrt(self) -> Self {
+                    <$t>::from_f32(self.to_f32().cbrt())
+                }
+
+                fn powf(self, n: Self) -> Self {
+                    <$t>::from_f32(self.to_f32().powf(n.to_f32()))
+                }
+
+                fn powi(self, n: i32) -> Self {
+                    <$t>::from_f32(self.to_f32().powi(n))
+                }
+
+                fn floor(self) -> Self {
+                    <$t>::from_f32(self.to_f32().floor())
+                }
+
+                fn ceil(self) -> Self {
+                    <$t>::from_f32(self.to_f32().ceil())
+                }
+
+                fn round(self) -> Self {
+                    <$t>::from_f32(self.to_f32().round())
+                }
+
+                fn round_ties_even(self) -> Self {
+                    <$t>::from_f32(self.to_f32().round_ties_even())
+                }
+
+                fn trunc(self) -> Self {
+                    <$t>::from_f32(self.to_f32().trunc())
+                }
+
+                fn sin(self) -> Self {
+                    <$t>::from_f32(self.to_f32().sin())
+                }
+
+                fn cos(self) -> Self {
+                    <$t>::from_f32(self.to_f32().cos())
+                }
+
+                fn tan(self) -> Self {
+                    <$t>::from_f32(self.to_f32().tan())
+                }
+
+                fn asin(self) -> Self {
+                    <$t>::from_f32(self.to_f32().asin())
+                }
+
+                fn acos(self) -> Self {
+                    <$t>::from_f32(self.to_f32().acos())
+                }
+
+                fn atan(self) -> Self {
+                    <$t>::from_f32(self.to_f32().atan())
+                }
+
+                fn sinh(self) -> Self {
+                    <$t>::from_f32(self.to_f32().sinh())
+                }
+
+                fn cosh(self) -> Self {
+                    <$t>::from_f32(self.to_f32().cosh())
+                }
+
+                fn tanh(self) -> Self {
+                    <$t>::from_f32(self.to_f32().tanh())
+                }
+
+                fn asinh(self) -> Self {
+                    <$t>::from_f32(self.to_f32().asinh())
+                }
+
+                fn acosh(self) -> Self {
+                    <$t>::from_f32(self.to_f32().acosh())
+                }
+
+                fn atanh(self) -> Self {
+                    <$t>::from_f32(self.to_f32().atanh())
+                }
+
+                fn max(self, other: Self) -> Self {
+                    <$t>::max(self, other)
+                }
+
+                fn min(self, other: Self) -> Self {
+                    <$t>::min(self, other)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "f16")]
+impl_half_float!(f16);
+#[cfg(feature = "bf16")]
+impl_half_float!(bf16);
+
+/*
+   Conversions between reduced precision and f32/f64 Collectives.
+   The `half` crate converts whole slices at once (using the CPU's F16C instructions where available), which is much faster than
+   converting element by element. Narrowing conversions round to nearest-even, values out of range become infinity.
+   The shape is cloned, the source Collective is left untouched.
+*/
+macro_rules! impl_half_conversions {
+    ($($t:ty),*) => {
+        $(
+            impl Collective<$t> {
+                pub fn from_f32(source: &Collective<f32>) -> Self {
+                    let data = source.data.as_ref().map(|data| {
+                        let mut buffer = vec![<$t>::ZERO; data.len()].into_boxed_slice();
+                        buffer.convert_from_f32_slice(data);
+                        buffer
+                    });
+
+                    Collective::new(data, source.shape.clone())
+                }
+
+                pub fn from_f64(source: &Collective<f64>) -> Self {
+                    let data = source.data.as_ref().map(|data| {
+                        let mut buffer = vec![<$t>::ZERO; data.len()].into_boxed_slice();
+                        buffer.convert_from_f64_slice(data);
+                        buffer
+                    });
+
+                    Collective::new(data, source.shape.clone())
+                }
+
+                pub fn to_f32(&self) -> Collective<f32> {
+                    let data = self.data.as_ref().map(|data| data.to_f32_vec().into_boxed_slice());
+
+                    Collective::new(data, self.shape.clone())
+                }
+
+                pub fn to_f64(&self) -> Collective<f64> {
+                    let data = self.data.as_ref().map(|data| data.to_f64_vec().into_boxed_slice());
+
+                    Collective::new(data, self.shape.clone())
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "f16")]
+impl_half_conversions!(f16);
+#[cfg(feature = "bf16")]
+impl_half_conversions!(bf16);
@@ -0,0 +1,574 @@
+/*
+ * Numrs/src/preprocessing.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Feature scalers
+   ---------------
+   `Tensor::normalize` applies a fixed divisor and offset, which is fine for u8 pixels but not for features whose range is only
+   known from the training data. A scaler learns per-feature statistics once (`fit`) and then applies the same affine map to any
+   number of Collectives (`transform`), e.g. the training set, the validation set and later the inputs seen during inference:
+
+       scaled = (value - center) / scale
+       value  = scaled * scale + center      (inverse_transform)
+
+   - `StandardScaler` : center = mean,   scale = standard deviation  -> zero mean, unit variance
+   - `MinMaxScaler`   : center and scale chosen so that [min, max] maps onto the feature range, [0, 1] by default
+   - `RobustScaler`   : center = median, scale = interquartile range -> not thrown off by outliers
+
+   The axis says which values belong to the same feature (see header.rs), a Collective is seen as a (rows x columns) matrix,
+   where rows is the product of all but the last dimension:
+   - Axis::Rows    -> statistics are computed going down the rows, one set per column (the usual "samples x features" layout)
+   - Axis::Columns -> statistics are computed going across the columns, one set per row
+   - Axis::None    -> a single set of statistics for all elements
+
+   The fitted statistics live in `ScalerParameters` (kept as f64, whatever the element type) and can be saved to a small text file,
+   so the exact same scaling can be loaded and applied at inference time without the training data.
+*/
+
+use super::{
+    collective::Collective,
+    header::Axis,
+    num::{Float, Num},
+};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+/*
+   The fitted state shared by all scalers, one `center` and one `scale` per feature.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScalerParameters {
+    pub axis: Axis,
+    pub center: Vec<f64>,
+    pub scale: Vec<f64>,
+}
+
+impl ScalerParameters {
+    /*
+       Writes the parameters as plain text, one `key value...` line each.
+       f64 values are written in their shortest form that parses back to the exact same value.
+
+       # numrs scaler parameters
+       kind StandardScaler
+       axis Rows
+       center 0.5 12
+       scale 0.25 3.5
+    */
+    pub fn write_to<W: Write>(&self, kind: &str, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "# numrs scaler parameters")?;
+        writeln!(writer, "kind {}", kind)?;
+        writeln!(writer, "axis {:?}", self.axis)?;
+        writeln!(writer, "center {}", join(&self.center))?;
+        writeln!(writer, "scale {}", join(&self.scale))?;
+
+        Ok(())
+    }
+
+    /*
+       Reads parameters written by `write_to`.
+       Returns an `InvalidData` error if the file was written by a different kind of scaler or is malformed.
+    */
+    pub fn read_from<R: BufRead>(kind: &str, reader: R) -> io::Result<Self> {
+        let mut found_kind = None;
+        let mut axis = None;
+        let mut center = None;
+        let mut scale = None;
+
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+
+            match key {
+                "kind" => found_kind = Some(value.trim().to_string()),
+                "axis" => axis = Some(parse_axis(value.trim())?),
+                "center" => center = Some(parse_values(value)?),
+                "scale" => scale = Some(parse_values(value)?),
+                _ => return Err(invalid_data(format!("unknown key \"{}\"", key))),
+            }
+        }
+
+        match found_kind {
+            Some(found) if found == kind => {}
+            Some(found) => {
+                return Err(invalid_data(format!("expected {} parameters, found {}", kind, found)))
+            }
+            None => return Err(invalid_data("missing \"kind\" line".to_string())),
+        }
+
+        let parameters = ScalerParameters {
+            axis: axis.ok_or_else(|| invalid_data("missing \"axis\" line".to_string()))?,
+            center: center.ok_or_else(|| invalid_data("missing \"center\" line".to_string()))?,
+            scale: scale.ok_or_else(|| invalid_data("missing \"scale\" line".to_string()))?,
+        };
+
+        if parameters.center.len() != parameters.scale.len() {
+            return Err(invalid_data(format!(
+                "center has {} values but scale has {}",
+                parameters.center.len(),
+                parameters.scale.len()
+            )));
+        }
+
+        Ok(parameters)
+    }
+}
+
+/*
+   Everything a scaler has in common. An implementation only has to compute the statistics (`fit`) and hand out its
+   parameters, transforming and saving/loading is the same affine map for all of them.
+*/
+pub trait Scaler: Sized {
+    /// Name written to (and expected in) saved parameter files.
+    const KIND: &'static str;
+
+    /// Computes per-feature statistics of `data` along `axis` and stores them in the scaler.
+    fn fit<E: Num>(&mut self, data: &Collective<E>, axis: Axis) -> &mut Self;
+
+    /// The fitted parameters, or `None` if the scaler has not been fitted yet.
+    fn parameters(&self) -> Option<&ScalerParameters>;
+
+    /// Creates a fitted scaler from previously computed (e.g. loaded) parameters.
+    fn from_parameters(parameters: ScalerParameters) -> Self;
+
+    /// Returns a new Collective with `(value - center) / scale` applied to every element.
+    ///
+    /// # Panics
+    /// If the scaler has not been fitted, or `data` does not have the number of features the scaler was fitted on.
+    fn transform<E: Float>(&self, data: &Collective<E>) -> Collective<E> {
+        apply(fitted(self), data, |value, center, scale| (value - center) / scale)
+    }
+
+    /// Returns a new Collective with `value * scale + center` applied to every element, undoing `transform`.
+    ///
+    /// # Panics
+    /// If the scaler has not been fitted, or `data` does not have the number of features the scaler was fitted on.
+    fn inverse_transform<E: Float>(&self, data: &Collective<E>) -> Collective<E> {
+        apply(fitted(self), data, |value, center, scale| value * scale + center)
+    }
+
+    fn fit_transform<E: Float>(&mut self, data: &Collective<E>, axis: Axis) -> Collective<E> {
+        self.fit(data, axis).transform(data)
+    }
+
+    fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let parameters = self.parameters().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("{} has not been fitted", Self::KIND))
+        })?;
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        parameters.write_to(Self::KIND, &mut writer)?;
+        writer.flush()
+    }
+
+    fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+
+        Ok(Self::from_parameters(ScalerParameters::read_from(Self::KIND, reader)?))
+    }
+}
+
+/*
+   Standardization: zero mean and unit variance per feature.
+   The standard deviation is the population one (divides by n), features with zero variance are left unscaled.
+*/
+#[derive(Debug, Clone)]
+pub struct StandardScaler {
+    with_mean: bool,
+    with_std: bool,
+    parameters: Option<ScalerParameters>,
+}
+
+impl StandardScaler {
+    pub fn new() -> Self {
+        Self {
+            with_mean: true,
+            with_std: true,
+            parameters: None,
+        }
+    }
+
+    // Fluent setters, `false` replaces the statistic by the identity (center 0, scale 1)
+    pub fn with_mean(mut self, with_mean: bool) -> Self {
+        self.with_mean = with_mean;
+        self
+    }
+
+    pub fn with_std(mut self, with_std: bool) -> Self {
+        self.with_std = with_std;
+        self
+    }
+}
+
+impl Default for StandardScaler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scaler for StandardScaler {
+    const KIND: &'static str = "StandardScaler";
+
+    fn fit<E: Num>(&mut self, data: &Collective<E>, axis: Axis) -> &mut Self {
+        let features = features(data, axis, Self::KIND);
+        let (mut center, mut scale) = (Vec::new(), Vec::new());
+
+        for values in features {
+            let n = values.len() as f64;
+            let mean = values.iter().sum::<f64>() / n;
+            let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / n;
+
+            center.push(if self.with_mean { mean } else { 0.0 });
+            scale.push(if self.with_std { non_zero(variance.sqrt()) } else { 1.0 });
+        }
+
+        self.parameters = Some(ScalerParameters { axis, center, scale });
+        self
+    }
+
+    fn parameters(&self) -> Option<&ScalerParameters> {
+        self.parameters.as_ref()
+    }
+
+    fn from_parameters(parameters: ScalerParameters) -> Self {
+        Self {
+            parameters: Some(parameters),
+            ..Self::new()
+        }
+    }
+}
+
+/*
+   Min-Max scaling: maps [min, max] of every feature onto `feature_range`, [0, 1] unless set otherwise.
+   scaled = (value - min) / (max - min) * (high - low) + low, rewritten as (value - center) / scale with
+   scale = (max - min) / (high - low) and center = min - low * scale. Constant features are only shifted.
+*/
+#[derive(Debug, Clone)]
+pub struct MinMaxScaler {
+    feature_range: (f64, f64),
+    parameters: Option<ScalerParameters>,
+}
+
+impl MinMaxScaler {
+    pub fn new() -> Self {
+        Self {
+            feature_range: (0.0, 1.0),
+            parameters: None,
+        }
+    }
+
+    pub fn with_feature_range(mut self, low: f64, high: f64) -> Self {
+        if low >= high {
+            panic!(
+                "MinMaxScaler::with_feature_range(): low ({}) must be smaller than high ({})",
+                low, high
+            );
+        }
+
+        self.feature_range = (low, high);
+        self
+    }
+}
+
+impl Default for MinMaxScaler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scaler for MinMaxScaler {
+    const KIND: &'static str = "MinMaxScaler";
+
+    fn fit<E: Num>(&mut self, data: &Collective<E>, axis: Axis) -> &mut Self {
+        let features = features(data, axis, Self::KIND);
+        let (low, high) = self.feature_range;
+        let (mut center, mut scale) = (Vec::new(), Vec::new());
+
+        for values in features {
+            let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let feature_scale = non_zero((max - min) / (high - low));
+
+            center.push(min - low * feature_scale);
+            scale.push(feature_scale);
+        }
+
+        self.parameters = Some(ScalerParameters { axis, center, scale });
+        self
+    }
+
+    fn parameters(&self) -> Option<&ScalerParameters> {
+        self.parameters.as_ref()
+    }
+
+    fn from_parameters(parameters: ScalerParameters) -> Self {
+        Self {
+            parameters: Some(parameters),
+            ..Self::new()
+        }
+    }
+}
+
+/*
+   Robust scaling: center = median, scale = the range between two quantiles (the interquartile range 25..75 by default).
+   Quantiles are interpolated linearly between the two closest values, the same way NumPy's default `percentile` does it.
+   A handful of outliers barely moves either statistic, unlike the mean and standard deviation.
+*/
+#[derive(Debug, Clone)]
+pub struct RobustScaler {
+    with_centering: bool,
+    with_scaling: bool,
+    quantile_range: (f64, f64),
+    parameters: Option<ScalerParameters>,
+}
+
+impl RobustScaler {
+    pub fn new() -> Self {
+        Self {
+            with_centering: true,
+            with_scaling: true,
+            quantile_range: (25.0, 75.0),
+            parameters: None,
+        }
+    }
+
+    pub fn with_centering(mut self, with_centering: bool) -> Self {
+        self.with_centering = with_centering;
+        self
+    }
+
+    pub fn with_scaling(mut self, with_scaling: bool) -> Self {
+        self.with_scaling = with_scaling;
+        self
+    }
+
+    // Percentages, 0.0 <= low < high <= 100.0
+    pub fn with_quantile_range(mut self, low: f64, high: f64) -> Self {
+        if !(0.0..=100.0).contains(&low) || !(0.0..=100.0).contains(&high) || low >= high {
+            panic!(
+                "RobustScaler::with_quantile_range(): Invalid range ({}, {}), expected 0 <= low < high <= 100",
+                low, high
+            );
+        }
+
+        self.quantile_range = (low, high);
+        self
+    }
+}
+
+impl Default for RobustScaler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scaler for RobustScaler {
+    const KIND: &'static str = "RobustScaler";
+
+    fn fit<E: Num>(&mut self, data: &Collective<E>, axis: Axis) -> &mut Self {
+        let features = features(data, axis, Self::KIND);
+        let (low, high) = self.quantile_range;
+        let (mut center, mut scale) = (Vec::new(), Vec::new());
+
+        for mut values in features {
+            values.sort_by(|a, b| a.total_cmp(b));
+
+            center.push(if self.with_centering { quantile(&values, 50.0) } else { 0.0 });
+            scale.push(if self.with_scaling {
+                non_zero(quantile(&values, high) - quantile(&values, low))
+            } else {
+                1.0
+            });
+        }
+
+        self.parameters = Some(ScalerParameters { axis, center, scale });
+        self
+    }
+
+    fn parameters(&self) -> Option<&ScalerParameters> {
+        self.parameters.as_ref()
+    }
+
+    fn from_parameters(parameters: ScalerParameters) -> Self {
+        Self {
+            parameters: Some(parameters),
+            ..Self::new()
+        }
+    }
+}
+
+fn fitted<S: Scaler>(scaler: &S) -> &ScalerParameters {
+    match scaler.parameters() {
+        Some(parameters) => parameters,
+        None => panic!("{}::transform(): The scaler has not been fitted", S::KIND),
+    }
+}
+
+/*
+   Returns the (rows, columns) view of a Collective, see the module comment.
+   The view indexes the data, so a shape that does not match its length is rejected here rather than as an index out of bounds.
+*/
+fn matrix<E>(data: &Collective<E>, caller: &str) -> (usize, usize) {
+    let shape = match &data.shape {
+        Some(shape) => shape,
+        None => panic!("{}: Collective shape is not set", caller),
+    };
+
+    let (rows, columns) = (shape.rows() as usize, shape.columns() as usize);
+
+    match &data.data {
+        Some(values) if !values.is_empty() && rows * columns != values.len() => panic!(
+            "{}: Shape {:?} does not match the {} elements of the data",
+            caller,
+            shape.to_vec(),
+            values.len()
+        ),
+        _ => (rows, columns),
+    }
+}
+
+/*
+   Maps the element at (row, column) to the index of the feature it belongs to.
+*/
+fn feature_index(axis: Axis, row: usize, column: usize) -> usize {
+    match axis {
+        Axis::Rows => column,
+        Axis::Columns => row,
+        Axis::None => 0,
+    }
+}
+
+/*
+   Splits the data of a Collective into one Vec<f64> per feature.
+*/
+fn features<E: Num>(data: &Collective<E>, axis: Axis, caller: &str) -> Vec<Vec<f64>> {
+    let (rows, columns) = matrix(data, caller);
+    let values = match &data.data {
+        Some(values) if !values.is_empty() => values,
+        _ => panic!("{}::fit(): Collective data is not allocated", caller),
+    };
+
+    let number_of_features = match axis {
+        Axis::Rows => columns,
+        Axis::Columns => rows,
+        Axis::None => 1,
+    };
+
+    let mut features = vec![Vec::new(); number_of_features];
+
+    for row in 0..rows {
+        for column in 0..columns {
+            features[feature_index(axis, row, column)].push(values[row * columns + column].to_f64());
+        }
+    }
+
+    features
+}
+
+/*
+   Applies `function(value, center, scale)` to every element, picking the parameters of the feature the element belongs to.
+*/
+fn apply<E, F>(parameters: &ScalerParameters, data: &Collective<E>, function: F) -> Collective<E>
+where
+    E: Float,
+    F: Fn(f64, f64, f64) -> f64,
+{
+    let (rows, columns) = matrix(data, "Scaler::transform()");
+    let expected = match parameters.axis {
+        Axis::Rows => columns,
+        Axis::Columns => rows,
+        Axis::None => 1,
+    };
+
+    if expected != parameters.center.len() {
+        panic!(
+            "Scaler::transform(): The scaler was fitted on {} features along {:?}, the data has {}",
+            parameters.center.len(),
+            parameters.axis,
+            expected
+        );
+    }
+
+    let transformed = data.data.as_ref().map(|values| {
+        let mut buffer = values.to_vec();
+
+        for row in 0..rows {
+            for column in 0..columns {
+                let feature = feature_index(parameters.axis, row, column);
+                let index = row * columns + column;
+
+                buffer[index] = E::from_f64(function(
+                    buffer[index].to_f64(),
+                    parameters.center[feature],
+                    parameters.scale[feature],
+                ));
+            }
+        }
+
+        buffer.into_boxed_slice()
+    });
+
+    Collective::new(transformed, data.shape.clone())
+}
+
+/*
+   Linear interpolation between the two closest ranks, `sorted` must be sorted and not empty.
+*/
+fn quantile(sorted: &[f64], percentage: f64) -> f64 {
+    let position = percentage / 100.0 * (sorted.len() - 1) as f64;
+    let below = position.floor() as usize;
+    let above = position.ceil() as usize;
+
+    sorted[below] + (sorted[above] - sorted[below]) * (position - below as f64)
+}
+
+// A feature that does not vary would divide by zero, it is left unscaled instead
+fn non_zero(scale: f64) -> f64 {
+    if scale == 0.0 {
+        1.0
+    } else {
+        scale
+    }
+}
+
+fn join(values: &[f64]) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn parse_axis(value: &str) -> io::Result<Axis> {
+    match value {
+        "Rows" => Ok(Axis::Rows),
+        "Columns" => Ok(Axis::Columns),
+        "None" => Ok(Axis::None),
+        _ => Err(invalid_data(format!("unknown axis \"{}\"", value))),
+    }
+}
+
+fn parse_values(value: &str) -> io::Result<Vec<f64>> {
+    value
+        .split_whitespace()
+        .map(|token| {
+            token
+                .parse::<f64>()
+                .map_err(|_| invalid_data(format!("\"{}\" is not a number", token)))
+        })
+        .collect()
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
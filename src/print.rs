@@ -0,0 +1,471 @@
+/*
+ * Numrs/src/print.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Printing Collectives
+   --------------------
+   `Display` prints a Collective the way NumPy's `str()` prints an array: one pair of brackets per level of the `Dimensions` chain,
+   every element padded to the same width, rows wrapped at the line width, blank lines between the blocks of 3-D and higher data.
+
+       println!("{}", collective);
+
+       [[ 0.   1.5 -2. ]
+        [ 3.   4.   5. ]]
+
+   Floats are printed with at most `precision` fractional digits (fewer if fewer suffice), or in scientific notation when the
+   magnitudes are too far apart for fixed notation (the largest is 1e8 or more, the smallest below 1e-4, or their ratio above 1000).
+   `suppress` keeps small values in fixed notation, where they round to 0. A Collective with more than `threshold` elements is
+   summarized, every axis longer than 2 * `edgeitems` only shows its first and last `edgeitems` entries with "..." in between.
+   Booleans, e.g. the masks of `isclose`, print as NumPy's True and False.
+
+   The options are global, like NumPy's `np.set_printoptions`:
+
+       numrs::print::set_printoptions(PrintOptions::new().with_precision(3).with_suppress(true));
+
+   `with_printoptions` changes them for the current thread only, for the duration of a closure, like `np.printoptions`:
+
+       let text = with_printoptions(PrintOptions::new().with_linewidth(120), || format!("{}", collective));
+
+   The precision of a format string overrides the options for a single value: `format!("{:.2}", collective)`.
+*/
+
+use super::{
+    collective::{strides, Collective},
+    num::Num,
+};
+use std::{cell::RefCell, fmt, sync::RwLock};
+
+/*
+   The elements a Collective can be printed with, every Num type and bool.
+*/
+pub trait PrintElement: Copy {
+    // Printed as text, padded to a common width, rather than as a float
+    const IS_TEXT: bool;
+
+    fn as_f64(self) -> f64;
+    fn as_text(self) -> String;
+}
+
+impl<E: Num> PrintElement for E {
+    const IS_TEXT: bool = E::IS_INTEGER;
+
+    fn as_f64(self) -> f64 {
+        self.to_f64()
+    }
+
+    fn as_text(self) -> String {
+        self.to_string()
+    }
+}
+
+impl PrintElement for bool {
+    const IS_TEXT: bool = true;
+
+    fn as_f64(self) -> f64 {
+        if self { 1.0 } else { 0.0 }
+    }
+
+    fn as_text(self) -> String {
+        if self { "True" } else { "False" }.to_string()
+    }
+}
+
+/*
+   The options that control how Collectives are printed. The defaults are NumPy's.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrintOptions {
+    // Maximum number of fractional digits of floats
+    pub precision: usize,
+    // Number of characters per line, before rows wrap
+    pub linewidth: usize,
+    // Collectives with more elements than this are summarized
+    pub threshold: usize,
+    // Number of entries shown at the start and the end of each summarized axis
+    pub edgeitems: usize,
+    // Print small floats in fixed notation (rounding them to 0) instead of switching to scientific notation
+    pub suppress: bool,
+}
+
+impl PrintOptions {
+    pub const fn new() -> Self {
+        Self {
+            precision: 8,
+            linewidth: 75,
+            threshold: 1000,
+            edgeitems: 3,
+            suppress: false,
+        }
+    }
+
+    // Fluent setters
+    pub fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    pub fn with_linewidth(mut self, linewidth: usize) -> Self {
+        self.linewidth = linewidth;
+        self
+    }
+
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn with_edgeitems(mut self, edgeitems: usize) -> Self {
+        self.edgeitems = edgeitems;
+        self
+    }
+
+    pub fn with_suppress(mut self, suppress: bool) -> Self {
+        self.suppress = suppress;
+        self
+    }
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static PRINT_OPTIONS: RwLock<PrintOptions> = RwLock::new(PrintOptions::new());
+
+thread_local! {
+    // Set by `with_printoptions`, takes precedence over the global options on this thread
+    static SCOPED_OPTIONS: RefCell<Option<PrintOptions>> = const { RefCell::new(None) };
+}
+
+/// Sets the print options for every thread.
+pub fn set_printoptions(options: PrintOptions) {
+    *PRINT_OPTIONS.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = options;
+}
+
+/// Restores NumPy's default print options for every thread.
+pub fn reset_printoptions() {
+    set_printoptions(PrintOptions::new());
+}
+
+/// The print options in effect on this thread.
+pub fn get_printoptions() -> PrintOptions {
+    SCOPED_OPTIONS
+        .with(|scoped| *scoped.borrow())
+        .unwrap_or_else(|| *PRINT_OPTIONS.read().unwrap_or_else(|poisoned| poisoned.into_inner()))
+}
+
+/// Runs `f` with `options` in effect on this thread, the previous options are restored afterwards (even if `f` panics).
+pub fn with_printoptions<T, F: FnOnce() -> T>(options: PrintOptions, f: F) -> T {
+    struct Restore(Option<PrintOptions>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED_OPTIONS.with(|scoped| *scoped.borrow_mut() = self.0);
+        }
+    }
+
+    let _restore = Restore(SCOPED_OPTIONS.with(|scoped| scoped.replace(Some(options))));
+
+    f()
+}
+
+impl<E> Collective<E>
+where
+    E: PrintElement,
+{
+    /// Formats the Collective like `Display`, with the given options instead of the global ones.
+    pub fn to_string_with(&self, options: &PrintOptions) -> String {
+        format_collective(self, options, " ", 0)
+    }
+}
+
+impl<E> fmt::Display for Collective<E>
+where
+    E: PrintElement,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut options = get_printoptions();
+
+        if let Some(precision) = f.precision() {
+            options.precision = precision;
+        }
+
+        f.write_str(&format_collective(self, &options, " ", 0))
+    }
+}
+
+/*
+   Formats a Collective as nested brackets. `separator` goes between elements (" " for Display, ", " for Debug),
+   `indent` is the column the opening bracket is printed at, continuation lines are indented to line up with it.
+*/
+pub(crate) fn format_collective<E: PrintElement>(
+    collective: &Collective<E>,
+    options: &PrintOptions,
+    separator: &str,
+    indent: usize,
+) -> String {
+    let data = match &collective.data {
+        Some(data) if !data.is_empty() => data,
+        _ => return "[]".to_string(),
+    };
+
+    // Data without a shape prints as a single axis
+    let extents: Vec<usize> = match &collective.shape {
+        Some(shape) if shape.get_n() == data.len() => collective.extents(),
+        _ => vec![data.len()],
+    };
+
+    let summarize = data.len() > options.threshold;
+
+    // Indices shown along each axis, None stands for "..."
+    let shown: Vec<Vec<Option<usize>>> = extents
+        .iter()
+        .map(|&extent| {
+            if summarize && extent > 2 * options.edgeitems {
+                (0..options.edgeitems)
+                    .map(Some)
+                    .chain(std::iter::once(None))
+                    .chain((extent - options.edgeitems..extent).map(Some))
+                    .collect()
+            } else {
+                (0..extent).map(Some).collect()
+            }
+        })
+        .collect();
+
+    let strides = strides(&extents);
+
+    let mut visible = Vec::new();
+    collect_visible(&shown, &strides, 0, 0, &mut visible);
+
+    let formatter = ElementFormatter::new(visible.iter().map(|&index| data[index]), options);
+
+    let layout = Layout {
+        shown: &shown,
+        strides: &strides,
+        separator,
+        linewidth: options.linewidth,
+    };
+
+    layout.format(data, &formatter, 0, 0, indent)
+}
+
+// Flat indices of every element that is printed
+fn collect_visible(shown: &[Vec<Option<usize>>], strides: &[usize], axis: usize, offset: usize, visible: &mut Vec<usize>) {
+    for index in shown[axis].iter().flatten() {
+        let offset = offset + index * strides[axis];
+
+        if axis + 1 == shown.len() {
+            visible.push(offset);
+        } else {
+            collect_visible(shown, strides, axis + 1, offset, visible);
+        }
+    }
+}
+
+struct Layout<'a> {
+    shown: &'a [Vec<Option<usize>>],
+    strides: &'a [usize],
+    separator: &'a str,
+    linewidth: usize,
+}
+
+impl Layout<'_> {
+    fn format<E: PrintElement>(&self, data: &[E], formatter: &ElementFormatter, axis: usize, offset: usize, indent: usize) -> String {
+        let line_end = self.separator.trim_end();
+        let mut out = String::from("[");
+
+        if axis + 1 == self.shown.len() {
+            // Innermost axis, the elements themselves, wrapped at the line width
+            let items: Vec<String> = self.shown[axis]
+                .iter()
+                .map(|index| match index {
+                    Some(index) => formatter.format(data[offset + index * self.strides[axis]]),
+                    None => "...".to_string(),
+                })
+                .collect();
+
+            let mut column = indent + 1;
+            for (position, item) in items.iter().enumerate() {
+                let closing = if position + 1 == items.len() { 1 } else { line_end.len() };
+
+                if position > 0 {
+                    if column + self.separator.len() + item.len() + closing > self.linewidth {
+                        out.push_str(line_end);
+                        out.push('\n');
+                        out.push_str(&" ".repeat(indent + 1));
+                        column = indent + 1;
+                    } else {
+                        out.push_str(self.separator);
+                        column += self.separator.len();
+                    }
+                }
+
+                out.push_str(item);
+                column += item.len();
+            }
+        } else {
+            // Blocks of 3-D and higher data are separated by blank lines, one more per level
+            let newlines = "\n".repeat(self.shown.len() - axis - 1);
+
+            for (position, index) in self.shown[axis].iter().enumerate() {
+                if position > 0 {
+                    out.push_str(line_end);
+                    out.push_str(&newlines);
+                    out.push_str(&" ".repeat(indent + 1));
+                }
+
+                match index {
+                    Some(index) => out.push_str(&self.format(
+                        data,
+                        formatter,
+                        axis + 1,
+                        offset + index * self.strides[axis],
+                        indent + 1,
+                    )),
+                    None => out.push_str("..."),
+                }
+            }
+        }
+
+        out.push(']');
+        out
+    }
+}
+
+/*
+   Decides, from the elements that are printed, how every element is formatted, so all of them get the same width.
+*/
+enum Notation {
+    // Integers and booleans
+    Text,
+    // Fractional digits are trimmed per element and padded with spaces, "1. " and "2.5" line up
+    Fixed { precision: usize },
+    // All mantissas get the same number of fractional digits, "1.0e-05" and "1.5e+00"
+    Scientific { digits: usize },
+}
+
+struct ElementFormatter {
+    notation: Notation,
+    // Widths of the part before and from the decimal point, for fixed notation
+    integer_width: usize,
+    fraction_width: usize,
+    // Width of a whole element
+    width: usize,
+}
+
+impl ElementFormatter {
+    fn new<E: PrintElement, I: Iterator<Item = E> + Clone>(elements: I, options: &PrintOptions) -> Self {
+        let notation = if E::IS_TEXT {
+            Notation::Text
+        } else {
+            let magnitudes = elements
+                .clone()
+                .map(|element| element.as_f64().abs())
+                .filter(|magnitude| magnitude.is_finite() && *magnitude != 0.0);
+
+            let (min, max) = magnitudes.fold((f64::INFINITY, 0.0f64), |(min, max), magnitude| {
+                (min.min(magnitude), max.max(magnitude))
+            });
+
+            let scientific = max >= 1e8 || (!options.suppress && max > 0.0 && (min < 1e-4 || max / min > 1e3));
+
+            if scientific {
+                // The fewest mantissa digits (up to the precision) that represent every element
+                let digits = elements
+                    .clone()
+                    .map(|element| element.as_f64())
+                    .filter(|value| value.is_finite())
+                    .map(|value| mantissa_digits(value, options.precision))
+                    .max()
+                    .unwrap_or(0);
+
+                Notation::Scientific { digits }
+            } else {
+                Notation::Fixed {
+                    precision: options.precision,
+                }
+            }
+        };
+
+        let mut formatter = Self {
+            notation,
+            integer_width: 0,
+            fraction_width: 0,
+            width: 0,
+        };
+
+        for element in elements {
+            let text = formatter.raw(element);
+
+            match (&formatter.notation, text.find('.')) {
+                (Notation::Fixed { .. }, Some(point)) => {
+                    formatter.integer_width = formatter.integer_width.max(point);
+                    formatter.fraction_width = formatter.fraction_width.max(text.len() - point);
+                }
+                _ => formatter.width = formatter.width.max(text.len()),
+            }
+        }
+
+        formatter.width = formatter.width.max(formatter.integer_width + formatter.fraction_width);
+
+        formatter
+    }
+
+    // An element without padding
+    fn raw<E: PrintElement>(&self, element: E) -> String {
+        let value = element.as_f64();
+
+        match self.notation {
+            Notation::Text => element.as_text(),
+            _ if value.is_nan() => "nan".to_string(),
+            _ if value.is_infinite() => if value < 0.0 { "-inf" } else { "inf" }.to_string(),
+            Notation::Fixed { precision } => {
+                let text = format!("{:.*}", precision, value);
+
+                if text.contains('.') {
+                    text.trim_end_matches('0').to_string()
+                } else {
+                    text + "."
+                }
+            }
+            Notation::Scientific { digits } => {
+                let text = format!("{:.*e}", digits, value);
+                let (mantissa, exponent) = text.split_once('e').unwrap();
+                let exponent: i32 = exponent.parse().unwrap();
+                let mantissa = if mantissa.contains('.') { mantissa.to_string() } else { format!("{}.", mantissa) };
+
+                format!("{}e{}{:02}", mantissa, if exponent < 0 { '-' } else { '+' }, exponent.abs())
+            }
+        }
+    }
+
+    // An element padded to the common width
+    fn format<E: PrintElement>(&self, element: E) -> String {
+        let text = self.raw(element);
+
+        match (&self.notation, text.find('.')) {
+            (Notation::Fixed { .. }, Some(point)) => format!(
+                "{}{}{}",
+                " ".repeat(self.integer_width - point),
+                text,
+                " ".repeat(self.fraction_width - (text.len() - point))
+            ),
+            _ => format!("{:>width$}", text, width = self.width),
+        }
+    }
+}
+
+// Number of fractional mantissa digits (at most `precision`) needed to represent `value` in scientific notation
+fn mantissa_digits(value: f64, precision: usize) -> usize {
+    let text = format!("{:.*e}", precision, value);
+    let mantissa = text.split_once('e').unwrap().0;
+
+    match mantissa.split_once('.') {
+        Some((_, fraction)) => fraction.trim_end_matches('0').len(),
+        None => 0,
+    }
+}
@@ -0,0 +1,539 @@
+/*
+ * Numrs/src/safetensors.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Safetensors files
+   -----------------
+   .safetensors is the format most pretrained weights are distributed in. Unlike a pickle it holds nothing but data:
+
+       header_size                    u64 little endian
+       header                         header_size bytes of JSON, padded with spaces
+                                      {"dense1.weight": {"dtype": "F32", "shape": [4, 3], "data_offsets": [0, 48]}, ...,
+                                       "__metadata__": {"format": "pt"}}
+       data                           the tensors, little endian and in C order, back to back
+
+   `data_offsets` are relative to the start of the data. Every tensor must be exactly as long as its dtype and shape say, and the
+   tensors must cover the data without gaps or overlaps. A file that breaks any of these rules is rejected when it is opened,
+   so a malicious or truncated file can never make `load`/`view` read out of bounds.
+
+   `Safetensors::open` memory maps the file (see mmap.rs): opening a multi-GB checkpoint only reads its header, and `view`
+   returns a tensor's elements in place, without copying them. `load` copies a tensor into an owned Collective.
+
+       let weights = Safetensors::open("model.safetensors")?;
+       let embedding: TensorView<f32> = weights.view("embed.weight")?;     // zero-copy
+       let bias: Collective<f32> = weights.load("dense1.bias")?;           // owned copy
+
+   Shapes map onto `Dimensions` the same way .npy shapes do: 1-D tensors of n elements become 1 x n, scalars 1 x 1.
+   Supported dtypes are U8, I8, U16, I16, U32, I32, U64, I64, F32, F64, and F16/BF16 with the `f16`/`bf16` features.
+   Tensors of other dtypes (BOOL, F8_E4M3, ...) are validated but cannot be loaded.
+*/
+
+use super::{collective::Collective, dimensions::Dimensions, npy::shape_to_dimensions, pod::Pod};
+use memmap2::Mmap;
+use serde_json::{Map, Value};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufWriter, Write},
+    mem,
+    ops::Deref,
+    path::Path,
+    slice,
+};
+
+// Headers larger than this are rejected, like the reference implementation does
+const MAX_HEADER_SIZE: usize = 100_000_000;
+
+const METADATA_KEY: &str = "__metadata__";
+
+/*
+   An element type that has a safetensors dtype.
+*/
+pub trait SafetensorsElement: Pod {
+    const DTYPE: &'static str;
+}
+
+macro_rules! impl_safetensors_element {
+    ($($t:ty => $dtype:expr),*) => {
+        $(
+            impl SafetensorsElement for $t {
+                const DTYPE: &'static str = $dtype;
+            }
+        )*
+    };
+}
+
+impl_safetensors_element!(
+    u8 => "U8", i8 => "I8", u16 => "U16", i16 => "I16", u32 => "U32", i32 => "I32",
+    u64 => "U64", i64 => "I64", f32 => "F32", f64 => "F64"
+);
+
+#[cfg(feature = "f16")]
+impl_safetensors_element!(half::f16 => "F16");
+#[cfg(feature = "bf16")]
+impl_safetensors_element!(half::bf16 => "BF16");
+
+// Size in bytes of every dtype of the format, including those Numrs cannot load
+fn dtype_size(dtype: &str) -> Option<usize> {
+    match dtype {
+        "BOOL" | "U8" | "I8" | "F8_E5M2" | "F8_E4M3" => Some(1),
+        "U16" | "I16" | "F16" | "BF16" => Some(2),
+        "U32" | "I32" | "F32" => Some(4),
+        "U64" | "I64" | "F64" => Some(8),
+        _ => None,
+    }
+}
+
+/*
+   The header entry of one tensor.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TensorInfo {
+    pub dtype: String,
+    pub shape: Vec<usize>,
+    // Start and end of the tensor's bytes, relative to the start of the data
+    pub data_offsets: (usize, usize),
+}
+
+/*
+   A tensor viewed in place, borrowed from a `Safetensors`.
+*/
+pub struct TensorView<'a, E> {
+    pub data: &'a [E],
+    pub shape: Dimensions,
+}
+
+impl<E: SafetensorsElement> TensorView<'_, E> {
+    pub fn to_collective(&self) -> Collective<E> {
+        Collective::new(
+            Some(self.data.to_vec().into_boxed_slice()),
+            Some(Box::new(self.shape.clone())),
+        )
+    }
+}
+
+enum Storage {
+    Mapped(Mmap),
+    Owned(Vec<u8>),
+}
+
+impl Deref for Storage {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Storage::Mapped(map) => map,
+            Storage::Owned(bytes) => bytes,
+        }
+    }
+}
+
+/*
+   An opened and validated .safetensors file.
+*/
+pub struct Safetensors {
+    storage: Storage,
+    // Offset of the data in `storage`, 8 + header_size
+    data_start: usize,
+    // In the order of their data
+    tensors: Vec<(String, TensorInfo)>,
+    metadata: BTreeMap<String, String>,
+}
+
+impl Safetensors {
+    /// Memory maps a .safetensors file and validates its header. The data is not read.
+    ///
+    /// As with any memory map, the file must not be truncated or modified by another process while it is open.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+
+        // SAFETY: The mapping is read-only, see the note above about other processes modifying the file
+        let map = unsafe { Mmap::map(&file)? };
+
+        Self::parse(Storage::Mapped(map))
+    }
+
+    /// Validates a .safetensors file already in memory.
+    pub fn from_bytes(bytes: Vec<u8>) -> io::Result<Self> {
+        Self::parse(Storage::Owned(bytes))
+    }
+
+    fn parse(storage: Storage) -> io::Result<Self> {
+        if storage.len() < 8 {
+            return Err(invalid_data("file is too short for a header size".to_string()));
+        }
+
+        let header_size = u64::from_le_bytes(storage[..8].try_into().unwrap());
+
+        if header_size > MAX_HEADER_SIZE as u64 || header_size > (storage.len() - 8) as u64 {
+            return Err(invalid_data(format!(
+                "header size {} is larger than the file or the {} byte limit",
+                header_size, MAX_HEADER_SIZE
+            )));
+        }
+
+        let data_start = 8 + header_size as usize;
+        let data_len = storage.len() - data_start;
+
+        let header: Map<String, Value> = match serde_json::from_slice(&storage[8..data_start]) {
+            Ok(Value::Object(header)) => header,
+            Ok(_) => return Err(invalid_data("header is not a JSON object".to_string())),
+            Err(error) => return Err(invalid_data(format!("header is not valid JSON: {}", error))),
+        };
+
+        let mut tensors = Vec::with_capacity(header.len());
+        let mut metadata = BTreeMap::new();
+
+        for (name, entry) in header {
+            if name == METADATA_KEY {
+                metadata = parse_metadata(&entry)?;
+            } else {
+                let info = parse_info(&name, &entry)?;
+                tensors.push((name, info));
+            }
+        }
+
+        tensors.sort_by_key(|(_, info)| info.data_offsets);
+
+        // The tensors must cover the data exactly, back to back
+        let mut expected_start = 0;
+        for (name, info) in &tensors {
+            let (start, end) = info.data_offsets;
+
+            if start != expected_start {
+                return Err(invalid_data(format!(
+                    "tensor '{}' starts at {}, expected {} (tensors must not overlap or leave gaps)",
+                    name, start, expected_start
+                )));
+            }
+
+            expected_start = end;
+        }
+
+        if expected_start != data_len {
+            return Err(invalid_data(format!(
+                "tensors cover {} bytes, the file has {} bytes of data",
+                expected_start, data_len
+            )));
+        }
+
+        Ok(Self {
+            storage,
+            data_start,
+            tensors,
+            metadata,
+        })
+    }
+
+    /// Names of the tensors, in the order of their data.
+    pub fn names(&self) -> Vec<&str> {
+        self.tensors.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tensors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tensors.is_empty()
+    }
+
+    pub fn info(&self, name: &str) -> Option<&TensorInfo> {
+        self.tensors
+            .iter()
+            .find(|(tensor, _)| tensor == name)
+            .map(|(_, info)| info)
+    }
+
+    /// The free form string to string map stored under "__metadata__".
+    pub fn metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    /// Copies a tensor into an owned Collective.
+    ///
+    /// # Errors
+    /// `NotFound` if there is no tensor named `name`, `InvalidData` if its dtype is not the dtype of `E`
+    /// or its shape has an axis of length zero.
+    pub fn load<E: SafetensorsElement>(&self, name: &str) -> io::Result<Collective<E>> {
+        let (bytes, shape) = self.tensor::<E>(name)?;
+
+        let elements: Vec<E> = bytes.chunks_exact(E::SIZE).map(E::from_le_slice).collect();
+
+        Ok(Collective::new(
+            Some(elements.into_boxed_slice()),
+            Some(Box::new(shape)),
+        ))
+    }
+
+    /// Views a tensor in place, without copying it.
+    ///
+    /// # Errors
+    /// Those of `load`, and `InvalidData` if the elements are not aligned in memory or the machine is big endian.
+    /// Files written by `save_safetensors` (or the reference implementation) and opened with `open` are always aligned.
+    pub fn view<E: SafetensorsElement>(&self, name: &str) -> io::Result<TensorView<'_, E>> {
+        let (bytes, shape) = self.tensor::<E>(name)?;
+
+        if cfg!(target_endian = "big") && E::SIZE > 1 {
+            return Err(invalid_data(
+                "safetensors data is little endian and cannot be viewed in place on this machine, use load".to_string(),
+            ));
+        }
+
+        if !(bytes.as_ptr() as usize).is_multiple_of(mem::align_of::<E>()) {
+            return Err(invalid_data(format!(
+                "tensor '{}' is not aligned in memory and cannot be viewed in place, use load",
+                name
+            )));
+        }
+
+        // SAFETY: `tensor` checked that `bytes` holds exactly get_n() elements, the check above that they are aligned.
+        // `Pod` is sealed and only implemented for plain old data, so any bytes are valid elements.
+        // The slice borrows `self`, which keeps the storage alive.
+        let data = unsafe { slice::from_raw_parts(bytes.as_ptr() as *const E, bytes.len() / E::SIZE) };
+
+        Ok(TensorView { data, shape })
+    }
+
+    /// Copies every tensor, all tensors must have the element type `E`.
+    pub fn load_all<E: SafetensorsElement>(&self) -> io::Result<BTreeMap<String, Collective<E>>> {
+        self.tensors
+            .iter()
+            .map(|(name, _)| Ok((name.clone(), self.load(name)?)))
+            .collect()
+    }
+
+    // The bytes and shape of a tensor, after checking its dtype
+    fn tensor<E: SafetensorsElement>(&self, name: &str) -> io::Result<(&[u8], Dimensions)> {
+        let info = self.info(name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Safetensors: no tensor named '{}'", name),
+            )
+        })?;
+
+        if info.dtype != E::DTYPE {
+            return Err(invalid_data(format!(
+                "tensor '{}' has dtype {}, the element type is {}",
+                name,
+                info.dtype,
+                E::DTYPE
+            )));
+        }
+
+        let shape = shape_to_dimensions(&info.shape)?;
+        let (start, end) = info.data_offsets;
+
+        Ok((&self.storage[self.data_start + start..self.data_start + end], shape))
+    }
+}
+
+/*
+   Collects Collectives, possibly of different element types, and writes them as one .safetensors file.
+*/
+pub struct SafetensorsWriter {
+    // name, dtype, shape, little endian bytes
+    tensors: Vec<(String, &'static str, Vec<usize>, Vec<u8>)>,
+    metadata: BTreeMap<String, String>,
+}
+
+impl SafetensorsWriter {
+    pub fn new() -> Self {
+        Self {
+            tensors: Vec::new(),
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    // Fluent setter, adds an entry to "__metadata__", e.g. ("format", "pt")
+    pub fn with_metadata(mut self, key: &str, value: &str) -> Self {
+        self.metadata.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Adds a Collective as the tensor `name`.
+    ///
+    /// # Errors
+    /// `InvalidInput` if the name is taken or is "__metadata__", the Collective has no data or shape allocated,
+    /// or its shape does not match the number of elements.
+    pub fn add<E: SafetensorsElement>(&mut self, name: &str, collective: &Collective<E>) -> io::Result<()> {
+        if name == METADATA_KEY || self.tensors.iter().any(|(tensor, ..)| tensor == name) {
+            return Err(invalid_input(format!("SafetensorsWriter::add(): name '{}' is already taken", name)));
+        }
+
+        let (data, shape) = match (&collective.data, &collective.shape) {
+            (Some(data), Some(shape)) => (data, shape),
+            _ => {
+                return Err(invalid_input(
+                    "SafetensorsWriter::add(): Collective data or shape is not allocated".to_string(),
+                ))
+            }
+        };
+
+        let shape: Vec<usize> = shape.to_vec().iter().map(|&extent| extent as usize).collect();
+
+        // data_offsets are taken from the bytes, a shape that disagrees would make a file Safetensors::parse rejects
+        if shape.iter().product::<usize>() != data.len() {
+            return Err(invalid_input(format!(
+                "SafetensorsWriter::add(): Shape {:?} does not match the {} elements of the data",
+                shape,
+                data.len()
+            )));
+        }
+
+        let mut bytes = Vec::with_capacity(data.len() * E::SIZE);
+        for &element in data.iter() {
+            element.extend_le_bytes(&mut bytes);
+        }
+
+        self.tensors.push((name.to_string(), E::DTYPE, shape, bytes));
+
+        Ok(())
+    }
+
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        // Largest elements first, so every tensor starts at a multiple of its element size
+        let mut order: Vec<&(String, &'static str, Vec<usize>, Vec<u8>)> = self.tensors.iter().collect();
+        order.sort_by(|a, b| {
+            let size = |dtype: &str| dtype_size(dtype).unwrap_or(1);
+            size(b.1).cmp(&size(a.1)).then_with(|| a.0.cmp(&b.0))
+        });
+
+        let mut header = Map::new();
+        let mut offset = 0;
+
+        for (name, dtype, shape, bytes) in &order {
+            let mut entry = Map::new();
+            entry.insert("dtype".to_string(), Value::from(*dtype));
+            entry.insert("shape".to_string(), Value::from(shape.clone()));
+            entry.insert("data_offsets".to_string(), Value::from(vec![offset, offset + bytes.len()]));
+
+            header.insert(name.clone(), Value::Object(entry));
+            offset += bytes.len();
+        }
+
+        if !self.metadata.is_empty() {
+            let metadata = self
+                .metadata
+                .iter()
+                .map(|(key, value)| (key.clone(), Value::from(value.clone())))
+                .collect();
+
+            header.insert(METADATA_KEY.to_string(), Value::Object(metadata));
+        }
+
+        let mut json = serde_json::to_vec(&Value::Object(header))?;
+
+        // Pad with spaces so the data starts at a multiple of 8 bytes
+        while !json.len().is_multiple_of(8) {
+            json.push(b' ');
+        }
+
+        writer.write_all(&(json.len() as u64).to_le_bytes())?;
+        writer.write_all(&json)?;
+
+        for (.., bytes) in order {
+            writer.write_all(bytes)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+}
+
+impl Default for SafetensorsWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Saves named Collectives of one element type as a .safetensors file.
+pub fn save_safetensors<E: SafetensorsElement, P: AsRef<Path>>(
+    path: P,
+    collectives: &BTreeMap<String, Collective<E>>,
+) -> io::Result<()> {
+    let mut writer = SafetensorsWriter::new();
+
+    for (name, collective) in collectives {
+        writer.add(name, collective)?;
+    }
+
+    writer.save(path)
+}
+
+/// Loads every tensor of a .safetensors file, all tensors must have the element type `E`.
+pub fn load_safetensors<E: SafetensorsElement, P: AsRef<Path>>(path: P) -> io::Result<BTreeMap<String, Collective<E>>> {
+    Safetensors::open(path)?.load_all()
+}
+
+fn parse_info(name: &str, entry: &Value) -> io::Result<TensorInfo> {
+    let invalid = |what: &str| invalid_data(format!("tensor '{}': {}", name, what));
+
+    let dtype = entry
+        .get("dtype")
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid("missing dtype"))?;
+
+    let size = dtype_size(dtype).ok_or_else(|| invalid(&format!("unknown dtype {}", dtype)))?;
+
+    let shape = entry
+        .get("shape")
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid("missing shape"))?
+        .iter()
+        .map(|extent| extent.as_u64().map(|extent| extent as usize))
+        .collect::<Option<Vec<usize>>>()
+        .ok_or_else(|| invalid("shape must be a list of non-negative integers"))?;
+
+    let offsets = entry
+        .get("data_offsets")
+        .and_then(Value::as_array)
+        .filter(|offsets| offsets.len() == 2)
+        .and_then(|offsets| Some((offsets[0].as_u64()? as usize, offsets[1].as_u64()? as usize)))
+        .ok_or_else(|| invalid("data_offsets must be a list of two non-negative integers"))?;
+
+    let length = shape
+        .iter()
+        .try_fold(size, |length, &extent| length.checked_mul(extent))
+        .ok_or_else(|| invalid("shape is too large"))?;
+
+    if offsets.1 < offsets.0 || offsets.1 - offsets.0 != length {
+        return Err(invalid(&format!(
+            "data_offsets {:?} do not hold the {} bytes of a {} tensor of shape {:?}",
+            offsets, length, dtype, shape
+        )));
+    }
+
+    Ok(TensorInfo {
+        dtype: dtype.to_string(),
+        shape,
+        data_offsets: offsets,
+    })
+}
+
+fn parse_metadata(entry: &Value) -> io::Result<BTreeMap<String, String>> {
+    entry
+        .as_object()
+        .ok_or_else(|| invalid_data("__metadata__ must be an object".to_string()))?
+        .iter()
+        .map(|(key, value)| match value.as_str() {
+            Some(value) => Ok((key.clone(), value.to_string())),
+            None => Err(invalid_data(format!("__metadata__ value of '{}' must be a string", key))),
+        })
+        .collect()
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
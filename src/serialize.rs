@@ -0,0 +1,115 @@
+/*
+ * Numrs/src/serialize.rs
+ * Q@khaa.pk
+ */
+
+/*
+   serde support, enabled by the `serde` feature
+   ---------------------------------------------
+   `Dimensions` is a linked list of nodes, serializing it node by node would tie every config and model file to that layout.
+   Instead it is serialized as its extents, the same `Vec<f64>` that `to_vec()` returns and `from_vec()` takes:
+
+       [60000.0, 28.0, 28.0]
+
+   A `Collective` is serialized as its shape, the name of its element type (see `Dtype` in num.rs) and its data as one flat sequence:
+
+       {"shape": [2.0, 3.0], "dtype": "f32", "data": [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]}
+
+   Either of shape and data is null when it is not allocated. Deserialization rejects data of a different element type than the
+   Collective being deserialized, extents `Dimensions::from_vec` would panic on, and data whose length differs from `get_n()`.
+*/
+
+use super::{collective::Collective, dimensions::Dimensions, num::Dtype};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+impl Serialize for Dimensions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.to_vec())
+    }
+}
+
+impl<'de> Deserialize<'de> for Dimensions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let extents = Vec::<f64>::deserialize(deserializer)?;
+
+        if extents.len() < 2 {
+            return Err(D::Error::custom(format!(
+                "Dimensions need at least 2 extents, found {}",
+                extents.len()
+            )));
+        }
+
+        if let Some(extent) = extents
+            .iter()
+            .find(|&&extent| !(extent.is_finite() && extent >= 1.0 && extent.fract() == 0.0))
+        {
+            return Err(D::Error::custom(format!(
+                "Dimensions extents must be positive whole numbers, found {}",
+                extent
+            )));
+        }
+
+        Ok(Dimensions::from_vec(extents))
+    }
+}
+
+#[derive(Serialize)]
+struct CollectiveRef<'a, E> {
+    shape: Option<&'a Dimensions>,
+    dtype: &'static str,
+    data: Option<&'a [E]>,
+}
+
+#[derive(Deserialize)]
+struct CollectiveOwned<E> {
+    shape: Option<Dimensions>,
+    dtype: String,
+    data: Option<Vec<E>>,
+}
+
+impl<E> Serialize for Collective<E>
+where
+    E: Serialize + Dtype,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        CollectiveRef {
+            shape: self.shape.as_deref(),
+            dtype: E::NAME,
+            data: self.data.as_deref(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, E> Deserialize<'de> for Collective<E>
+where
+    E: Deserialize<'de> + Dtype,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let owned = CollectiveOwned::<E>::deserialize(deserializer)?;
+
+        if owned.dtype != E::NAME {
+            return Err(D::Error::custom(format!(
+                "Collective has dtype '{}', expected '{}'",
+                owned.dtype,
+                E::NAME
+            )));
+        }
+
+        if let (Some(shape), Some(data)) = (&owned.shape, &owned.data) {
+            if data.len() != shape.get_n() {
+                return Err(D::Error::custom(format!(
+                    "Collective has {} elements, its shape {:?} needs {}",
+                    data.len(),
+                    shape.to_vec(),
+                    shape.get_n()
+                )));
+            }
+        }
+
+        Ok(Collective {
+            data: owned.data.map(|data| data.into_boxed_slice()),
+            shape: owned.shape.map(Box::new),
+        })
+    }
+}
@@ -0,0 +1,216 @@
+/*
+ * Numrs/src/softmax.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Softmax, log-softmax and logsumexp
+   ----------------------------------
+   Along an axis (0 is the outermost, the order of `Dimensions::to_vec()`), for the logits x_1 ... x_n of every lane:
+
+       logsumexp(x)    = ln(sum(e^x_j))
+       log_softmax(x)i = x_i - logsumexp(x)
+       softmax(x)i     = e^(x_i - logsumexp(x))
+
+   Computed naively e^x overflows for logits above ~88 (f32) or ~709 (f64), so the maximum m of the lane is subtracted
+   first, logsumexp(x) = m + ln(sum(e^(x_j - m))), every exponent is then <= 0 and the largest term is exactly 1.
+   The sums are accumulated in f64 whatever the element type.
+
+       let probabilities = logits.softmax(1, None);           // [batch, classes], one distribution per sample
+       let log_probabilities = logits.log_softmax(1, None);
+
+   An optional mask of `bool`s excludes positions, e.g. the padding of sequences of different lengths. `true` keeps a position.
+   The mask is broadcast to the shape of the logits (see `zip_with`), so a [batch, 1, keys] mask can mask the keys of every
+   query. Masked positions get probability 0 (log-probability -infinity) and do not count towards logsumexp.
+   A lane whose positions are all masked has probabilities 0, log-probabilities -infinity and logsumexp -infinity.
+
+   softmax and log_softmax keep the shape of the logits, logsumexp removes the axis.
+*/
+
+use super::{
+    apply::broadcast_extents,
+    collective::{dimensions_from_extents, strides, Collective},
+    num::Float,
+};
+
+impl<E> Collective<E>
+where
+    E: Float,
+{
+    /// The softmax of every lane along `axis`, excluding the positions where `mask` is `false`.
+    ///
+    /// # Panics
+    /// If `axis` is not an axis of the Collective, or `mask` cannot be broadcast to its shape.
+    pub fn softmax(&self, axis: usize, mask: Option<&Collective<bool>>) -> Collective<E> {
+        self.normalize_lanes("softmax", axis, mask, |x, logsumexp| (x - logsumexp).exp(), 0.0)
+    }
+
+    /// The log-softmax of every lane along `axis`, excluding the positions where `mask` is `false`.
+    ///
+    /// # Panics
+    /// If `axis` is not an axis of the Collective, or `mask` cannot be broadcast to its shape.
+    pub fn log_softmax(&self, axis: usize, mask: Option<&Collective<bool>>) -> Collective<E> {
+        self.normalize_lanes("log_softmax", axis, mask, |x, logsumexp| x - logsumexp, f64::NEG_INFINITY)
+    }
+
+    /// ln(sum(e^x)) of every lane along `axis`, excluding the positions where `mask` is `false`.
+    ///
+    /// # Returns
+    /// A new Collective with `axis` removed. If the Collective has no data allocated, neither has the result.
+    ///
+    /// # Panics
+    /// If `axis` is not an axis of the Collective, or `mask` cannot be broadcast to its shape.
+    pub fn logsumexp(&self, axis: usize, mask: Option<&Collective<bool>>) -> Collective<E> {
+        let (data, mask) = match self.lane_inputs("logsumexp", axis, mask) {
+            Some(inputs) => inputs,
+            None => return Collective { data: None, shape: None },
+        };
+
+        let extents = self.extents();
+        let (outer, inner, length) = lane_layout(&extents, axis);
+
+        let mut result = Vec::with_capacity(outer * inner);
+        for start in lane_starts(outer, inner, length) {
+            let lane = Lane { start, inner, length };
+
+            result.push(E::from_f64(lane.logsumexp(&data, mask.as_deref())));
+        }
+
+        let remaining: Vec<usize> = extents
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| index != axis)
+            .map(|(_, &extent)| extent)
+            .collect();
+
+        Collective {
+            data: Some(result.into_boxed_slice()),
+            shape: Some(Box::new(dimensions_from_extents(&remaining))),
+        }
+    }
+
+    /*
+       softmax and log_softmax: every kept element becomes `f(x, logsumexp of its lane)`, masked elements become `masked`.
+    */
+    fn normalize_lanes(
+        &self,
+        method: &str,
+        axis: usize,
+        mask: Option<&Collective<bool>>,
+        f: impl Fn(f64, f64) -> f64,
+        masked: f64,
+    ) -> Collective<E> {
+        let (data, mask) = match self.lane_inputs(method, axis, mask) {
+            Some(inputs) => inputs,
+            None => return Collective { data: None, shape: None },
+        };
+
+        let (outer, inner, length) = lane_layout(&self.extents(), axis);
+
+        let mut result = vec![E::zero(); data.len()];
+        for start in lane_starts(outer, inner, length) {
+            let lane = Lane { start, inner, length };
+            let logsumexp = lane.logsumexp(&data, mask.as_deref());
+
+            for index in lane.indices() {
+                let kept = mask.as_ref().is_none_or(|mask| mask[index]);
+
+                result[index] = E::from_f64(if kept && logsumexp != f64::NEG_INFINITY {
+                    f(data[index], logsumexp)
+                } else {
+                    masked
+                });
+            }
+        }
+
+        Collective {
+            data: Some(result.into_boxed_slice()),
+            shape: self.shape.clone(),
+        }
+    }
+
+    /*
+       The elements as f64 and the mask broadcast to the shape of the Collective, None if the Collective is unallocated.
+    */
+    fn lane_inputs(&self, method: &str, axis: usize, mask: Option<&Collective<bool>>) -> Option<(Vec<f64>, Option<Vec<bool>>)> {
+        let extents = self.extents();
+
+        if axis >= extents.len() {
+            panic!(
+                "Collective::{}(): Axis {} is out of range for a Collective of {} axes",
+                method,
+                axis,
+                extents.len()
+            );
+        }
+
+        let data: Vec<f64> = match &self.data {
+            Some(data) if !data.is_empty() => data.iter().map(|value| value.to_f64()).collect(),
+            _ => return None,
+        };
+
+        let mask = match mask {
+            Some(mask) => {
+                if mask.data.is_none() || broadcast_extents(&extents, &mask.extents()).as_ref() != Some(&extents) {
+                    panic!(
+                        "Collective::{}(): Mask of shape {:?} cannot be broadcast to {:?}",
+                        method,
+                        mask.extents(),
+                        extents
+                    );
+                }
+
+                self.zip_with(mask, |_, keep| keep).data.map(|data| data.into_vec())
+            }
+            None => None,
+        };
+
+        Some((data, mask))
+    }
+}
+
+/*
+   The elements start, start + inner, ..., start + (length - 1) * inner of the data.
+*/
+struct Lane {
+    start: usize,
+    inner: usize,
+    length: usize,
+}
+
+impl Lane {
+    fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.length).map(move |k| self.start + k * self.inner)
+    }
+
+    // m + ln(sum(e^(x - m))) over the kept elements, -infinity if there are none
+    fn logsumexp(&self, data: &[f64], mask: Option<&[bool]>) -> f64 {
+        let kept = || self.indices().filter(move |&index| mask.is_none_or(|mask| mask[index]));
+
+        let max = kept().map(|index| data[index]).fold(f64::NEG_INFINITY, |max, x| if x > max || x.is_nan() { x } else { max });
+
+        // No kept elements, or all of them -infinity
+        if max == f64::NEG_INFINITY {
+            return f64::NEG_INFINITY;
+        }
+
+        // +infinity (or NaN) dominates the sum
+        if !max.is_finite() {
+            return max;
+        }
+
+        let sum: f64 = kept().map(|index| (data[index] - max).exp()).sum();
+
+        max + sum.ln()
+    }
+}
+
+// The number of blocks before the axis, the stride of the axis and its extent
+fn lane_layout(extents: &[usize], axis: usize) -> (usize, usize, usize) {
+    (extents[..axis].iter().product(), strides(extents)[axis], extents[axis])
+}
+
+// Index of the first element of every lane
+fn lane_starts(outer: usize, inner: usize, length: usize) -> impl Iterator<Item = usize> {
+    (0..outer).flat_map(move |block| (0..inner).map(move |offset| block * length * inner + offset))
+}
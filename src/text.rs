@@ -0,0 +1,563 @@
+/*
+ * Numrs/src/text.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Delimited text (CSV, TSV, whitespace separated columns)
+   -------------------------------------------------------
+   Small datasets and experiment logs are plain text more often than not. `Tensor::loadtxt`, `Tensor::genfromtxt` and
+   `Tensor::savetxt` follow their NumPy namesakes:
+
+   - `loadtxt` is strict, every field must be present and parse.
+   - `genfromtxt` replaces missing fields (empty, or one of the configured missing value strings such as "NA") with a filling value.
+   - `savetxt` writes one line per row, floats formatted by a printf style format string such as "%.6f" or "%.4e".
+
+   Both readers produce a 2D Collective, one row per line and one column per (selected) field. Every field is parsed according to the
+   dtype of its column (`TextDtype`, float unless configured otherwise) and then converted to the element type of the Collective.
+   Parsed values go through f64, so integers beyond 2^53 lose precision.
+
+   All options live in `TextOptions`, built with the usual fluent setters:
+
+       let options = TextOptions::new().with_delimiter(',').with_skip_header(1).with_columns(vec![0, 2]);
+       let data: Collective<f32> = Tensor::genfromtxt("iris.csv", &options)?;
+*/
+
+use super::{
+    collective::Collective,
+    dimensions::Dimensions,
+    num::{Num, Tensor},
+};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+/*
+   How the fields of a column are parsed.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextDtype {
+    // Any float literal, including "nan" and "inf"
+    Float,
+    // Whole numbers only, "1.5" is an error
+    Integer,
+    // "true"/"false", "yes"/"no" or "1"/"0", case-insensitive, parsed as 1 and 0
+    Boolean,
+}
+
+/*
+   Options for `Tensor::loadtxt`, `Tensor::genfromtxt` and `Tensor::savetxt`.
+   The defaults read whitespace separated columns, ignore everything after '#', and write the shortest representation of every value
+   separated by a space.
+*/
+#[derive(Debug, Clone)]
+pub struct TextOptions {
+    delimiter: Option<char>,
+    comments: Option<String>,
+    skip_header: usize,
+    skip_footer: usize,
+    columns: Option<Vec<usize>>,
+    dtypes: BTreeMap<usize, TextDtype>,
+    missing_values: Vec<String>,
+    filling_value: f64,
+    filling_values: BTreeMap<usize, f64>,
+    format: Option<FloatFormat>,
+    header: Option<String>,
+    footer: Option<String>,
+}
+
+impl TextOptions {
+    pub fn new() -> Self {
+        Self {
+            delimiter: None,
+            comments: Some("#".to_string()),
+            skip_header: 0,
+            skip_footer: 0,
+            columns: None,
+            dtypes: BTreeMap::new(),
+            missing_values: Vec::new(),
+            filling_value: f64::NAN,
+            filling_values: BTreeMap::new(),
+            format: None,
+            header: None,
+            footer: None,
+        }
+    }
+
+    // Fluent setters
+
+    // Field separator, e.g. ',' or '\t'. Without one, fields are separated by any run of whitespace (and written separated by a space)
+    pub fn with_delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = Some(delimiter);
+        self
+    }
+
+    // Everything from this string to the end of a line is ignored, None to disable comments
+    pub fn with_comments(mut self, comments: Option<&str>) -> Self {
+        self.comments = comments.map(|comments| comments.to_string());
+        self
+    }
+
+    // Number of lines to skip at the start of the file, e.g. 1 for a row of column names
+    pub fn with_skip_header(mut self, lines: usize) -> Self {
+        self.skip_header = lines;
+        self
+    }
+
+    // Number of data lines (not counting blank and comment lines) to skip at the end of the file
+    pub fn with_skip_footer(mut self, lines: usize) -> Self {
+        self.skip_footer = lines;
+        self
+    }
+
+    // Indices of the columns to read, in the order they should appear in the Collective
+    pub fn with_columns(mut self, columns: Vec<usize>) -> Self {
+        self.columns = Some(columns);
+        self
+    }
+
+    // How the fields of a column (index into the file's columns, before selection) are parsed
+    pub fn with_column_dtype(mut self, column: usize, dtype: TextDtype) -> Self {
+        self.dtypes.insert(column, dtype);
+        self
+    }
+
+    // Strings that mark a missing field, in addition to an empty field
+    pub fn with_missing_values(mut self, missing_values: Vec<&str>) -> Self {
+        self.missing_values = missing_values.into_iter().map(|value| value.to_string()).collect();
+        self
+    }
+
+    // Value `genfromtxt` puts in place of missing fields. The default is NaN, which becomes 0 for integer element types
+    pub fn with_filling_value(mut self, value: f64) -> Self {
+        self.filling_value = value;
+        self
+    }
+
+    // Filling value for a single column (index into the file's columns), overrides `with_filling_value`
+    pub fn with_column_filling_value(mut self, column: usize, value: f64) -> Self {
+        self.filling_values.insert(column, value);
+        self
+    }
+
+    /*
+       printf style format `savetxt` writes every value with, e.g. "%.6f", "%10.4e", "%+.3g" or "%d".
+       Supported are the flags '-', '+' and '0', a width, a precision and the conversions f, e, E, g, G, d and i.
+       Text around the conversion is written as is.
+    */
+    pub fn with_format(mut self, format: &str) -> Self {
+        match FloatFormat::parse(format) {
+            Ok(format) => self.format = Some(format),
+            Err(message) => panic!("TextOptions::with_format(): {}", message),
+        }
+
+        self
+    }
+
+    // Text `savetxt` writes before the data, every line prefixed with the comments string
+    pub fn with_header(mut self, header: &str) -> Self {
+        self.header = Some(header.to_string());
+        self
+    }
+
+    // Text `savetxt` writes after the data, every line prefixed with the comments string
+    pub fn with_footer(mut self, footer: &str) -> Self {
+        self.footer = Some(footer.to_string());
+        self
+    }
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tensor {
+    /*
+      Loads a 2D Collective from a delimited text file, one row per line.
+      Every selected field must be present and must parse according to its column's dtype.
+
+      @param
+      - `path`: The text file to read.
+      - `options`: Delimiter, skipped lines, selected columns, dtypes (see `TextOptions`).
+
+      @returns
+      - `io::Result<Collective<E>>`: A rows x columns Collective. `InvalidData` for a missing or malformed field,
+        rows with differing numbers of fields, or a file without data.
+    */
+    pub fn loadtxt<E: Num, P: AsRef<Path>>(path: P, options: &TextOptions) -> io::Result<Collective<E>> {
+        Self::loadtxt_from(BufReader::new(File::open(path)?), options)
+    }
+
+    // `loadtxt` from any reader
+    pub fn loadtxt_from<E: Num, R: BufRead>(reader: R, options: &TextOptions) -> io::Result<Collective<E>> {
+        read(reader, options, false)
+    }
+
+    /*
+      Loads a 2D Collective from a delimited text file like `loadtxt`, but missing fields are replaced by the filling value.
+
+      @param
+      - `path`: The text file to read.
+      - `options`: Delimiter, skipped lines, selected columns, dtypes, missing value strings and filling values (see `TextOptions`).
+
+      @returns
+      - `io::Result<Collective<E>>`: A rows x columns Collective. `InvalidData` for a malformed field,
+        rows with differing numbers of fields, or a file without data.
+    */
+    pub fn genfromtxt<E: Num, P: AsRef<Path>>(path: P, options: &TextOptions) -> io::Result<Collective<E>> {
+        Self::genfromtxt_from(BufReader::new(File::open(path)?), options)
+    }
+
+    // `genfromtxt` from any reader
+    pub fn genfromtxt_from<E: Num, R: BufRead>(reader: R, options: &TextOptions) -> io::Result<Collective<E>> {
+        read(reader, options, true)
+    }
+
+    /*
+      Saves a Collective as delimited text, one line per row. A Collective with more than two dimensions is written
+      as `Dimensions::rows()` lines of `Dimensions::columns()` values.
+
+      @param
+      - `path`: The text file to write.
+      - `data`: The Collective to save.
+      - `options`: Delimiter, float format, header and footer (see `TextOptions`).
+
+      @returns
+      - `io::Result<()>`: `InvalidInput` if the Collective has no data or shape allocated.
+    */
+    pub fn savetxt<E: Num, P: AsRef<Path>>(path: P, data: &Collective<E>, options: &TextOptions) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        Self::savetxt_to(&mut writer, data, options)?;
+        writer.flush()
+    }
+
+    // `savetxt` to any writer
+    pub fn savetxt_to<E: Num, W: Write>(writer: &mut W, data: &Collective<E>, options: &TextOptions) -> io::Result<()> {
+        let (elements, shape) = match (&data.data, &data.shape) {
+            (Some(elements), Some(shape)) => (elements, shape),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Tensor::savetxt(): Collective data or shape is not allocated",
+                ))
+            }
+        };
+
+        let columns = (shape.columns() as usize).max(1);
+        let delimiter = options.delimiter.unwrap_or(' ').to_string();
+        let comments = options.comments.as_deref().unwrap_or("#");
+
+        if let Some(header) = &options.header {
+            for line in header.lines() {
+                writeln!(writer, "{} {}", comments, line)?;
+            }
+        }
+
+        for row in elements.chunks(columns) {
+            let fields: Vec<String> = row
+                .iter()
+                .map(|&element| match &options.format {
+                    Some(format) => format.format(element.to_f64()),
+                    None => element.to_string(),
+                })
+                .collect();
+
+            writeln!(writer, "{}", fields.join(&delimiter))?;
+        }
+
+        if let Some(footer) = &options.footer {
+            for line in footer.lines() {
+                writeln!(writer, "{} {}", comments, line)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn read<E: Num, R: BufRead>(reader: R, options: &TextOptions, fill_missing: bool) -> io::Result<Collective<E>> {
+    // (line number, fields) of every line that holds data
+    let mut rows: Vec<(usize, Vec<String>)> = Vec::new();
+
+    for (index, line) in reader.lines().enumerate().skip(options.skip_header) {
+        let line = line?;
+
+        let content = match options.comments.as_deref().and_then(|comments| line.find(comments)) {
+            Some(start) => &line[..start],
+            None => line.as_str(),
+        };
+
+        if content.trim().is_empty() {
+            continue;
+        }
+
+        let fields = match options.delimiter {
+            Some(delimiter) => content.split(delimiter).map(|field| field.trim().to_string()).collect(),
+            None => content.split_whitespace().map(|field| field.to_string()).collect(),
+        };
+
+        rows.push((index + 1, fields));
+    }
+
+    rows.truncate(rows.len().saturating_sub(options.skip_footer));
+
+    let width = match rows.first() {
+        Some((_, fields)) => fields.len(),
+        None => return Err(invalid_data("no data to load".to_string())),
+    };
+
+    let columns: Vec<usize> = match &options.columns {
+        Some(columns) => columns.clone(),
+        None => (0..width).collect(),
+    };
+
+    if columns.is_empty() {
+        return Err(invalid_data("no columns selected".to_string()));
+    }
+
+    if let Some(&column) = columns.iter().find(|&&column| column >= width) {
+        return Err(invalid_data(format!("column {} is out of range, lines have {} columns", column, width)));
+    }
+
+    let mut elements: Vec<E> = Vec::with_capacity(rows.len() * columns.len());
+
+    for (line_number, fields) in &rows {
+        if fields.len() != width {
+            return Err(invalid_data(format!(
+                "line {} has {} columns, expected {}",
+                line_number,
+                fields.len(),
+                width
+            )));
+        }
+
+        for &column in &columns {
+            let field = fields[column].as_str();
+
+            let value = if field.is_empty() || options.missing_values.iter().any(|missing| missing == field) {
+                if !fill_missing {
+                    return Err(invalid_data(format!(
+                        "line {}, column {}: missing value, use genfromtxt to fill missing values",
+                        line_number, column
+                    )));
+                }
+
+                options.filling_values.get(&column).copied().unwrap_or(options.filling_value)
+            } else {
+                let dtype = options.dtypes.get(&column).copied().unwrap_or(TextDtype::Float);
+
+                parse_field(field, dtype).ok_or_else(|| {
+                    invalid_data(format!(
+                        "line {}, column {}: '{}' is not a valid {:?} value",
+                        line_number, column, field, dtype
+                    ))
+                })?
+            };
+
+            elements.push(E::from_f64(value));
+        }
+    }
+
+    Ok(Collective::new(
+        Some(elements.into_boxed_slice()),
+        Some(Box::new(Dimensions::new(columns.len() as f64, rows.len() as f64))),
+    ))
+}
+
+fn parse_field(field: &str, dtype: TextDtype) -> Option<f64> {
+    match dtype {
+        TextDtype::Float => field.parse::<f64>().ok(),
+        TextDtype::Integer => field.parse::<i64>().ok().map(|value| value as f64),
+        TextDtype::Boolean => match field.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "1" => Some(1.0),
+            "false" | "no" | "0" => Some(0.0),
+            _ => None,
+        },
+    }
+}
+
+/*
+   A parsed printf style format, "%[flags][width][.precision]conversion" with optional text around it.
+   Values are always formatted as f64, the same way Python's % operator (and so `numpy.savetxt`) formats them.
+*/
+#[derive(Debug, Clone, PartialEq)]
+struct FloatFormat {
+    prefix: String,
+    suffix: String,
+    left_align: bool,
+    plus_sign: bool,
+    zero_pad: bool,
+    width: usize,
+    precision: Option<usize>,
+    conversion: char,
+}
+
+impl FloatFormat {
+    fn parse(format: &str) -> Result<Self, String> {
+        let start = format
+            .find('%')
+            .ok_or_else(|| format!("format '{}' has no % conversion", format))?;
+
+        let prefix = format[..start].to_string();
+        let mut chars = format[start + 1..].char_indices().peekable();
+
+        let (mut left_align, mut plus_sign, mut zero_pad) = (false, false, false);
+        while let Some(&(_, flag)) = chars.peek() {
+            match flag {
+                '-' => left_align = true,
+                '+' => plus_sign = true,
+                '0' => zero_pad = true,
+                _ => break,
+            }
+            chars.next();
+        }
+
+        let mut width = 0;
+        while let Some(&(_, digit)) = chars.peek() {
+            match digit.to_digit(10) {
+                Some(digit) => width = width * 10 + digit as usize,
+                None => break,
+            }
+            chars.next();
+        }
+
+        let mut precision = None;
+        if let Some(&(_, '.')) = chars.peek() {
+            chars.next();
+
+            let mut digits = 0;
+            while let Some(&(_, digit)) = chars.peek() {
+                match digit.to_digit(10) {
+                    Some(digit) => digits = digits * 10 + digit as usize,
+                    None => break,
+                }
+                chars.next();
+            }
+
+            precision = Some(digits);
+        }
+
+        let (offset, conversion) = chars
+            .next()
+            .ok_or_else(|| format!("format '{}' has no conversion character", format))?;
+
+        if !matches!(conversion, 'f' | 'e' | 'E' | 'g' | 'G' | 'd' | 'i') {
+            return Err(format!("unsupported conversion '%{}' in format '{}'", conversion, format));
+        }
+
+        let suffix = format[start + 1 + offset + conversion.len_utf8()..].to_string();
+
+        if suffix.contains('%') {
+            return Err(format!("format '{}' has more than one % conversion", format));
+        }
+
+        Ok(Self {
+            prefix,
+            suffix,
+            left_align,
+            plus_sign,
+            zero_pad,
+            width,
+            precision,
+            conversion,
+        })
+    }
+
+    fn format(&self, value: f64) -> String {
+        let precision = self.precision.unwrap_or(6);
+        let upper = self.conversion.is_ascii_uppercase();
+        let magnitude = value.abs();
+
+        let body = if value.is_nan() {
+            "nan".to_string()
+        } else if value.is_infinite() {
+            "inf".to_string()
+        } else {
+            match self.conversion {
+                'f' => format!("{:.*}", precision, magnitude),
+                'e' | 'E' => exponential(magnitude, precision),
+                'g' | 'G' => general(magnitude, precision),
+                _ => format!("{}", magnitude.trunc()),
+            }
+        };
+
+        let body = if upper { body.to_uppercase() } else { body };
+
+        // Like Python, -0.0 keeps its sign, except for %d where it (and e.g. -0.5) truncates to 0
+        let negative = match self.conversion {
+            'd' | 'i' => value.trunc() < 0.0,
+            _ => value.is_sign_negative() && !value.is_nan(),
+        };
+
+        let sign = if negative {
+            "-"
+        } else if self.plus_sign {
+            "+"
+        } else {
+            ""
+        };
+
+        let length = sign.len() + body.len();
+        let padding = self.width.saturating_sub(length);
+
+        let field = if padding == 0 {
+            format!("{}{}", sign, body)
+        } else if self.left_align {
+            format!("{}{}{}", sign, body, " ".repeat(padding))
+        } else if self.zero_pad && value.is_finite() {
+            format!("{}{}{}", sign, "0".repeat(padding), body)
+        } else {
+            format!("{}{}{}", " ".repeat(padding), sign, body)
+        };
+
+        format!("{}{}{}", self.prefix, field, self.suffix)
+    }
+}
+
+// %e of a non-negative value: mantissa with `precision` decimals, exponent with a sign and at least two digits
+fn exponential(magnitude: f64, precision: usize) -> String {
+    let formatted = format!("{:.*e}", precision, magnitude);
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+
+    format!("{}e{}{:02}", mantissa, if exponent < 0 { '-' } else { '+' }, exponent.abs())
+}
+
+// %g of a non-negative value: %f or %e depending on the exponent, `precision` significant digits, trailing zeros removed
+fn general(magnitude: f64, precision: usize) -> String {
+    let precision = precision.max(1);
+
+    let exponent: i32 = if magnitude == 0.0 {
+        0
+    } else {
+        // The exponent after rounding to `precision` significant digits
+        let formatted = format!("{:.*e}", precision - 1, magnitude);
+        formatted.split_once('e').unwrap().1.parse().unwrap()
+    };
+
+    if exponent >= -4 && exponent < precision as i32 {
+        let decimals = (precision as i32 - 1 - exponent) as usize;
+        strip_zeros(&format!("{:.*}", decimals, magnitude))
+    } else {
+        let formatted = exponential(magnitude, precision - 1);
+        let (mantissa, exponent) = formatted.split_once('e').unwrap();
+        format!("{}e{}", strip_zeros(mantissa), exponent)
+    }
+}
+
+fn strip_zeros(number: &str) -> String {
+    if number.contains('.') {
+        number.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        number.to_string()
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
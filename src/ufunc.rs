@@ -0,0 +1,234 @@
+/*
+ * Numrs/src/ufunc.rs
+ * Q@khaa.pk
+ */
+
+/*
+   Element-wise math functions (NumPy's "ufuncs")
+   ----------------------------------------------
+   Every function comes in two forms, like `map`/`map_inplace` (see apply.rs):
+
+       let activations = logits.exp();   // a new Collective, `logits` is left untouched
+       logits.exp_inplace();             // overwrites the elements of `logits`
+
+   They are defined for the float element types (f32, f64 and, with their features, f16 and bf16).
+   The half types compute in f32 and round the result back.
+
+   - exp, ln, log2, log10, log1p (ln(1 + x)), expm1 (e^x - 1), sqrt, cbrt, pow
+   - abs, sign (-1, 0 or 1, NaN stays NaN)
+   - sin, cos, tan, arcsin, arccos, arctan and sinh, cosh, tanh, arcsinh, arccosh, arctanh
+   - floor, ceil, round (halves to even, like NumPy and Rounding::NearestEven), trunc
+   - clip(min, max)
+   - maximum(&other), minimum(&other), element by element with broadcasting (see `zip_with`), NaN wins like in NumPy
+
+   Values outside a function's domain give NaN, e.g. ln(-1) or asin(2), as with the f32/f64 methods.
+   Unallocated Collectives stay unallocated.
+*/
+
+use super::{
+    apply::{broadcast_extents, broadcast_strides},
+    collective::Collective,
+    num::Float,
+};
+
+/*
+   Generates the out-of-place and in-place forms of a function that maps one element to one element.
+*/
+macro_rules! unary {
+    ($($name:ident, $name_inplace:ident, $function:expr, $doc:literal;)*) => {
+        $(
+            #[doc = concat!("Returns a new Collective of ", $doc, " of every element.")]
+            pub fn $name(&self) -> Collective<E> {
+                self.map($function)
+            }
+
+            #[doc = concat!("Replaces every element with ", $doc, " of the element.")]
+            pub fn $name_inplace(&mut self) {
+                self.map_inplace(|value| *value = $function(*value));
+            }
+        )*
+    };
+}
+
+impl<E> Collective<E>
+where
+    E: Float,
+{
+    unary! {
+        exp, exp_inplace, E::exp, "e raised to the power";
+        ln, ln_inplace, E::ln, "the natural logarithm";
+        log2, log2_inplace, E::log2, "the base 2 logarithm";
+        log10, log10_inplace, E::log10, "the base 10 logarithm";
+        log1p, log1p_inplace, E::ln_1p, "ln(1 + x), accurate for x close to zero,";
+        expm1, expm1_inplace, E::exp_m1, "e^x - 1, accurate for x close to zero,";
+        sqrt, sqrt_inplace, E::sqrt, "the square root";
+        cbrt, cbrt_inplace, E::cbrt, "the cube root";
+        abs, abs_inplace, E::abs, "the absolute value";
+        sign, sign_inplace, sign, "the sign (-1, 0 or 1, NaN for NaN)";
+        sin, sin_inplace, E::sin, "the sine";
+        cos, cos_inplace, E::cos, "the cosine";
+        tan, tan_inplace, E::tan, "the tangent";
+        arcsin, arcsin_inplace, E::asin, "the inverse sine";
+        arccos, arccos_inplace, E::acos, "the inverse cosine";
+        arctan, arctan_inplace, E::atan, "the inverse tangent";
+        sinh, sinh_inplace, E::sinh, "the hyperbolic sine";
+        cosh, cosh_inplace, E::cosh, "the hyperbolic cosine";
+        tanh, tanh_inplace, E::tanh, "the hyperbolic tangent";
+        arcsinh, arcsinh_inplace, E::asinh, "the inverse hyperbolic sine";
+        arccosh, arccosh_inplace, E::acosh, "the inverse hyperbolic cosine";
+        arctanh, arctanh_inplace, E::atanh, "the inverse hyperbolic tangent";
+        floor, floor_inplace, E::floor, "the largest integer less than or equal to";
+        ceil, ceil_inplace, E::ceil, "the smallest integer greater than or equal to";
+        round, round_inplace, E::round_ties_even, "the nearest integer (halves to even)";
+        trunc, trunc_inplace, E::trunc, "the integer part";
+    }
+
+    /// Returns a new Collective of every element raised to the power `exponent`.
+    pub fn pow(&self, exponent: E) -> Collective<E> {
+        self.map(|value| value.powf(exponent))
+    }
+
+    /// Raises every element to the power `exponent`.
+    pub fn pow_inplace(&mut self, exponent: E) {
+        self.map_inplace(|value| *value = value.powf(exponent));
+    }
+
+    /// Returns a new Collective of every element limited to the range [`min`, `max`]. NaN stays NaN.
+    ///
+    /// # Panics
+    /// If `min` is greater than `max`.
+    pub fn clip(&self, min: E, max: E) -> Collective<E> {
+        check_clip_range(min, max);
+
+        self.map(|value| clip(value, min, max))
+    }
+
+    /// Limits every element to the range [`min`, `max`]. NaN stays NaN.
+    ///
+    /// # Panics
+    /// If `min` is greater than `max`.
+    pub fn clip_inplace(&mut self, min: E, max: E) {
+        check_clip_range(min, max);
+
+        self.map_inplace(|value| *value = clip(*value, min, max));
+    }
+
+    /// Returns a new Collective of the larger of the elements of `self` and `other`, broadcasting their shapes.
+    /// If either element is NaN the result is NaN.
+    ///
+    /// # Panics
+    /// If the shapes cannot be broadcast together.
+    pub fn maximum(&self, other: &Collective<E>) -> Collective<E> {
+        self.zip_with(other, maximum)
+    }
+
+    /// Replaces every element with the larger of it and the corresponding element of `other`, which is broadcast to the shape of `self`.
+    ///
+    /// # Panics
+    /// If `other` cannot be broadcast to the shape of `self`.
+    pub fn maximum_inplace(&mut self, other: &Collective<E>) {
+        self.combine_inplace("maximum_inplace", other, maximum);
+    }
+
+    /// Returns a new Collective of the smaller of the elements of `self` and `other`, broadcasting their shapes.
+    /// If either element is NaN the result is NaN.
+    ///
+    /// # Panics
+    /// If the shapes cannot be broadcast together.
+    pub fn minimum(&self, other: &Collective<E>) -> Collective<E> {
+        self.zip_with(other, minimum)
+    }
+
+    /// Replaces every element with the smaller of it and the corresponding element of `other`, which is broadcast to the shape of `self`.
+    ///
+    /// # Panics
+    /// If `other` cannot be broadcast to the shape of `self`.
+    pub fn minimum_inplace(&mut self, other: &Collective<E>) {
+        self.combine_inplace("minimum_inplace", other, minimum);
+    }
+
+    // Combines every element with the element of `other` broadcast to its position, writing into the existing buffer
+    fn combine_inplace(&mut self, method: &str, other: &Collective<E>, f: fn(E, E) -> E) {
+        let (extents, other_extents) = (self.extents(), other.extents());
+
+        // Nothing to combine when either Collective is unallocated
+        let (data, right) = match (&mut self.data, &other.data) {
+            (Some(data), Some(right)) => (data, right),
+            _ => return,
+        };
+
+        if broadcast_extents(&extents, &other_extents).as_deref() != Some(&extents[..]) {
+            panic!(
+                "Collective::{}(): Shape {:?} cannot be broadcast to {:?}",
+                method, other_extents, extents
+            );
+        }
+
+        // Same shape, no index arithmetic needed
+        if extents == other_extents {
+            data.iter_mut().zip(right.iter()).for_each(|(a, &b)| *a = f(*a, b));
+            return;
+        }
+
+        let right_strides = broadcast_strides(&other_extents, &extents);
+        let mut coordinates = vec![0; extents.len()];
+        let mut right_offset = 0;
+
+        for element in data.iter_mut() {
+            *element = f(*element, right[right_offset]);
+
+            // Advance like an odometer, as in zip_with
+            for axis in (0..extents.len()).rev() {
+                coordinates[axis] += 1;
+                right_offset += right_strides[axis];
+
+                if coordinates[axis] < extents[axis] {
+                    break;
+                }
+
+                coordinates[axis] = 0;
+                right_offset -= right_strides[axis] * extents[axis];
+            }
+        }
+    }
+}
+
+fn sign<E: Float>(value: E) -> E {
+    if value.is_nan() || value.is_zero() {
+        value
+    } else {
+        value.signum()
+    }
+}
+
+fn check_clip_range<E: Float>(min: E, max: E) {
+    if min > max {
+        panic!("Collective::clip(): min {} is greater than max {}", min, max);
+    }
+}
+
+fn clip<E: Float>(value: E, min: E, max: E) -> E {
+    if value < min {
+        min
+    } else if value > max {
+        max
+    } else {
+        value
+    }
+}
+
+fn maximum<E: Float>(a: E, b: E) -> E {
+    if a.is_nan() || b.is_nan() {
+        E::nan()
+    } else {
+        a.max(b)
+    }
+}
+
+fn minimum<E: Float>(a: E, b: E) -> E {
+    if a.is_nan() || b.is_nan() {
+        E::nan()
+    } else {
+        a.min(b)
+    }
+}
@@ -0,0 +1,123 @@
+/*
+ * numrs/tests/activations_test.rs
+ * Tests for activation functions and their derivatives in activations.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test activations_test -- --nocapture
+*/
+
+use numrs::activations::{self, Activation};
+use numrs::approx::CloseOptions;
+use numrs::assert_allclose;
+use numrs::collective::Collective;
+use numrs::dimensions::Dimensions;
+
+fn row<E: Default + Copy>(data: Vec<E>) -> Collective<E> {
+    let n = data.len() as f64;
+
+    Collective::new(Some(data.into_boxed_slice()), Some(Box::new(Dimensions::new(n, 1.0))))
+}
+
+const ALL: [Activation; 9] = [
+    Activation::ReLU,
+    Activation::LeakyReLU(0.1),
+    Activation::ELU(1.5),
+    Activation::GELU,
+    Activation::GELUTanh,
+    Activation::SiLU,
+    Activation::Sigmoid,
+    Activation::Tanh,
+    Activation::Softplus,
+];
+
+#[test]
+fn test_reference_values() {
+    let x = row(vec![-3.0f64, -1.0, 0.0, 1.0, 2.0]);
+
+    assert_allclose!(activations::relu(&x), row(vec![0.0, 0.0, 0.0, 1.0, 2.0]));
+    assert_allclose!(activations::leaky_relu(&x, 0.01), row(vec![-0.03, -0.01, 0.0, 1.0, 2.0]));
+    assert_allclose!(activations::elu(&x, 1.0), row(vec![(-3.0f64).exp_m1(), (-1.0f64).exp_m1(), 0.0, 1.0, 2.0]));
+    assert_allclose!(activations::sigmoid(&x), row(vec![0.04742587317756678, 0.2689414213699951, 0.5, 0.7310585786300049, 0.8807970779778823]));
+    assert_allclose!(activations::tanh(&x), x.tanh());
+    assert_allclose!(activations::softplus(&x), row(vec![0.04858735157374206, 0.31326168751822286, std::f64::consts::LN_2, 1.3132616875182228, 2.1269280110429727]));
+    assert_allclose!(activations::silu(&x), row(vec![-0.14227761953270035, -0.2689414213699951, 0.0, 0.7310585786300049, 1.7615941559557646]));
+
+    // x * Phi(x) from the standard normal CDF
+    assert_allclose!(
+        activations::gelu(&x),
+        row(vec![-0.004049694094890, -0.15865525393145707, 0.0, 0.8413447460685429, 1.9544997361036416]),
+        CloseOptions::new().with_rtol(1e-9)
+    );
+    assert_allclose!(
+        activations::gelu_tanh(&x),
+        row(vec![-0.0036373920817729943, -0.15880800939172324, 0.0, 0.8411919906082768, 1.954597694087775]),
+        CloseOptions::new().with_rtol(1e-9)
+    );
+}
+
+#[test]
+fn test_derivatives_match_finite_differences() {
+    let points = vec![-4.0f64, -2.5, -1.0, -0.3, 0.2, 0.7, 1.5, 3.0];
+    let h = 1e-6;
+
+    for activation in ALL {
+        let x = row(points.clone());
+        let forward = activation.apply(&row(points.iter().map(|x| x + h).collect()));
+        let backward = activation.apply(&row(points.iter().map(|x| x - h).collect()));
+        let numerical = forward.zip_with(&backward, |f, b| (f - b) / (2.0 * h));
+
+        let analytical = activation.derivative(&x);
+
+        assert!(
+            analytical.allclose_with(&numerical, &CloseOptions::new().with_rtol(1e-6).with_atol(1e-8)),
+            "{:?} derivative {:?} should match finite differences {:?}",
+            activation,
+            analytical,
+            numerical
+        );
+    }
+}
+
+#[test]
+fn test_stable_at_extreme_inputs() {
+    let x = row(vec![f64::NEG_INFINITY, -1e300, -1000.0, 1000.0, 1e300, f64::INFINITY]);
+
+    for activation in ALL {
+        let value = activation.apply(&x);
+        let derivative = activation.derivative(&x);
+
+        assert!(
+            derivative.iter().all(|d| d.is_finite()),
+            "{:?} derivative should be finite at extreme inputs, got {:?}",
+            activation,
+            derivative
+        );
+        assert!(!value.iter().any(|v| v.is_nan()), "{:?} should not give NaN at extreme inputs, got {:?}", activation, value);
+    }
+
+    assert_eq!(activations::sigmoid(&x).iter().copied().collect::<Vec<f64>>(), vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0], "sigmoid should saturate");
+    assert_eq!(activations::softplus(&row(vec![1000.0f64, -1000.0])).iter().copied().collect::<Vec<f64>>(), vec![1000.0, 0.0], "softplus should not overflow");
+    assert_eq!(activations::gelu(&row(vec![-1000.0f64, 1000.0])).iter().copied().collect::<Vec<f64>>(), vec![0.0, 1000.0], "GELU should saturate");
+    assert_eq!(activations::silu_derivative(&row(vec![-1000.0f64, 1000.0])).iter().copied().collect::<Vec<f64>>(), vec![0.0, 1.0], "SiLU derivative should saturate");
+}
+
+#[test]
+fn test_nan_propagates() {
+    for activation in ALL {
+        assert!(activation.apply(&row(vec![f64::NAN]))[0].is_nan(), "{:?} of NaN should be NaN", activation);
+    }
+}
+
+#[test]
+fn test_f32_matches_f64() {
+    let points = vec![-20.0, -3.0, -0.5, 0.0, 0.5, 3.0, 20.0];
+    let x64 = row(points.clone());
+    let x32 = row(points.iter().map(|&x| x as f32).collect());
+
+    for activation in ALL {
+        assert_allclose!(activation.apply(&x32), activation.apply(&x64).cast::<f32>(), CloseOptions::new().with_rtol(1e-6));
+        assert_allclose!(activation.derivative(&x32), activation.derivative(&x64).cast::<f32>(), CloseOptions::new().with_rtol(1e-6));
+    }
+}
@@ -0,0 +1,100 @@
+/*
+ * numrs/tests/apply_test.rs
+ * Tests for map, zip_with, fold and reduce in apply.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test apply_test -- --nocapture
+*/
+
+mod common;
+
+use common::{collective, extents, values};
+use numrs::Axis;
+use numrs::collective::Collective;
+
+#[test]
+fn test_map_and_map_inplace() {
+    let mut matrix = collective(vec![-2.0f32, -0.5, 0.0, 3.0], vec![2.0, 2.0]);
+
+    let leaky = matrix.map(|x| if x > 0.0 { x } else { 0.1 * x });
+    assert_eq!(values(&leaky), vec![-0.2, -0.05, 0.0, 3.0], "map() should apply the closure");
+    assert_eq!(extents(&leaky), vec![2.0, 2.0], "map() should keep the shape");
+
+    let signs: Collective<bool> = matrix.map(|x| x >= 0.0);
+    assert_eq!(values(&signs), vec![false, false, true, true], "map() may change the element type");
+
+    matrix.map_inplace(|x| *x = x.max(0.0));
+    assert_eq!(values(&matrix), vec![0.0, 0.0, 0.0, 3.0], "map_inplace() should update the elements");
+
+    let unallocated: Collective<f32> = Collective { data: None, shape: None };
+    assert!(unallocated.map(|x| x * 2.0).data.is_none(), "Unallocated data should map to unallocated data");
+}
+
+#[test]
+fn test_zip_with_broadcasting() {
+    let inputs = collective(vec![1, 2, 3, 4, 5, 6], vec![2.0, 3.0]);
+
+    let same = inputs.zip_with(&inputs, |a, b| a * b);
+    assert_eq!(values(&same), vec![1, 4, 9, 16, 25, 36], "Equal shapes should combine element by element");
+
+    let bias = collective(vec![10, 20, 30], vec![1.0, 3.0]);
+    let biased = inputs.zip_with(&bias, |x, b| x + b);
+    assert_eq!(values(&biased), vec![11, 22, 33, 14, 25, 36], "A row should broadcast over every row");
+    assert_eq!(extents(&biased), vec![2.0, 3.0], "Result should have the broadcast shape");
+
+    let scale = collective(vec![1.0f64, 10.0], vec![2.0, 1.0]);
+    let scaled = inputs.zip_with(&scale, |x, s| x as f64 * s);
+    assert_eq!(values(&scaled), vec![1.0, 2.0, 3.0, 40.0, 50.0, 60.0], "A column should broadcast over every column");
+
+    let batch = collective((0..12).collect::<Vec<i32>>(), vec![2.0, 2.0, 3.0]);
+    let shifted = batch.zip_with(&bias, |x, b| x + b);
+    assert_eq!(extents(&shifted), vec![2.0, 2.0, 3.0], "Missing outer axes should broadcast");
+    assert_eq!(values(&shifted)[9..], [19, 30, 41], "Last row of the second sample");
+
+    let outer = scale.zip_with(&bias, |s, b| s * b as f64);
+    assert_eq!(extents(&outer), vec![2.0, 3.0], "A column and a row should broadcast to a matrix");
+    assert_eq!(values(&outer), vec![10.0, 20.0, 30.0, 100.0, 200.0, 300.0], "Outer product");
+}
+
+#[test]
+#[should_panic(expected = "cannot be broadcast")]
+fn test_zip_with_incompatible_shapes_panics() {
+    let a = collective(vec![1; 6], vec![2.0, 3.0]);
+    let b = collective(vec![1; 4], vec![2.0, 2.0]);
+
+    a.zip_with(&b, |x, y| x + y);
+}
+
+#[test]
+fn test_fold_and_reduce_along_axes() {
+    let matrix = collective(vec![1.0, 5.0, 3.0, 4.0, 2.0, 6.0], vec![2.0, 3.0]);
+
+    let column_sums = matrix.fold(Some(0), 0.0, |sum, x| sum + x);
+    assert_eq!(values(&column_sums), vec![5.0, 7.0, 9.0], "Folding axis 0 should sum each column");
+    assert_eq!(extents(&column_sums), vec![1.0, 3.0], "The folded axis should be removed");
+    assert_eq!(matrix.fold(Axis::Rows.index(), 0.0, |sum, x| sum + x), column_sums, "Axis::Rows is axis 0 of a matrix");
+    assert_eq!(Axis::None.index(), None);
+
+    let row_maxima = matrix.reduce(Some(1), |a, b| if b > a { b } else { a });
+    assert_eq!(values(&row_maxima), vec![5.0, 6.0], "Reducing axis 1 should give each row's maximum");
+
+    let total = matrix.fold(None, 0.0, |sum, x| sum + x);
+    assert_eq!(values(&total), vec![21.0], "None should fold every element");
+    assert_eq!(extents(&total), vec![1.0, 1.0], "None should give a 1 x 1 Collective");
+
+    let counts = matrix.fold(Some(1), 0usize, |count, x| count + (x > 2.5) as usize);
+    assert_eq!(values(&counts), vec![2, 2], "The accumulator may have another type");
+
+    let batch = collective((0..12).collect::<Vec<i32>>(), vec![2.0, 2.0, 3.0]);
+    let summed = batch.reduce(Some(1), |a, b| a + b);
+    assert_eq!(extents(&summed), vec![2.0, 3.0], "Reducing the middle axis should keep the others");
+    assert_eq!(values(&summed), vec![3, 5, 7, 15, 17, 19], "Middle axis sums");
+}
+
+#[test]
+#[should_panic(expected = "Collective::fold(): Axis 2 is out of range")]
+fn test_fold_axis_out_of_range_panics() {
+    collective(vec![1; 4], vec![2.0, 2.0]).fold(Some(2), 0, |a, b| a + b);
+}
@@ -0,0 +1,103 @@
+/*
+ * numrs/tests/approx_test.rs
+ * Tests for approximate equality of Collectives in approx.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test approx_test -- --nocapture
+*/
+
+mod common;
+
+use common::collective;
+use numrs::approx::CloseOptions;
+use numrs::assert_allclose;
+use numrs::collective::Collective;
+use numrs::num::Tensor;
+
+#[test]
+fn test_isclose_tolerances() {
+    let actual = collective(vec![1.0, 1.000001, 100.01, 0.0, f64::INFINITY, f64::NAN], vec![2.0, 3.0]);
+    let expected = collective(vec![1.0, 1.0, 100.0, 1e-9, f64::INFINITY, f64::NAN], vec![2.0, 3.0]);
+
+    let close = actual.isclose(&expected);
+    assert_eq!(close, collective(vec![true, true, false, true, true, false], vec![2.0, 3.0]), "Masks compare and print with {{:?}}");
+    assert_eq!(
+        format!("{:?}", close),
+        "Collective([[ True,  True, False],\n            [ True,  True, False]], shape=[2, 3])"
+    );
+    assert_eq!(
+        &close.data.unwrap()[..],
+        &[true, true, false, true, true, false],
+        "Default tolerances are rtol=1e-5 and atol=1e-8, NaN is not close to NaN"
+    );
+    assert_eq!(close.shape.unwrap().to_vec(), vec![2.0, 3.0], "Result should keep the shape");
+
+    let close = actual.isclose_with(&expected, &CloseOptions::new().with_rtol(1e-3).with_equal_nan(true));
+    assert!(close.data.unwrap().iter().all(|&close| close), "Looser rtol and equal_nan should accept every element");
+
+    let options = CloseOptions::new();
+    assert!(!options.is_close(f64::INFINITY, f64::NEG_INFINITY), "Infinities of different signs are not close");
+    assert!(!options.is_close(1e300, f64::INFINITY), "Finite values are not close to infinity");
+    assert!(options.is_close(1e-8, 0.0), "atol applies around zero");
+    assert!(!options.is_close(1e-7, 0.0), "Differences beyond atol are not close to zero");
+}
+
+#[test]
+fn test_allclose() {
+    let a = collective(vec![0.1f32 + 0.2, 0.7], vec![1.0, 2.0]);
+    let b = collective(vec![0.3f32, 0.7], vec![1.0, 2.0]);
+
+    assert!(a.allclose(&b), "Rounding errors should be close");
+    let exact = CloseOptions::new().with_rtol(0.0).with_atol(0.0);
+    let nudged = collective(vec![1.0, 2.0 + 1e-12], vec![1.0, 2.0]);
+    assert!(!nudged.allclose_with(&collective(vec![1.0, 2.0], vec![1.0, 2.0]), &exact), "Zero tolerance should be exact");
+
+    let reshaped = collective(vec![0.3f32, 0.7], vec![2.0, 1.0]);
+    assert!(!a.allclose(&reshaped), "Different shapes are not close");
+
+    let unallocated: Collective<f32> = Collective { data: None, shape: b.shape.clone() };
+    assert!(!unallocated.allclose(&b), "Unallocated data is not close");
+
+    let pixels = collective(vec![10u8, 20], vec![1.0, 2.0]);
+    assert!(pixels.allclose_with(&collective(vec![11u8, 20], vec![1.0, 2.0]), &CloseOptions::new().with_atol(1.0)), "Integers compare too");
+}
+
+#[test]
+fn test_assert_allclose_macro() {
+    let mut collective = collective(vec![0.0f32, 127.5, 255.0], vec![1.0, 3.0]);
+    Tensor::normalize(&mut collective, 127.5, -1.0);
+
+    assert_allclose!(collective, self::collective(vec![-1.0f32, 0.0, 1.0], vec![1.0, 3.0]));
+    assert_allclose!(
+        collective,
+        self::collective(vec![-1.01f32, 0.0, 1.01], vec![1.0, 3.0]),
+        CloseOptions::new().with_atol(0.02),
+    );
+}
+
+#[test]
+fn test_assert_allclose_reports_mismatches() {
+    let actual = collective(vec![1.0, 2.0, 3.5, 4.0], vec![2.0, 2.0]);
+    let expected = collective(vec![1.0, 2.5, 3.0, 4.0], vec![2.0, 2.0]);
+
+    let message = *std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| assert_allclose!(actual, expected)))
+        .err()
+        .unwrap()
+        .downcast::<String>()
+        .unwrap();
+
+    assert!(message.contains("2 of 4 elements differ"), "Message should count mismatches, got {}", message);
+    assert!(message.contains("first at index 1: 2 != 2.5"), "Message should show the first mismatch, got {}", message);
+    assert!(message.contains("max absolute difference 0.5"), "Message should show the largest difference, got {}", message);
+}
+
+#[test]
+#[should_panic(expected = "Shapes differ")]
+fn test_isclose_shape_mismatch_panics() {
+    let a = collective(vec![1.0; 6], vec![2.0, 3.0]);
+    let b = collective(vec![1.0; 6], vec![3.0, 2.0]);
+
+    a.isclose(&b);
+}
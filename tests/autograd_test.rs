@@ -0,0 +1,135 @@
+/*
+ * numrs/tests/autograd_test.rs
+ * Tests for Variable, backward() and gradient checking in autograd.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test autograd_test -- --nocapture
+*/
+
+mod common;
+
+use common::collective;
+use numrs::activations::Activation;
+use numrs::assert_allclose;
+use numrs::autograd::{self, GradCheckOptions, Variable};
+use numrs::collective::Collective;
+
+fn check(f: impl Fn(&[Variable<f64>]) -> Variable<f64>, inputs: &[Collective<f64>]) {
+    if let Err(message) = autograd::check_gradients(f, inputs, &GradCheckOptions::new()) {
+        panic!("{}", message);
+    }
+}
+
+fn matrix() -> Collective<f64> {
+    collective(vec![0.5, -1.2, 2.0, 0.3, -0.7, 1.1], vec![2.0, 3.0])
+}
+
+fn row() -> Collective<f64> {
+    collective(vec![1.5, -0.4, 0.8], vec![1.0, 3.0])
+}
+
+#[test]
+fn test_arithmetic_gradients_with_broadcasting() {
+    // [2, 3] with [1, 3], the gradient of the row is summed over the two rows
+    check(|v| &v[0] + &v[1], &[matrix(), row()]);
+    check(|v| &v[0] - &v[1], &[matrix(), row()]);
+    check(|v| &(&v[0] * &v[1]) * &v[0], &[matrix(), row()]);
+    check(|v| &v[1] / &v[0], &[matrix(), row()]);
+    check(|v| -&v[0].exp().add_scalar(1.0).ln().mul_scalar(3.0), &[matrix()]);
+    check(|v| v[0].powf(3.0), &[matrix()]);
+}
+
+#[test]
+fn test_matmul_reduction_and_activation_gradients() {
+    let weights = collective(vec![0.2, -0.5, 0.9, 1.3, -0.8, 0.4], vec![3.0, 2.0]);
+    let bias = collective(vec![0.1, -0.2], vec![1.0, 2.0]);
+
+    check(|v| (&v[0].matmul(&v[1]) + &v[2]).tanh().mean(None), &[matrix(), weights.clone(), bias]);
+
+    // A stack of matrices times shared weights, and matrix by matrix
+    let stack = collective(vec![0.5, -1.2, 2.0, 0.3, -0.7, 1.1, 0.9, 0.4, -0.6, 1.7, -0.2, 0.8], vec![2.0, 2.0, 3.0]);
+    check(|v| v[0].matmul(&v[1]).sigmoid(), &[stack.clone(), weights]);
+    check(|v| v[0].matmul(&v[0].transpose()).sum(Some(2)), &[stack]);
+
+    check(|v| v[0].activation(Activation::GELU).sum(Some(0)), &[matrix()]);
+    check(|v| v[0].relu().mean(Some(1)), &[matrix()]);
+}
+
+#[test]
+fn test_reshape_and_slice_gradients() {
+    let stack = collective((0..24).map(|x| x as f64 * 0.1 - 1.0).collect(), vec![2.0, 3.0, 4.0]);
+
+    check(|v| v[0].reshape(&[6, 4]).powf(2.0), std::slice::from_ref(&stack));
+    check(|v| v[0].slice(1, 1, 3).powf(2.0), std::slice::from_ref(&stack));
+    check(|v| &v[0].slice(2, 0, 2) * &v[0].slice(2, 2, 4), &[stack]);
+}
+
+#[test]
+fn test_gradients_accumulate_until_zero_grad() {
+    let x = Variable::new(row());
+
+    // d/dx sum(x^2) = 2x, the second backward() adds the same again
+    for _ in 0..2 {
+        (&x * &x).sum(None).backward();
+    }
+    assert_allclose!(x.grad().unwrap(), row().map(|x| 4.0 * x));
+
+    // x is used twice in one expression, both paths add up
+    x.zero_grad();
+    assert!(x.grad().is_none(), "zero_grad() should clear the gradient");
+
+    (&x + &x.mul_scalar(2.0)).sum(None).backward();
+    assert_allclose!(x.grad().unwrap(), row().map(|_| 3.0));
+
+    // Constants get no gradient
+    let c = Variable::constant(row());
+    (&x * &c).sum(None).backward();
+    assert!(c.grad().is_none() && !c.requires_grad());
+}
+
+#[test]
+fn test_no_grad() {
+    let x = Variable::new(row());
+
+    let y = autograd::no_grad(|| {
+        assert!(!autograd::is_grad_enabled());
+        &x * &x
+    });
+
+    assert!(autograd::is_grad_enabled(), "no_grad() should restore recording");
+    assert!(!y.requires_grad() && y.is_leaf(), "Nothing should be recorded inside no_grad()");
+
+    let recorded = &x * &x;
+    assert!(recorded.requires_grad() && !recorded.is_leaf());
+    assert_eq!(format!("{:?}", recorded.sum(None)), "Variable(Collective([[3.05]], shape=[1, 1]), grad_fn=sum)");
+}
+
+#[test]
+#[should_panic(expected = "Variable::backward(): The value has 3 elements")]
+fn test_backward_needs_a_single_value() {
+    Variable::new(row()).mul_scalar(2.0).backward();
+}
+
+#[test]
+fn test_numerical_gradient() {
+    let gradients = autograd::numerical_gradient(|v| v[0].powf(2.0), &[row()], 1e-6);
+
+    assert_allclose!(gradients[0], row().map(|x| 2.0 * x), numrs::approx::CloseOptions::new().with_atol(1e-6));
+}
+
+#[test]
+fn test_long_tape_is_dropped_without_recursion() {
+    let leaf = Variable::new(collective(vec![1.0], vec![1.0, 1.0]));
+
+    let mut x = leaf.clone();
+    for _ in 0..20000 {
+        x = x.add_scalar(0.0);
+    }
+
+    x.backward();
+    drop(x);
+
+    assert_eq!(leaf.grad().unwrap()[0], 1.0);
+}
@@ -0,0 +1,171 @@
+/*
+ * numrs/tests/collective_test.rs
+ * Tests for collective.rs module
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test collective_test -- --nocapture
+*/
+
+use numrs::collective::Collective;
+use numrs::dimensions::Dimensions;
+use numrs::{Rounding, Saturation};
+
+fn vector<E: Default + Copy>(values: Vec<E>) -> Collective<E> {
+    let n = values.len() as f64;
+
+    Collective::new(Some(values.into_boxed_slice()), Some(Box::new(Dimensions::new(n, 1.0))))
+}
+
+#[test]
+fn test_cast_float_to_integer_defaults() {
+    let collective = vector(vec![2.5f64, 3.5, -2.5, 300.0, -1.0, f64::NAN]);
+
+    let result = collective.cast::<u8>();
+
+    assert_eq!(&result.data.unwrap()[..], &[2, 4, 0, 255, 0, 0], "cast() should round to nearest-even and saturate");
+    assert_eq!(result.shape.unwrap().get_n(), 6, "Shape should be preserved");
+}
+
+#[test]
+fn test_astype_truncate_and_wrap() {
+    let collective = vector(vec![2.7f32, -2.7, 300.0]);
+
+    let result = collective.astype::<u8>(Rounding::Truncate, Saturation::Wrap);
+
+    assert_eq!(&result.data.unwrap()[..], &[2, 254, 44], "Truncation rounds toward zero, wrap overflows modulo 256");
+}
+
+#[test]
+fn test_astype_integer_to_integer() {
+    let collective = vector(vec![-1i64, 70000, i64::MAX]);
+
+    let saturated = collective.astype::<i16>(Rounding::NearestEven, Saturation::Saturate);
+    let wrapped = collective.astype::<i16>(Rounding::NearestEven, Saturation::Wrap);
+
+    assert_eq!(&saturated.data.unwrap()[..], &[-1, i16::MAX, i16::MAX], "Saturate should clamp to the i16 range");
+    assert_eq!(&wrapped.data.unwrap()[..], &[-1, 70000i64 as i16, -1], "Wrap should behave like an `as` cast");
+}
+
+#[test]
+fn test_astype_integer_to_integer_is_exact() {
+    let collective = vector(vec![u64::MAX, (1u64 << 53) + 1]);
+
+    let result = collective.cast::<u128>();
+
+    assert_eq!(&result.data.unwrap()[..], &[u64::MAX as u128, (1u128 << 53) + 1], "Integer casts should not go through f64");
+}
+
+#[test]
+fn test_astype_narrowing_float() {
+    // 1 + 2^-24 + 2^-25 lies between the f32 values 1 and 1 + 2^-23, closer to 1 + 2^-23
+    let collective = vector(vec![1.0f64 + 2f64.powi(-24) + 2f64.powi(-25), -1.0e300, 1.0e-50]);
+
+    let nearest = collective.astype::<f32>(Rounding::NearestEven, Saturation::Wrap);
+    let truncated = collective.astype::<f32>(Rounding::Truncate, Saturation::Saturate);
+
+    assert_eq!(nearest[0], 1.0 + f32::EPSILON, "Nearest-even should round up");
+    assert_eq!(nearest[1], f32::NEG_INFINITY, "Wrap should overflow to infinity");
+    assert_eq!(truncated[0], 1.0, "Truncation should round toward zero");
+    assert_eq!(truncated[1], f32::MIN, "Saturate should clamp to f32::MIN");
+    assert_eq!(truncated[2], 0.0, "Values below the smallest subnormal should truncate to zero");
+}
+
+#[test]
+fn test_cast_keeps_infinities() {
+    // -inf is what attention and softmax masks are made of, saturating must not turn it into f64::MIN
+    let collective = vector(vec![f32::INFINITY, f32::NEG_INFINITY, f32::NAN]);
+
+    let widened = collective.cast::<f64>();
+    assert_eq!(widened[0], f64::INFINITY, "inf should stay inf");
+    assert_eq!(widened[1], f64::NEG_INFINITY, "-inf should stay -inf");
+    assert!(widened[2].is_nan(), "NaN should stay NaN");
+
+    let narrowed = vector(vec![f64::NEG_INFINITY, 1.0e300]).cast::<f32>();
+    assert_eq!(narrowed[0], f32::NEG_INFINITY, "-inf should stay -inf");
+    assert_eq!(narrowed[1], f32::MAX, "Finite values out of range should still clamp");
+}
+
+#[test]
+fn test_astype_large_integer_to_float_rounds_once() {
+    let collective = vector(vec![(1i64 << 54) + (1 << 30) + 1, (1i64 << 60) - 1, i64::MAX, i64::MIN]);
+
+    // Through f64 the first value would round to the f32 tie 2^54 + 2^30, and from there down to 2^54
+    let nearest = collective.astype::<f32>(Rounding::NearestEven, Saturation::Saturate);
+    assert_eq!(nearest[0] as f64, 2f64.powi(54) + 2f64.powi(31), "Nearest-even should round the exact integer");
+    assert_eq!(nearest[2] as f64, 2f64.powi(63), "i64::MAX should round up to 2^63");
+
+    // Through f64 these would round up to 2^60 and 2^63 before truncating
+    let truncated = collective.astype::<f32>(Rounding::Truncate, Saturation::Saturate);
+    assert_eq!(truncated[1] as f64, 2f64.powi(60) - 2f64.powi(36), "Truncation should not round up");
+    assert_eq!(truncated[2] as f64, 2f64.powi(63) - 2f64.powi(39), "Truncation should not round up");
+    assert_eq!(truncated[3] as f64, -(2f64.powi(63)), "i64::MIN is exact");
+
+    let truncated = collective.astype::<f64>(Rounding::Truncate, Saturation::Saturate);
+    assert_eq!(truncated[1], 2f64.powi(60) - 2f64.powi(7), "Truncation to f64 should not round up");
+    assert_eq!(truncated[2], 2f64.powi(63) - 2f64.powi(10), "Truncation to f64 should not round up");
+
+    let small = vector(vec![(1i64 << 53) + 1, -(1i64 << 53) - 1]);
+    assert_eq!(&small.cast::<f64>().data.unwrap()[..], &[2f64.powi(53), -(2f64.powi(53))], "Ties round to even");
+}
+
+#[test]
+fn test_astype_truncate_subnormal() {
+    // 1.5 times the smallest positive f32 subnormal
+    let smallest = f32::from_bits(1) as f64;
+    let collective = vector(vec![smallest * 1.5, smallest * 2.5]);
+
+    let truncated = collective.astype::<f32>(Rounding::Truncate, Saturation::Saturate);
+
+    assert_eq!(truncated[0], f32::from_bits(1), "Truncation should work in the subnormal range");
+    assert_eq!(truncated[1], f32::from_bits(2), "Truncation should work in the subnormal range");
+}
+
+#[test]
+fn test_cast_unallocated() {
+    let collective: Collective<f64> = Collective::new(None, Some(Box::new(Dimensions::new(2.0, 2.0))));
+
+    let result = collective.cast::<f32>();
+
+    assert!(result.data.is_none(), "Unallocated data should stay unallocated");
+    assert!(result.shape.is_some(), "Shape should still be set");
+}
+
+#[test]
+fn test_clone_and_equality() {
+    let collective = Collective::new(
+        Some(vec![1.0f64, 2.0, 3.0, 4.0].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(2.0, 2.0))),
+    );
+
+    let mut copy = collective.clone();
+    assert_eq!(copy, collective, "A clone should be equal");
+
+    copy[3] = 5.0;
+    assert_eq!(collective[3], 4.0, "A clone should not share data");
+    assert_ne!(copy, collective, "Different data should not be equal");
+
+    let row = Collective::new(collective.data.clone(), Some(Box::new(Dimensions::new(4.0, 1.0))));
+    assert_ne!(row, collective, "Different shapes should not be equal");
+
+    let nan = vector(vec![f64::NAN]);
+    assert_ne!(nan, nan.clone(), "NaN should not be equal to itself");
+}
+
+#[test]
+fn test_debug_format() {
+    let collective = Collective::new(
+        Some(vec![0.0f64, 1.5, -2.0, 3.0, 4.0, 5.0].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(3.0, 2.0))),
+    );
+
+    assert_eq!(
+        format!("{:?}", collective),
+        "Collective([[ 0. ,  1.5, -2. ],\n            [ 3. ,  4. ,  5. ]], shape=[2, 3])",
+        "Debug should print comma separated elements and the shape"
+    );
+
+    let unallocated: Collective<i32> = Collective { data: None, shape: None };
+    assert_eq!(format!("{:?}", unallocated), "Collective([], shape=None)", "Unallocated Collectives should print too");
+}
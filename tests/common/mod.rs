@@ -0,0 +1,44 @@
+/*
+ * numrs/tests/common/mod.rs
+ * Helpers shared by the integration tests, `mod common;` in a test file
+ * Q@khaa.pk
+ */
+
+// Every test file uses a different subset of the helpers
+#![allow(dead_code)]
+
+use numrs::collective::Collective;
+use numrs::dimensions::Dimensions;
+
+// A Collective of the given data and extents, from the outermost axis to the innermost
+pub fn collective<E: Default + Copy>(data: Vec<E>, extents: Vec<f64>) -> Collective<E> {
+    Collective::new(Some(data.into_boxed_slice()), Some(Box::new(Dimensions::from_vec(extents))))
+}
+
+pub fn values<E: Copy>(collective: &Collective<E>) -> Vec<E> {
+    collective.iter().copied().collect()
+}
+
+pub fn extents<E>(collective: &Collective<E>) -> Vec<f64> {
+    collective.shape.as_ref().unwrap().to_vec()
+}
+
+// A file name in the temporary directory, unique to the test process
+pub fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("numrs_{}_{}", std::process::id(), name))
+}
+
+// Builds an .npy file the way NumPy lays it out, with a version 1.0 header
+pub fn npy_bytes(dict: &str, data: &[u8]) -> Vec<u8> {
+    let mut header = dict.to_string();
+    while !(10 + header.len() + 1).is_multiple_of(64) {
+        header.push(' ');
+    }
+    header.push('\n');
+
+    let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+    bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(header.as_bytes());
+    bytes.extend_from_slice(data);
+    bytes
+}
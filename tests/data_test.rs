@@ -0,0 +1,161 @@
+/*
+ * numrs/tests/data_test.rs
+ * Tests for the DataLoader in data.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test data_test -- --nocapture
+*/
+
+mod common;
+
+use common::{collective, extents, values};
+use numrs::collective::Collective;
+use numrs::data::DataLoader;
+use numrs::labels;
+
+// 10 samples of 2 features, sample i is [10 * i, 10 * i + 1] with the label i
+fn dataset() -> (Collective<f32>, Collective<i32>) {
+    let features = (0..10).flat_map(|i| [10.0 * i as f32, 10.0 * i as f32 + 1.0]).collect();
+
+    (collective(features, vec![10.0, 2.0]), collective((0..10).collect(), vec![1.0, 10.0]))
+}
+
+#[test]
+fn test_batches_in_order() {
+    let (features, labels) = dataset();
+    let mut loader = DataLoader::new(&features, &labels, 4);
+
+    assert_eq!((loader.samples(), loader.len()), (10, 3));
+
+    let batches: Vec<_> = loader.epoch().collect();
+    assert_eq!(batches.len(), 3);
+
+    let (x, y) = &batches[0];
+    assert_eq!(extents(x), vec![4.0, 2.0], "Features should be split along the leading axis");
+    assert_eq!(values(x), vec![0.0, 1.0, 10.0, 11.0, 20.0, 21.0, 30.0, 31.0]);
+    assert_eq!(extents(y), vec![1.0, 4.0], "1-D labels should give 1-D batches");
+    assert_eq!(values(y), vec![0, 1, 2, 3]);
+
+    // The last batch holds the rest
+    let (x, y) = &batches[2];
+    assert_eq!((extents(x), extents(y)), (vec![2.0, 2.0], vec![1.0, 2.0]));
+    assert_eq!(values(y), vec![8, 9]);
+
+    let mut loader = DataLoader::new(&features, &labels, 4).with_drop_last(true);
+    assert_eq!(loader.len(), 2);
+    assert_eq!(loader.epoch().count(), 2, "drop_last should discard the smaller batch");
+    assert!(DataLoader::new(&features, &labels, 16).with_drop_last(true).is_empty());
+}
+
+#[test]
+fn test_shuffle_visits_every_sample_once_per_epoch() {
+    let (features, labels) = dataset();
+    let mut loader = DataLoader::new(&features, &labels, 3).with_shuffle(true).with_seed(7);
+
+    let mut orders = Vec::new();
+    for _ in 0..3 {
+        let mut order = Vec::new();
+
+        for (x, y) in loader.epoch() {
+            // Features and labels stay paired
+            for (sample, &label) in values(&y).iter().enumerate() {
+                assert_eq!(x[sample * 2], 10.0 * label as f32);
+            }
+            order.extend(values(&y));
+        }
+
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..10).collect::<Vec<i32>>(), "Every sample should be visited once");
+
+        orders.push(order);
+    }
+
+    assert!(orders[0] != orders[1] || orders[1] != orders[2], "Epochs should be shuffled differently");
+
+    // The same seed gives the same orders
+    let mut again = DataLoader::new(&features, &labels, 3).with_shuffle(true).with_seed(7);
+    let first: Vec<i32> = again.epoch().flat_map(|(_, y)| values(&y)).collect();
+    assert_eq!(first, orders[0]);
+}
+
+#[test]
+fn test_prefetch_gives_the_same_batches() {
+    let (features, labels) = dataset();
+
+    let mut inline = DataLoader::new(&features, &labels, 4).with_shuffle(true).with_seed(1);
+    let mut prefetched = DataLoader::new(&features, &labels, 4).with_shuffle(true).with_seed(1).with_prefetch(2);
+
+    for _ in 0..2 {
+        let expected: Vec<_> = inline.epoch().collect();
+        let actual: Vec<_> = prefetched.epoch().collect();
+
+        assert!(expected == actual, "Prefetching should not change the batches");
+    }
+
+    // Dropping an epoch before its end stops the thread
+    let first = prefetched.epoch().next();
+    assert!(first.is_some());
+    assert_eq!(prefetched.epoch().count(), 3);
+}
+
+#[test]
+fn test_labels_with_a_leading_axis() {
+    // 6 images of 2 x 3 and one-hot labels [6, 4]
+    let images = collective((0..36).map(|x| x as f64).collect(), vec![6.0, 2.0, 3.0]);
+    let targets: Collective<f64> = labels::one_hot(&collective(vec![0, 3, 1, 2, 2, 0], vec![1.0, 6.0]), 4);
+
+    let mut loader = DataLoader::new(&images, &targets, 4);
+    let (x, y) = loader.epoch().nth(1).unwrap();
+
+    // one_hot() of 1-D labels is [1, 6, 4], split along its second axis
+    assert_eq!(extents(&targets), vec![1.0, 6.0, 4.0]);
+    assert_eq!(extents(&x), vec![2.0, 2.0, 3.0]);
+    assert_eq!(values(&x), (24..36).map(|x| x as f64).collect::<Vec<f64>>());
+    assert_eq!(extents(&y), vec![1.0, 2.0, 4.0]);
+    assert_eq!(values(&labels::from_one_hot(&y)), vec![2, 0]);
+
+    // Labels with a leading axis of samples
+    let rows = collective((0..12).map(|x| x as f64).collect(), vec![6.0, 2.0]);
+    let (_, y) = DataLoader::new(&images, &rows, 4).epoch().next().unwrap();
+
+    assert_eq!(extents(&y), vec![4.0, 2.0]);
+    assert_eq!(values(&y), (0..8).map(|x| x as f64).collect::<Vec<f64>>());
+}
+
+#[test]
+fn test_one_dimensional_features() {
+    // 6 scalar samples [1, 6] with their labels [1, 6]
+    let features = collective(vec![0.5f32, 1.5, 2.5, 3.5, 4.5, 5.5], vec![1.0, 6.0]);
+    let targets = collective((0..6).collect::<Vec<i32>>(), vec![1.0, 6.0]);
+
+    let mut loader = DataLoader::new(&features, &targets, 4);
+    assert_eq!((loader.samples(), loader.len()), (6, 2));
+
+    let batches: Vec<_> = loader.epoch().collect();
+    assert_eq!(extents(&batches[0].0), vec![1.0, 4.0]);
+    assert_eq!(values(&batches[1].0), vec![4.5, 5.5]);
+    assert_eq!(values(&batches[1].1), vec![4, 5]);
+
+    // The features [1, 4] of a single sample with its label
+    let loader = DataLoader::new(&collective(vec![1.0f32; 4], vec![1.0, 4.0]), &collective(vec![3], vec![1.0, 1.0]), 4);
+    assert_eq!(loader.samples(), 1);
+}
+
+#[test]
+#[should_panic(expected = "DataLoader::new(): Features of shape [10, 2] and labels of shape [1, 9] do not hold the same number of samples")]
+fn test_labels_must_match_the_samples() {
+    let (features, _) = dataset();
+
+    DataLoader::new(&features, &collective((0..9).collect::<Vec<i32>>(), vec![1.0, 9.0]), 4);
+}
+
+#[test]
+#[should_panic(expected = "DataLoader::new(): Features of shape [10, 2] do not match the 19 elements of their data")]
+fn test_shape_must_match_the_data() {
+    let (_, labels) = dataset();
+
+    DataLoader::new(&collective(vec![0.0f32; 19], vec![10.0, 2.0]), &labels, 4).with_prefetch(2).epoch().count();
+}
@@ -0,0 +1,159 @@
+/*
+ * numrs/tests/idx_test.rs
+ * Tests for reading and writing IDX (MNIST) files in idx.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test idx_test -- --nocapture
+*/
+
+mod common;
+
+use common::temp_path;
+use numrs::collective::Collective;
+use numrs::dimensions::Dimensions;
+use numrs::idx;
+use std::io::Write;
+
+// Two 2 x 3 "images", laid out like train-images-idx3-ubyte
+fn images_bytes() -> Vec<u8> {
+    let mut bytes = vec![0x00, 0x00, 0x08, 0x03];
+    for extent in [2u32, 2, 3] {
+        bytes.extend_from_slice(&extent.to_be_bytes());
+    }
+    bytes.extend(0u8..12);
+    bytes
+}
+
+#[test]
+fn test_read_mnist_style_images() {
+    let images = Collective::<u8>::read_idx(&mut &images_bytes()[..]).unwrap();
+
+    assert_eq!(images.shape.as_ref().unwrap().to_vec(), vec![2.0, 2.0, 3.0], "Extents should become the Dimensions chain");
+    assert_eq!(images.shape.as_ref().unwrap().columns(), 3.0, "Innermost extent should be the columns");
+    assert_eq!(&images.data.unwrap()[..], &(0u8..12).collect::<Vec<u8>>()[..], "Pixels should load in order");
+}
+
+#[test]
+fn test_read_labels_file() {
+    // train-labels-idx1-ubyte is a 1-D file
+    let mut bytes = vec![0x00, 0x00, 0x08, 0x01];
+    bytes.extend_from_slice(&5u32.to_be_bytes());
+    bytes.extend_from_slice(&[5, 0, 4, 1, 9]);
+
+    let labels = Collective::<u8>::read_idx(&mut &bytes[..]).unwrap();
+
+    let shape = labels.shape.unwrap();
+    assert_eq!(shape.rows(), 1.0, "Labels should load as a single row");
+    assert_eq!(shape.columns(), 5.0, "Labels should load as one column per label");
+}
+
+#[test]
+fn test_gzipped_file_round_trip() {
+    let path = temp_path("images-idx3-ubyte.gz");
+
+    let images = Collective::<u8>::read_idx(&mut &images_bytes()[..]).unwrap();
+    images.save_idx(&path).unwrap();
+
+    let raw = std::fs::read(&path).unwrap();
+    assert_eq!(&raw[..2], &[0x1f, 0x8b], "A .gz file name should be gzipped");
+
+    let loaded = Collective::<u8>::load_idx(&path).unwrap();
+    assert_eq!(loaded.data, images.data, "Gzipped file should round-trip");
+
+    let header = idx::read_header(&path).unwrap();
+    assert_eq!(header.shape, vec![2, 2, 3], "Header should be readable through gzip");
+    assert_eq!(header.dtype(), Some("u8"), "dtype should be u8");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_gzip_is_detected_by_content() {
+    let path = temp_path("images-without-extension");
+
+    let mut encoder = flate2::write::GzEncoder::new(std::fs::File::create(&path).unwrap(), flate2::Compression::fast());
+    encoder.write_all(&images_bytes()).unwrap();
+    encoder.finish().unwrap();
+
+    let loaded = Collective::<u8>::load_idx(&path).unwrap();
+    assert_eq!(loaded.shape.unwrap().get_n(), 12, "Gzipped data should be detected without a .gz name");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_other_dtypes_are_big_endian() {
+    let path = temp_path("values-idx2-float");
+
+    let values = Collective::new(
+        Some(vec![1.5f32, -2.0, 0.25, 1e-3].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(2.0, 2.0))),
+    );
+    values.save_idx(&path).unwrap();
+
+    let raw = std::fs::read(&path).unwrap();
+    assert_eq!(&raw[..4], &[0x00, 0x00, 0x0D, 0x02], "Magic number should name f32 and two dimensions");
+    assert_eq!(&raw[12..16], &1.5f32.to_be_bytes(), "Data should be big endian");
+
+    assert_eq!(Collective::<f32>::load_idx(&path).unwrap().data, values.data, "f32 should round-trip");
+
+    let error = Collective::<u8>::load_idx(&path).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "Loading f32 data as u8 should fail");
+
+    let i16_values = Collective::new(
+        Some(vec![-300i16, 300].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(2.0, 1.0))),
+    );
+    let mut buffer = Vec::new();
+    i16_values.write_idx(&mut buffer).unwrap();
+    assert_eq!(Collective::<i16>::read_idx(&mut &buffer[..]).unwrap().data, i16_values.data, "i16 should round-trip");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_invalid_magic_number() {
+    let mut bytes = images_bytes();
+    bytes[2] = 0x0A;
+
+    let error = Collective::<u8>::read_idx(&mut &bytes[..]).err().unwrap();
+    assert!(error.to_string().contains("0x0A"), "Unknown dtypes should be reported");
+
+    bytes[0] = 0x01;
+    let error = Collective::<u8>::read_idx(&mut &bytes[..]).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "A non-zero first byte is not IDX");
+}
+
+#[test]
+fn test_header_sizes_are_not_trusted() {
+    let header = |extents: [u32; 3]| {
+        let mut bytes = vec![0x00, 0x00, 0x08, 0x03];
+        for extent in extents {
+            bytes.extend_from_slice(&extent.to_be_bytes());
+        }
+        bytes.extend(0u8..12);
+        bytes
+    };
+
+    // 2^48 bytes claimed, 12 present
+    let error = Collective::<u8>::read_idx(&mut &header([65536; 3])[..]).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof, "Missing data should be reported, not allocated");
+
+    let error = Collective::<u8>::read_idx(&mut &header([u32::MAX; 3])[..]).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "A size that overflows should be invalid data");
+}
+
+#[test]
+fn test_shape_must_match_data() {
+    let mismatched = Collective::new(
+        Some(vec![1u8, 2, 3, 4, 5].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(3.0, 2.0))),
+    );
+
+    let mut buffer = Vec::new();
+    let error = mismatched.write_idx(&mut buffer).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput, "A shape that does not match the data should be rejected");
+    assert!(buffer.is_empty(), "Nothing should be written");
+}
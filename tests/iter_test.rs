@@ -0,0 +1,90 @@
+/*
+ * numrs/tests/iter_test.rs
+ * Tests for iterating over Collectives in iter.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test iter_test -- --nocapture
+*/
+
+use numrs::collective::Collective;
+use numrs::dimensions::Dimensions;
+
+// 2 samples of 2 x 3
+fn batch() -> Collective<i32> {
+    Collective::new(
+        Some((0..12).collect::<Vec<i32>>().into_boxed_slice()),
+        Some(Box::new(Dimensions::from_vec(vec![2.0, 2.0, 3.0]))),
+    )
+}
+
+#[test]
+fn test_iter_and_iter_mut() {
+    let mut collective = batch();
+
+    assert_eq!(collective.iter().sum::<i32>(), 66, "iter() should visit every element");
+
+    for value in collective.iter_mut() {
+        *value *= 2;
+    }
+    for value in &mut collective {
+        *value += 1;
+    }
+
+    assert_eq!((&collective).into_iter().copied().collect::<Vec<i32>>(), (0..12).map(|i| i * 2 + 1).collect::<Vec<i32>>(), "Elements should be updated in place");
+    assert_eq!(collective.into_iter().last(), Some(23), "An owned Collective should iterate by value");
+
+    let unallocated: Collective<i32> = Collective { data: None, shape: None };
+    assert_eq!(unallocated.iter().count(), 0, "Unallocated data should iterate over nothing");
+}
+
+#[test]
+fn test_indexed_iter() {
+    let collective = batch();
+    let indexed: Vec<(Vec<usize>, i32)> = collective.indexed_iter().map(|(coordinates, &value)| (coordinates, value)).collect();
+
+    assert_eq!(indexed.len(), 12, "Every element should be visited");
+    assert_eq!(indexed[0], (vec![0, 0, 0], 0), "First coordinates should be zero");
+    assert_eq!(indexed[4], (vec![0, 1, 1], 4), "Innermost axis should change fastest");
+    assert_eq!(indexed[11], (vec![1, 1, 2], 11), "Last coordinates should be the extents minus one");
+}
+
+#[test]
+fn test_axis_iter() {
+    let collective = batch();
+
+    let samples: Vec<Collective<i32>> = collective.axis_iter(0).collect();
+    assert_eq!(samples.len(), 2, "Axis 0 should yield the samples");
+    assert_eq!(samples[1].shape.as_ref().unwrap().to_vec(), vec![2.0, 3.0], "Samples should be 2 x 3");
+    assert_eq!(samples[1].iter().copied().collect::<Vec<i32>>(), (6..12).collect::<Vec<i32>>(), "Second sample");
+
+    let rows: Vec<Collective<i32>> = collective.axis_iter(1).collect();
+    assert_eq!(rows[1].iter().copied().collect::<Vec<i32>>(), vec![3, 4, 5, 9, 10, 11], "Axis 1 should gather from every sample");
+
+    let columns = collective.axis_iter(2);
+    assert_eq!(columns.len(), 3, "Axis 2 should yield one sub-array per column");
+    assert_eq!(columns.last().unwrap().iter().copied().collect::<Vec<i32>>(), vec![2, 5, 8, 11], "Last column");
+
+    let matrix = Collective::new(Some(vec![1.0, 2.0, 3.0, 4.0].into_boxed_slice()), Some(Box::new(Dimensions::new(2.0, 2.0))));
+    let column = matrix.axis_iter(1).next().unwrap();
+    assert_eq!(column.shape.as_ref().unwrap().to_vec(), vec![1.0, 2.0], "Sub-arrays of one axis should be 1-D");
+    assert_eq!(column.iter().copied().collect::<Vec<f64>>(), vec![1.0, 3.0], "First column");
+}
+
+#[test]
+#[should_panic(expected = "out of range")]
+fn test_axis_iter_out_of_range_panics() {
+    batch().axis_iter(3);
+}
+
+#[test]
+fn test_collect_into_collective() {
+    let squares: Collective<i32> = (1..=4).map(|i| i * i).collect();
+
+    assert_eq!(squares.shape.as_ref().unwrap().to_vec(), vec![1.0, 4.0], "Collected Collectives should be 1-D");
+    assert_eq!(&squares.data.as_ref().unwrap()[..], &[1, 4, 9, 16], "Collected elements");
+
+    let empty: Collective<i32> = std::iter::empty().collect();
+    assert!(empty.data.is_none(), "An empty iterator should give an unallocated Collective");
+}
@@ -0,0 +1,79 @@
+/*
+ * numrs/tests/labels_test.rs
+ * Tests for one-hot encoding and the label utilities in labels.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test labels_test -- --nocapture
+*/
+
+mod common;
+
+use common::collective;
+use numrs::collective::Collective;
+use numrs::dimensions::Dimensions;
+use numrs::labels;
+use numrs::num::Tensor;
+
+#[test]
+fn test_one_hot_adds_a_trailing_axis() {
+    let labels = collective(vec![2, 0, 1, 2], vec![2.0, 2.0]);
+    let encoded: Collective<f32> = labels::one_hot(&labels, 3);
+
+    assert_eq!(encoded.shape.as_ref().unwrap().to_vec(), vec![2.0, 2.0, 3.0]);
+    assert_eq!(
+        encoded.data.as_deref().unwrap(),
+        &[0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]
+    );
+
+    // Labels of Tensor::randint round trip through the encoding
+    let labels = Tensor::randint(0, 5, Dimensions::new(16.0, 1.0));
+    let encoded: Collective<f64> = labels::one_hot(&labels, 5);
+
+    assert_eq!(encoded.shape.as_ref().unwrap().to_vec(), vec![1.0, 16.0, 5.0]);
+    assert_eq!(labels::from_one_hot(&encoded), labels);
+}
+
+#[test]
+#[should_panic(expected = "labels::one_hot(): Label 3 is out of range for 3 classes")]
+fn test_one_hot_rejects_labels_out_of_range() {
+    let _: Collective<f32> = labels::one_hot(&collective(vec![0, 3], vec![1.0, 2.0]), 3);
+}
+
+#[test]
+fn test_argmax() {
+    let scores = collective(vec![0.1, 0.7, 0.2, 0.5, 0.5, f64::NAN, 3.0, -1.0, 3.0], vec![3.0, 3.0]);
+
+    // Per row: the first of equal maxima wins, NaN beats everything
+    assert_eq!(labels::from_one_hot(&scores).data.as_deref().unwrap(), &[1, 2, 0]);
+    assert_eq!(scores.argmax(Some(0)).data.as_deref().unwrap(), &[2, 0, 1]);
+
+    let flat = scores.argmax(None);
+    assert_eq!(flat.shape.as_ref().unwrap().to_vec(), vec![1.0, 1.0]);
+    assert_eq!(flat.data.as_deref().unwrap(), &[5]);
+
+    // Integer elements
+    assert_eq!(collective(vec![3, 9, 9, 1], vec![1.0, 4.0]).argmax(Some(1)).data.as_deref().unwrap(), &[1]);
+}
+
+#[test]
+fn test_bincount_and_unique_with_counts() {
+    let labels = collective(vec![3, 1, 1, 0, 3, 3], vec![2.0, 3.0]);
+
+    assert_eq!(labels::bincount(&labels, 0), vec![1, 2, 0, 3]);
+    assert_eq!(labels::bincount(&labels, 6), vec![1, 2, 0, 3, 0, 0]);
+    assert_eq!(labels::unique_with_counts(&labels), (vec![0, 1, 3], vec![1, 2, 3]));
+
+    // Nothing allocated, e.g. Tensor::randint of zero elements
+    let empty = Tensor::randint(0, 3, Dimensions::new(0.0, 0.0));
+    assert_eq!(labels::bincount(&empty, 3), vec![0, 0, 0]);
+    assert_eq!(labels::unique_with_counts(&empty), (vec![], vec![]));
+    assert!(labels::one_hot::<f32>(&empty, 3).data.is_none());
+}
+
+#[test]
+#[should_panic(expected = "labels::bincount(): Label -1 is negative")]
+fn test_bincount_rejects_negative_labels() {
+    labels::bincount(&collective(vec![0, -1], vec![1.0, 2.0]), 0);
+}
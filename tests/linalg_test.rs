@@ -0,0 +1,56 @@
+/*
+ * numrs/tests/linalg_test.rs
+ * Tests for matmul and transpose in linalg.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test linalg_test -- --nocapture
+*/
+
+mod common;
+
+use common::{collective, extents, values};
+
+#[test]
+fn test_matmul() {
+    let a = collective(vec![1.0f64, 2.0, 3.0, 4.0, 5.0, 6.0], vec![2.0, 3.0]);
+    let b = collective(vec![7.0f64, 8.0, 9.0, 10.0, 11.0, 12.0], vec![3.0, 2.0]);
+
+    let product = a.matmul(&b);
+    assert_eq!(extents(&product), vec![2.0, 2.0], "[2, 3] x [3, 2] should be [2, 2]");
+    assert_eq!(values(&product), vec![58.0, 64.0, 139.0, 154.0]);
+
+    // A stack of two matrices times the same matrix, and matrix by matrix
+    let stack = collective(vec![1.0f64, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0], vec![2.0, 2.0, 2.0]);
+    let identity = collective(vec![1.0f64, 0.0, 0.0, 1.0], vec![2.0, 2.0]);
+
+    assert_eq!(values(&stack.matmul(&identity)), values(&stack), "Every matrix should be multiplied by the shared one");
+    assert_eq!(
+        values(&stack.matmul(&stack)),
+        vec![7.0, 10.0, 15.0, 22.0, 67.0, 78.0, 91.0, 106.0],
+        "Stacks should be multiplied matrix by matrix"
+    );
+    assert_eq!(extents(&stack.matmul(&stack)), vec![2.0, 2.0, 2.0]);
+}
+
+#[test]
+#[should_panic(expected = "Collective::matmul(): Shapes [2, 3] and [2, 3] are not aligned")]
+fn test_matmul_rejects_unaligned_shapes() {
+    let a = collective(vec![0.0f32; 6], vec![2.0, 3.0]);
+
+    a.matmul(&a);
+}
+
+#[test]
+fn test_transpose() {
+    let a = collective(vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0], vec![2.0, 3.0]);
+
+    let transposed = a.transpose();
+    assert_eq!(extents(&transposed), vec![3.0, 2.0]);
+    assert_eq!(values(&transposed), vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+    assert_eq!(transposed.transpose(), a, "Transposing twice should give the original");
+
+    let stack = collective(vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0], vec![2.0, 2.0, 2.0]);
+    assert_eq!(values(&stack.transpose()), vec![1.0, 3.0, 2.0, 4.0, 5.0, 7.0, 6.0, 8.0], "Every matrix should be transposed");
+}
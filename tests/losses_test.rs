@@ -0,0 +1,194 @@
+/*
+ * numrs/tests/losses_test.rs
+ * Tests for loss functions and their gradients in losses.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test losses_test -- --nocapture
+*/
+
+mod common;
+
+use common::collective;
+use numrs::approx::CloseOptions;
+use numrs::assert_allclose;
+use numrs::collective::Collective;
+use numrs::losses::{self, CrossEntropyOptions, Loss, Reduction};
+
+// 3 samples of 4 classes
+fn logits() -> Collective<f64> {
+    collective(vec![2.0, 1.0, 0.1, -1.0, 0.5, 0.5, 0.5, 0.5, -3.0, 4.0, 0.0, 1.0], vec![3.0, 4.0])
+}
+
+fn labels() -> Collective<i32> {
+    collective(vec![0, 2, 1], vec![1.0, 3.0])
+}
+
+/*
+   Central differences of the reduced loss with respect to every input element, compared with the analytical gradient.
+*/
+fn check_gradient(input: &Collective<f64>, loss: impl Fn(&Collective<f64>) -> Loss<f64>) {
+    let h = 1e-6;
+    let analytical = loss(input).gradient;
+
+    let numerical: Collective<f64> = (0..input.iter().count())
+        .map(|index| {
+            let (mut plus, mut minus) = (input.clone(), input.clone());
+            plus[index] += h;
+            minus[index] -= h;
+
+            (loss(&plus).item() - loss(&minus).item()) / (2.0 * h)
+        })
+        .collect();
+
+    assert!(
+        analytical.iter().zip(numerical.iter()).all(|(a, n)| (a - n).abs() < 1e-6),
+        "Gradient {:?} should match finite differences {:?}",
+        analytical,
+        numerical
+    );
+}
+
+#[test]
+fn test_cross_entropy_value() {
+    let loss = losses::cross_entropy(&logits(), &labels(), &CrossEntropyOptions::new());
+
+    // -log softmax of the target logit of every sample
+    let log_probabilities = logits().log_softmax(1, None);
+    let expected = -(log_probabilities[0] + log_probabilities[6] + log_probabilities[9]) / 3.0;
+
+    assert!((loss.item() - expected).abs() < 1e-12, "Mean cross-entropy should be {}, got {}", expected, loss.item());
+    assert_eq!(loss.gradient.shape.as_ref().unwrap().to_vec(), vec![3.0, 4.0], "Gradient should have the shape of the logits");
+
+    let unreduced = losses::cross_entropy(&logits(), &labels(), &CrossEntropyOptions::new().with_reduction(Reduction::None));
+    assert_eq!(unreduced.value.shape.as_ref().unwrap().to_vec(), vec![1.0, 3.0], "Unreduced losses should have the shape of the targets");
+    assert_allclose!(unreduced.value, collective(vec![-log_probabilities[0], -log_probabilities[6], -log_probabilities[9]], vec![1.0, 3.0]));
+
+    let summed = losses::cross_entropy(&logits(), &labels(), &CrossEntropyOptions::new().with_reduction(Reduction::Sum));
+    assert!((summed.item() - 3.0 * expected).abs() < 1e-12, "Summed cross-entropy should be three times the mean");
+}
+
+#[test]
+fn test_cross_entropy_gradients() {
+    for options in [
+        CrossEntropyOptions::new(),
+        CrossEntropyOptions::new().with_reduction(Reduction::Sum),
+        CrossEntropyOptions::new().with_label_smoothing(0.2),
+        CrossEntropyOptions::new().with_ignore_index(2),
+    ] {
+        check_gradient(&logits(), |logits| losses::cross_entropy(logits, &labels(), &options));
+    }
+}
+
+#[test]
+fn test_label_smoothing_and_ignore_index() {
+    let smoothed = losses::cross_entropy(&logits(), &labels(), &CrossEntropyOptions::new().with_label_smoothing(0.1));
+    let plain = losses::cross_entropy(&logits(), &labels(), &CrossEntropyOptions::new());
+
+    // (1 - e) * cross-entropy + e * the mean over classes of -log p
+    let log_probabilities = logits().log_softmax(1, None);
+    let uniform = -log_probabilities.iter().sum::<f64>() / 4.0 / 3.0;
+    assert!((smoothed.item() - (0.9 * plain.item() + 0.1 * uniform)).abs() < 1e-12, "Label smoothing should mix in the uniform distribution");
+
+    let targets = collective(vec![0, -100, 1], vec![1.0, 3.0]);
+    let ignored = losses::cross_entropy(&logits(), &targets, &CrossEntropyOptions::new().with_ignore_index(-100));
+    let expected = -(log_probabilities[0] + log_probabilities[9]) / 2.0;
+    assert!((ignored.item() - expected).abs() < 1e-12, "Ignored targets should not count towards the mean");
+    assert!(ignored.gradient.iter().skip(4).take(4).all(|&g| g == 0.0), "Ignored targets should have no gradient");
+}
+
+#[test]
+#[should_panic(expected = "Target 4 is out of range for 4 classes")]
+fn test_cross_entropy_target_out_of_range_panics() {
+    losses::cross_entropy(&logits(), &collective(vec![0, 4, 1], vec![1.0, 3.0]), &CrossEntropyOptions::new());
+}
+
+#[test]
+fn test_cross_entropy_f32_large_logits() {
+    let logits = collective(vec![1000.0f32, -1000.0, 0.0, 0.0], vec![2.0, 2.0]);
+    let loss = losses::cross_entropy(&logits, &collective(vec![1, 0], vec![1.0, 2.0]), &CrossEntropyOptions::new());
+
+    assert!((loss.item() - (2000.0 + std::f32::consts::LN_2) / 2.0).abs() < 1e-3, "Large logits should give a finite loss, got {}", loss.item());
+    assert_allclose!(loss.gradient, collective(vec![0.5f32, -0.5, -0.25, 0.25], vec![2.0, 2.0]));
+}
+
+#[test]
+fn test_binary_cross_entropy_with_logits() {
+    let logits = collective(vec![-2.0, 0.0, 3.0, 800.0, -800.0], vec![1.0, 5.0]);
+    let targets = collective(vec![0.0, 1.0, 1.0, 0.0, 0.0], vec![1.0, 5.0]);
+
+    let loss = losses::binary_cross_entropy_with_logits(&logits, &targets, Reduction::None);
+    let sigmoid = |x: f64| 1.0 / (1.0 + (-x).exp());
+    assert_allclose!(
+        collective(loss.value.iter().take(3).copied().collect(), vec![1.0, 3.0]),
+        collective(vec![-(1.0 - sigmoid(-2.0)).ln(), -(0.5f64).ln(), -sigmoid(3.0).ln()], vec![1.0, 3.0])
+    );
+    assert_eq!(loss.value[3], 800.0, "A confident wrong prediction should not overflow");
+    assert_eq!(loss.value[4], 0.0, "A confident right prediction should have no loss");
+
+    check_gradient(&collective(vec![-2.0, 0.0, 3.0], vec![1.0, 3.0]), |x| {
+        losses::binary_cross_entropy_with_logits(x, &collective(vec![0.0, 1.0, 0.3], vec![1.0, 3.0]), Reduction::Mean)
+    });
+}
+
+#[test]
+fn test_regression_losses() {
+    let input = collective(vec![1.0f64, 2.0, 5.0, -1.0], vec![2.0, 2.0]);
+    let target = collective(vec![1.5, 2.0, 2.0, 0.0], vec![2.0, 2.0]);
+
+    assert!((losses::mse(&input, &target, Reduction::Mean).item() - (0.25 + 0.0 + 9.0 + 1.0) / 4.0).abs() < 1e-12, "MSE");
+    assert!((losses::mae(&input, &target, Reduction::Sum).item() - 4.5).abs() < 1e-12, "MAE");
+
+    let huber = losses::huber(&input, &target, 1.0, Reduction::None);
+    assert_allclose!(huber.value, collective(vec![0.125, 0.0, 2.5, 0.5], vec![2.0, 2.0]));
+    assert_allclose!(huber.gradient, collective(vec![-0.5, 0.0, 1.0, -1.0], vec![2.0, 2.0]));
+
+    let mae = losses::mae(&input, &target, Reduction::None);
+    assert_eq!(mae.gradient[1], 0.0, "MAE gradient at the target should be 0");
+
+    let moved = collective(vec![1.2, 2.3, 5.0, -1.4], vec![2.0, 2.0]);
+    check_gradient(&moved, |x| losses::mse(x, &target, Reduction::Mean));
+    check_gradient(&moved, |x| losses::mae(x, &target, Reduction::Sum));
+    check_gradient(&moved, |x| losses::huber(x, &target, 0.5, Reduction::Mean));
+}
+
+#[test]
+fn test_kl_div() {
+    let target = collective(vec![0.5, 0.5, 0.0, 0.25, 0.25, 0.5], vec![2.0, 3.0]);
+    let input = collective(vec![0.2f64, 0.3, 0.5, 0.1, 0.1, 0.8], vec![2.0, 3.0]).ln();
+
+    let loss = losses::kl_div(&input, &target, Reduction::Sum);
+    let expected = 0.5 * (0.5f64 / 0.2).ln() + 0.5 * (0.5f64 / 0.3).ln() + 2.0 * 0.25 * (0.25f64 / 0.1).ln() + 0.5 * (0.5f64 / 0.8).ln();
+    assert!((loss.item() - expected).abs() < 1e-12, "KL divergence should be {}, got {}", expected, loss.item());
+
+    let same = losses::kl_div(&target.map(|p| if p > 0.0 { p.ln() } else { f64::NEG_INFINITY }), &target, Reduction::Mean);
+    assert_eq!(same.item(), 0.0, "KL divergence of a distribution from itself should be 0");
+
+    check_gradient(&input, |x| losses::kl_div(x, &target, Reduction::Mean));
+}
+
+#[test]
+#[should_panic(expected = "losses::mse(): Shapes [2, 2] and [1, 4] differ")]
+fn test_shape_mismatch_panics() {
+    losses::mse(&collective(vec![1.0; 4], vec![2.0, 2.0]), &collective(vec![1.0; 4], vec![1.0, 4.0]), Reduction::Mean);
+}
+
+#[test]
+#[should_panic(expected = "only a reduced loss has a single value")]
+fn test_item_of_unreduced_loss_panics() {
+    losses::mse(&collective(vec![1.0; 4], vec![2.0, 2.0]), &collective(vec![0.0; 4], vec![2.0, 2.0]), Reduction::None).item();
+}
+
+#[test]
+fn test_f32_matches_f64() {
+    let input = collective(vec![0.3f64, -1.2, 2.5, 0.0], vec![2.0, 2.0]);
+    let target = collective(vec![0.0f64, 1.0, 1.0, 0.5], vec![2.0, 2.0]);
+    let options = CloseOptions::new().with_rtol(1e-6);
+
+    let loss64 = losses::binary_cross_entropy_with_logits(&input, &target, Reduction::Mean);
+    let loss32 = losses::binary_cross_entropy_with_logits(&input.cast::<f32>(), &target.cast::<f32>(), Reduction::Mean);
+
+    assert_allclose!(loss32.value, loss64.value.cast::<f32>(), options);
+    assert_allclose!(loss32.gradient, loss64.gradient.cast::<f32>(), options);
+}
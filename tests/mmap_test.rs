@@ -0,0 +1,166 @@
+/*
+ * numrs/tests/mmap_test.rs
+ * Tests for memory-mapped Collectives in mmap.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test mmap_test -- --nocapture
+*/
+
+mod common;
+
+use common::{npy_bytes, temp_path};
+use numrs::collective::Collective;
+use numrs::dimensions::Dimensions;
+use numrs::mmap::MappedCollective;
+use numrs::npy::{ByteOrder, NpyOptions};
+
+// 5 samples of 2 x 3
+fn samples() -> Collective<f32> {
+    Collective::new(
+        Some((0..30).map(|i| i as f32).collect::<Vec<f32>>().into_boxed_slice()),
+        Some(Box::new(Dimensions::from_vec(vec![5.0, 2.0, 3.0]))),
+    )
+}
+
+#[test]
+fn test_map_npy_file() {
+    let path = temp_path("samples.npy");
+    samples().save_npy(&path).unwrap();
+
+    let mapped = MappedCollective::<f32>::open_npy(&path).unwrap();
+
+    assert_eq!(mapped.len(), 5, "Leading axis should be the number of samples");
+    assert_eq!(mapped.get_n(), 30, "All elements should be mapped");
+    assert_eq!(mapped.shape().to_vec(), vec![5.0, 2.0, 3.0], "Shape should come from the header");
+    assert_eq!(mapped[7], 7.0, "Indexing should read the mapped data");
+    assert_eq!(mapped.to_collective().data, samples().data, "Copying should give the saved data");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_batches_of_leading_axis() {
+    let path = temp_path("batches.npy");
+    samples().save_npy(&path).unwrap();
+
+    let mapped = MappedCollective::<f32>::open_npy(&path).unwrap();
+    let batch = mapped.batch(3, 5);
+
+    assert_eq!(batch.shape.as_ref().unwrap().to_vec(), vec![2.0, 2.0, 3.0], "Batch should keep the inner extents");
+    assert_eq!(
+        &batch.data.unwrap()[..],
+        &(18..30).map(|i| i as f32).collect::<Vec<f32>>()[..],
+        "Batch should hold samples 3 and 4"
+    );
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_batches_of_one_dimensional_file() {
+    // Labels of shape (10,) as numpy.save writes them, they load as 1 x 10
+    let path = temp_path("labels.npy");
+    let labels: Vec<u8> = (0..10).collect();
+    std::fs::write(&path, npy_bytes("{'descr': '|u1', 'fortran_order': False, 'shape': (10,), }", &labels)).unwrap();
+
+    let mapped = MappedCollective::<u8>::open_npy(&path).unwrap();
+    assert_eq!(mapped.shape().to_vec(), vec![1.0, 10.0], "A 1-D file should load as one row");
+    assert_eq!(mapped.len(), 10, "Every label should be an entry of the leading axis");
+
+    let batch = mapped.batch(4, 7);
+    assert_eq!(batch.shape.as_ref().unwrap().to_vec(), vec![1.0, 3.0], "Batch should stay one-dimensional");
+    assert_eq!(&batch.data.unwrap()[..], &[4, 5, 6], "Batch should hold labels 4 to 6");
+
+    // A 2-D file of a single row keeps its leading axis of one
+    let row = Collective::new(samples().data, Some(Box::new(Dimensions::new(30.0, 1.0))));
+    row.save_npy(&path).unwrap();
+    assert_eq!(MappedCollective::<f32>::open_npy(&path).unwrap().len(), 1, "A (1, n) file has one entry");
+
+    // Raw files can only be given as [1, n]
+    std::fs::write(&path, &labels).unwrap();
+    let mapped = MappedCollective::<u8>::open_raw(&path, Dimensions::new(10.0, 1.0), 0).unwrap();
+    assert_eq!(mapped.len(), 10, "A raw [1, n] shape should be n entries");
+    assert_eq!(&mapped.batch(8, 10).data.unwrap()[..], &[8, 9], "Batch should hold the last two labels");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_map_raw_file_with_offset() {
+    let path = temp_path("raw.bin");
+
+    let values = Collective::new(
+        Some(vec![1i64, -2, 3, -4, 5, -6].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(2.0, 3.0))),
+    );
+    values.save_raw(&path).unwrap();
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 48, "Raw file should hold nothing but the elements");
+
+    let mapped = MappedCollective::<i64>::open_raw(&path, Dimensions::new(2.0, 3.0), 0).unwrap();
+    assert_eq!(mapped.as_slice(), &values.data.as_ref().unwrap()[..], "Raw data should map in place");
+
+    // Skip the first row, as if it were a 16 byte header
+    let mapped = MappedCollective::<i64>::open_raw(&path, Dimensions::new(2.0, 2.0), 16).unwrap();
+    assert_eq!(mapped.as_slice(), &[3, -4, 5, -6], "Offset should skip leading bytes");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_raw_file_errors() {
+    let path = temp_path("short.bin");
+    std::fs::write(&path, [0u8; 20]).unwrap();
+
+    let error = MappedCollective::<f32>::open_raw(&path, Dimensions::new(3.0, 2.0), 0).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "A file too short for the shape should be rejected");
+
+    // A shape whose size in bytes overflows a usize
+    let huge = Dimensions::from_vec(vec![4294967296.0, 4294967296.0]);
+    let error = MappedCollective::<f32>::open_raw(&path, huge, 0).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "A shape too large for any file should be rejected");
+
+    let error = MappedCollective::<f32>::open_raw(&path, Dimensions::new(2.0, 1.0), 2).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput, "A misaligned offset should be rejected");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_npy_files_that_cannot_be_mapped() {
+    let path = temp_path("fortran.npy");
+
+    samples()
+        .save_npy_with(&path, NpyOptions::new().with_fortran_order(true))
+        .unwrap();
+    let error = MappedCollective::<f32>::open_npy(&path).err().unwrap();
+    assert!(error.to_string().contains("Fortran"), "Fortran order should be rejected, got {}", error);
+
+    let swapped = match ByteOrder::NATIVE {
+        ByteOrder::LittleEndian => ByteOrder::BigEndian,
+        ByteOrder::BigEndian => ByteOrder::LittleEndian,
+    };
+    samples()
+        .save_npy_with(&path, NpyOptions::new().with_byte_order(swapped))
+        .unwrap();
+    let error = MappedCollective::<f32>::open_npy(&path).err().unwrap();
+    assert!(error.to_string().contains("byte order"), "Byte swapped data should be rejected, got {}", error);
+
+    let error = MappedCollective::<f64>::open_npy(&path).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "A different dtype should be rejected");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn test_batch_out_of_bounds_panics() {
+    let path = temp_path("bounds.npy");
+    samples().save_npy(&path).unwrap();
+
+    let mapped = MappedCollective::<f32>::open_npy(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    mapped.batch(4, 6);
+}
@@ -0,0 +1,203 @@
+/*
+ * numrs/tests/nn_test.rs
+ * Tests for the layers, Sequential and nn::step() in nn.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test nn_test -- --nocapture
+*/
+
+mod common;
+
+use common::{collective, extents, values};
+use numrs::activations::Activation;
+use numrs::assert_allclose;
+use numrs::autograd::Variable;
+use numrs::collective::Collective;
+use numrs::losses::{self, Reduction};
+use numrs::nn::{self, ActivationLayer, Dropout, Embedding, Layer, LayerNorm, Linear, Sequential};
+use numrs::optim::Adam;
+
+fn input() -> Collective<f64> {
+    collective(vec![0.5, -1.2, 2.0, 0.3, -0.7, 1.1], vec![2.0, 3.0])
+}
+
+fn upstream() -> Collective<f64> {
+    collective(vec![0.4, -0.9, 1.3, 0.2, 0.7, -0.5], vec![2.0, 3.0])
+}
+
+// sum(forward(x) * upstream), the loss whose gradient backward() is given
+fn loss(layer: &mut impl Layer<f64>, x: &Collective<f64>, upstream: &Collective<f64>) -> f64 {
+    layer.forward(x).iter().zip(upstream.iter()).map(|(y, g)| y * g).sum()
+}
+
+#[test]
+fn test_linear_matches_autograd() {
+    let mut linear = Linear::<f64>::new(3, 2);
+    linear.bias = Some(collective(vec![0.1, -0.3], vec![1.0, 2.0]));
+
+    let output = linear.forward(&input());
+    assert_eq!(extents(&output), vec![2.0, 2.0]);
+
+    let upstream = collective(vec![1.0, -2.0, 0.5, 3.0], vec![2.0, 2.0]);
+    let input_gradient = linear.backward(&upstream);
+
+    let x = Variable::new(input());
+    let w = Variable::new(linear.weight.clone());
+    let b = Variable::new(linear.bias.clone().unwrap());
+    let y = &x.matmul(&w) + &b;
+    y.backward_with(&upstream);
+
+    assert_allclose!(output, *y.value());
+    assert_allclose!(input_gradient, x.grad().unwrap());
+
+    let parameters = linear.parameters();
+    assert_eq!(parameters.len(), 2);
+    assert_allclose!(*parameters[0].gradient, w.grad().unwrap());
+    assert_allclose!(*parameters[1].gradient, b.grad().unwrap());
+
+    // Weights start in [-1/sqrt(in), 1/sqrt(in)) and have no bias without one
+    let linear = Linear::<f32>::new(16, 8).with_bias(false);
+    assert!(linear.bias.is_none());
+    assert!(linear.weight.iter().all(|w| w.abs() <= 0.25));
+}
+
+#[test]
+fn test_layer_norm_forward_and_gradients() {
+    let mut norm = LayerNorm::<f64>::new(3);
+    norm.gain = collective(vec![1.5, 0.5, -1.0], vec![1.0, 3.0]);
+    norm.bias = collective(vec![0.2, 0.0, -0.1], vec![1.0, 3.0]);
+
+    let mut plain = LayerNorm::<f64>::new(3);
+    let normalized = plain.forward(&input());
+    for row in values(&normalized).chunks(3) {
+        let mean = row.iter().sum::<f64>() / 3.0;
+        let variance = row.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / 3.0;
+
+        assert!(mean.abs() < 1e-12);
+        assert!((variance - 1.0).abs() < 1e-4);
+    }
+
+    norm.forward(&input());
+    let input_gradient = values(&norm.backward(&upstream()));
+
+    // Central differences of the input
+    let epsilon = 1e-6;
+    for (index, &analytic) in input_gradient.iter().enumerate() {
+        let (mut plus, mut minus) = (input(), input());
+        plus[index] += epsilon;
+        minus[index] -= epsilon;
+
+        let numerical = (loss(&mut norm, &plus, &upstream()) - loss(&mut norm, &minus, &upstream())) / (2.0 * epsilon);
+        assert!((numerical - analytic).abs() < 1e-6, "{}: {} != {}", index, numerical, analytic);
+    }
+
+    // The gain gradient is sum(upstream * x_hat), the bias gradient sum(upstream) over the rows
+    let x_hat = values(&normalized);
+    let g = values(&upstream());
+    let parameters = norm.parameters();
+    for column in 0..3 {
+        let gain = g[column] * x_hat[column] + g[column + 3] * x_hat[column + 3];
+        assert!((parameters[0].gradient[column] - gain).abs() < 1e-9);
+        assert!((parameters[1].gradient[column] - (g[column] + g[column + 3])).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_embedding_lookup_and_gradient() {
+    let mut embedding = Embedding::<f64>::new(4, 2);
+    embedding.weight = collective(vec![0.0, 0.1, 1.0, 1.1, 2.0, 2.1, 3.0, 3.1], vec![4.0, 2.0]);
+
+    let output = embedding.lookup(&collective(vec![2, 0, 2], vec![1.0, 3.0]));
+    assert_eq!(extents(&output), vec![1.0, 3.0, 2.0]);
+    assert_eq!(values(&output), vec![2.0, 2.1, 0.0, 0.1, 2.0, 2.1]);
+
+    // Index 2 was looked up twice, its gradients add up
+    let gradient = embedding.backward(&collective(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], vec![1.0, 3.0, 2.0]));
+    assert_eq!(values(&gradient), vec![0.0; 3]);
+    assert_eq!(values(embedding.parameters()[0].gradient), vec![3.0, 4.0, 0.0, 0.0, 6.0, 8.0, 0.0, 0.0]);
+
+    // As a layer the indices are whole numbers of the element type
+    let output = embedding.forward(&collective(vec![3.0, 1.0], vec![2.0, 1.0]));
+    assert_eq!(extents(&output), vec![2.0, 1.0, 2.0]);
+    assert_eq!(values(&output), vec![3.0, 3.1, 1.0, 1.1]);
+
+    embedding.zero_grad();
+    assert_eq!(values(embedding.parameters()[0].gradient), vec![0.0; 8]);
+}
+
+#[test]
+#[should_panic(expected = "Embedding::lookup(): Index 4 is out of range for 4 embeddings")]
+fn test_embedding_index_out_of_range() {
+    Embedding::<f32>::new(4, 2).lookup(&collective(vec![1, 4], vec![1.0, 2.0]));
+}
+
+#[test]
+fn test_dropout_training_and_evaluation() {
+    let x = collective(vec![1.0f64; 10000], vec![100.0, 100.0]);
+    let mut dropout = Dropout::new(0.25).with_seed(7);
+
+    let output = dropout.forward(&x);
+    let kept = output.iter().filter(|&&y| y != 0.0).count();
+    assert!((7200..7800).contains(&kept), "kept {}", kept);
+    assert!(output.iter().all(|&y| y == 0.0 || (y - 1.0 / 0.75).abs() < 1e-12));
+
+    // The gradient goes through the same elements with the same scale
+    assert_eq!(values(&Layer::<f64>::backward(&mut dropout, &x)), values(&output));
+
+    Layer::<f64>::set_training(&mut dropout, false);
+    assert_eq!(values(&dropout.forward(&x)), values(&x));
+    assert_eq!(values(&Layer::<f64>::backward(&mut dropout, &x)), values(&x));
+}
+
+#[test]
+fn test_sequential_gradients_and_training() {
+    let mut model = Sequential::<f64>::new()
+        .with(Linear::new(3, 4))
+        .with(LayerNorm::new(4))
+        .with(ActivationLayer::new(Activation::Tanh))
+        .with(Linear::new(4, 3));
+
+    assert_eq!(model.parameters().len(), 6);
+
+    model.forward(&input());
+    let input_gradient = values(&model.backward(&upstream()));
+
+    let epsilon = 1e-6;
+    for (index, &analytic) in input_gradient.iter().enumerate() {
+        let (mut plus, mut minus) = (input(), input());
+        plus[index] += epsilon;
+        minus[index] -= epsilon;
+
+        let numerical = (loss(&mut model, &plus, &upstream()) - loss(&mut model, &minus, &upstream())) / (2.0 * epsilon);
+        assert!((numerical - analytic).abs() < 1e-6, "{}: {} != {}", index, numerical, analytic);
+    }
+
+    // Fit y = x1 - 2 x2 + 0.5
+    let x = collective(
+        vec![0.1, 0.5, -0.3, 0.8, 0.9, -0.7, -0.5, 0.2, 0.4, -0.9, -0.6, 0.3, 0.7, 0.0, -0.2, -0.4],
+        vec![8.0, 2.0],
+    );
+    let targets: Vec<f64> = values(&x).chunks(2).map(|x| x[0] - 2.0 * x[1] + 0.5).collect();
+    let target = collective(targets, vec![8.0, 1.0]);
+
+    let mut model = Sequential::<f64>::new()
+        .with(Linear::new(2, 8))
+        .with(ActivationLayer::new(Activation::Tanh))
+        .with(Linear::new(8, 1));
+    let mut optimizer = Adam::new(0.05);
+
+    let mut losses = Vec::new();
+    for _ in 0..300 {
+        let loss = losses::mse(&model.forward(&x), &target, Reduction::Mean);
+
+        model.zero_grad();
+        model.backward(&loss.gradient);
+        nn::step(&mut model, &mut optimizer);
+
+        losses.push(loss.item());
+    }
+
+    assert!(losses[299] < losses[0] * 0.05, "{} -> {}", losses[0], losses[299]);
+}
@@ -0,0 +1,214 @@
+/*
+ * numrs/tests/npy_test.rs
+ * Tests for reading and writing NumPy .npy files in npy.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test npy_test -- --nocapture
+*/
+
+mod common;
+
+use common::{npy_bytes, temp_path};
+use numrs::collective::Collective;
+use numrs::dimensions::Dimensions;
+use numrs::npy::{self, ByteOrder, NpyOptions};
+
+// 2 rows x 3 columns
+fn matrix() -> Collective<f64> {
+    Collective::new(
+        Some(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(3.0, 2.0))),
+    )
+}
+
+#[test]
+fn test_save_and_load_round_trip() {
+    let path = temp_path("round_trip.npy");
+
+    matrix().save_npy(&path).unwrap();
+    let loaded = Collective::<f64>::load_npy(&path).unwrap();
+
+    assert_eq!(loaded.data, matrix().data, "Data should round-trip exactly");
+    assert_eq!(loaded.shape.unwrap().to_vec(), vec![2.0, 3.0], "Shape should round-trip");
+
+    let header = npy::read_header(&path).unwrap();
+    assert_eq!(header.version, (1, 0), "Small headers should be written as version 1.0");
+    assert_eq!(header.descr, "<f8", "f64 should be saved as little endian f8");
+    assert!(!header.fortran_order, "Default order should be C");
+    assert_eq!(header.shape, vec![2, 3], "Header shape should be (rows, columns)");
+
+    let length = std::fs::metadata(&path).unwrap().len();
+    assert!((length - 48).is_multiple_of(64), "Data should start on a 64 byte boundary");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_header_layout_matches_numpy() {
+    let mut buffer = Vec::new();
+    matrix().write_npy(&mut buffer, NpyOptions::new()).unwrap();
+
+    assert_eq!(&buffer[..8], b"\x93NUMPY\x01\x00", "Magic string and version");
+
+    let header_len = u16::from_le_bytes([buffer[8], buffer[9]]) as usize;
+    let header = std::str::from_utf8(&buffer[10..10 + header_len]).unwrap();
+
+    assert!(
+        header.starts_with("{'descr': '<f8', 'fortran_order': False, 'shape': (2, 3), }"),
+        "Header should be the dict numpy.save writes, got {}",
+        header
+    );
+    assert!(header.ends_with('\n'), "Header should end with a newline");
+    assert!((10 + header_len).is_multiple_of(64), "Header should be padded to 64 bytes");
+}
+
+#[test]
+fn test_big_endian_fortran_order_round_trip() {
+    let source = Collective::new(
+        Some((0..24).collect::<Vec<i32>>().into_boxed_slice()),
+        Some(Box::new(Dimensions::from_vec(vec![2.0, 3.0, 4.0]))),
+    );
+
+    let options = NpyOptions::new()
+        .with_byte_order(ByteOrder::BigEndian)
+        .with_fortran_order(true)
+        .with_version(2, 0);
+
+    let mut buffer = Vec::new();
+    source.write_npy(&mut buffer, options).unwrap();
+
+    let header = npy::read_header_from(&mut &buffer[..]).unwrap();
+    assert_eq!(header.version, (2, 0), "Requested version should be written");
+    assert_eq!(header.descr, ">i4", "i32 should be saved as big endian i4");
+    assert!(header.fortran_order, "Fortran order should be recorded");
+
+    // In Fortran order the second element is index (1, 0, 0), which is 12 in C order
+    let second = i32::from_be_bytes(buffer[buffer.len() - 92..buffer.len() - 88].try_into().unwrap());
+    assert_eq!(second, 12, "Data should be written column-major");
+
+    let loaded = Collective::<i32>::read_npy(&mut &buffer[..]).unwrap();
+    assert_eq!(loaded.data, source.data, "Loading should restore C order");
+    assert_eq!(loaded.shape.unwrap().to_vec(), vec![2.0, 3.0, 4.0], "3-D shape should round-trip");
+}
+
+#[test]
+fn test_load_fortran_ordered_file_from_numpy() {
+    // np.asfortranarray(np.array([[1, 2, 3], [4, 5, 6]], dtype='<u2'))
+    let data: Vec<u8> = [1u16, 4, 2, 5, 3, 6].iter().flat_map(|v| v.to_le_bytes()).collect();
+    let bytes = npy_bytes("{'descr': '<u2', 'fortran_order': True, 'shape': (2, 3), }", &data);
+
+    let loaded = Collective::<u16>::read_npy(&mut &bytes[..]).unwrap();
+
+    assert_eq!(&loaded.data.unwrap()[..], &[1, 2, 3, 4, 5, 6], "Fortran data should be converted to C order");
+}
+
+#[test]
+fn test_load_one_and_zero_dimensional_arrays() {
+    let vector = npy_bytes("{'descr': '|u1', 'fortran_order': False, 'shape': (4,), }", &[7, 8, 9, 10]);
+    let loaded = Collective::<u8>::read_npy(&mut &vector[..]).unwrap();
+
+    let shape = loaded.shape.unwrap();
+    assert_eq!(shape.rows(), 1.0, "A 1-D array should load as a single row");
+    assert_eq!(shape.columns(), 4.0, "A 1-D array should load as n columns");
+
+    let scalar = npy_bytes("{'descr': '<f4', 'fortran_order': False, 'shape': (), }", &2.5f32.to_le_bytes());
+    let loaded = Collective::<f32>::read_npy(&mut &scalar[..]).unwrap();
+
+    assert_eq!(loaded[0], 2.5, "A 0-D array should load its single element");
+    assert_eq!(loaded.shape.unwrap().get_n(), 1, "A 0-D array should load as 1 x 1");
+}
+
+#[test]
+fn test_load_version_3_header() {
+    let mut bytes = npy_bytes("{'descr': '<i8', 'fortran_order': False, 'shape': (1, 2), }", &[]);
+    let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as u32;
+
+    // Version 3.0 has a u32 header length, which shifts the header by two bytes
+    let mut v3 = b"\x93NUMPY\x03\x00".to_vec();
+    v3.extend_from_slice(&header_len.to_le_bytes());
+    v3.extend_from_slice(&bytes.split_off(10));
+    v3.extend((-1i64).to_le_bytes());
+    v3.extend(i64::MAX.to_le_bytes());
+
+    let loaded = Collective::<i64>::read_npy(&mut &v3[..]).unwrap();
+
+    assert_eq!(&loaded.data.unwrap()[..], &[-1, i64::MAX], "Version 3.0 files should load");
+}
+
+#[test]
+fn test_dtype_mismatch_is_an_error() {
+    let mut buffer = Vec::new();
+    matrix().write_npy(&mut buffer, NpyOptions::new()).unwrap();
+
+    let error = Collective::<f32>::read_npy(&mut &buffer[..]).err().unwrap();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "Loading f8 data as f32 should fail");
+    assert!(error.to_string().contains("<f8"), "Error should name the dtype of the file");
+}
+
+#[test]
+fn test_invalid_files_are_errors() {
+    let not_npy = b"PK\x03\x04 not an npy file".to_vec();
+    assert_eq!(
+        Collective::<f64>::read_npy(&mut &not_npy[..]).err().unwrap().kind(),
+        std::io::ErrorKind::InvalidData,
+        "A missing magic string should be reported as invalid data"
+    );
+
+    let empty_axis = npy_bytes("{'descr': '<f8', 'fortran_order': False, 'shape': (0, 3), }", &[]);
+    assert_eq!(
+        Collective::<f64>::read_npy(&mut &empty_axis[..]).err().unwrap().kind(),
+        std::io::ErrorKind::InvalidData,
+        "Zero length axes cannot be represented by Dimensions"
+    );
+
+    let truncated = npy_bytes("{'descr': '<f8', 'fortran_order': False, 'shape': (2, 3), }", &[0; 16]);
+    assert_eq!(
+        Collective::<f64>::read_npy(&mut &truncated[..]).err().unwrap().kind(),
+        std::io::ErrorKind::UnexpectedEof,
+        "Missing data should be reported"
+    );
+
+    // 2^53 bytes of data claimed by a header followed by none, nothing that size may be allocated
+    let huge = npy_bytes("{'descr': '<f8', 'fortran_order': False, 'shape': (1048576, 1048576, 1024), }", &[0; 16]);
+    assert_eq!(
+        Collective::<f64>::read_npy(&mut &huge[..]).err().unwrap().kind(),
+        std::io::ErrorKind::UnexpectedEof,
+        "A header claiming more data than the file has should be reported"
+    );
+
+    let overflowing = npy_bytes("{'descr': '<f8', 'fortran_order': False, 'shape': (4294967296, 4294967296), }", &[]);
+    assert_eq!(
+        Collective::<f64>::read_npy(&mut &overflowing[..]).err().unwrap().kind(),
+        std::io::ErrorKind::InvalidData,
+        "A shape whose size overflows should be invalid data"
+    );
+}
+
+#[test]
+fn test_unallocated_collective_cannot_be_saved() {
+    let empty: Collective<f64> = Collective::new(None, Some(Box::new(Dimensions::new(2.0, 2.0))));
+
+    let error = empty.write_npy(&mut Vec::new(), NpyOptions::new()).unwrap_err();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput, "Unallocated data should not be saved");
+}
+
+#[cfg(feature = "f16")]
+#[test]
+fn test_f16_round_trip() {
+    use numrs::f16;
+
+    let source = Collective::new(
+        Some(vec![f16::from_f32(0.5), f16::from_f32(-2.0)].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(2.0, 1.0))),
+    );
+
+    let mut buffer = Vec::new();
+    source.write_npy(&mut buffer, NpyOptions::new()).unwrap();
+
+    assert_eq!(npy::read_header_from(&mut &buffer[..]).unwrap().descr, "<f2", "f16 should be saved as f2");
+    assert_eq!(Collective::<f16>::read_npy(&mut &buffer[..]).unwrap().data, source.data, "f16 should round-trip");
+}
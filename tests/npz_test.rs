@@ -0,0 +1,145 @@
+/*
+ * numrs/tests/npz_test.rs
+ * Tests for reading and writing NumPy .npz archives in npz.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test npz_test -- --nocapture
+*/
+
+mod common;
+
+use common::temp_path;
+use numrs::collective::Collective;
+use numrs::dimensions::Dimensions;
+use numrs::npz::{self, NpzArchive, NpzWriter};
+use std::collections::BTreeMap;
+use std::io::Cursor;
+
+fn checkpoint() -> BTreeMap<String, Collective<f32>> {
+    let mut collectives = BTreeMap::new();
+
+    collectives.insert(
+        "dense1_weights".to_string(),
+        Collective::new(
+            Some((0..12).map(|i| i as f32 * 0.5).collect::<Vec<f32>>().into_boxed_slice()),
+            Some(Box::new(Dimensions::new(4.0, 3.0))),
+        ),
+    );
+    collectives.insert(
+        "dense1_bias".to_string(),
+        Collective::new(
+            Some(vec![0.0f32; 4].into_boxed_slice()),
+            Some(Box::new(Dimensions::new(4.0, 1.0))),
+        ),
+    );
+
+    collectives
+}
+
+#[test]
+fn test_save_and_load_stored_archive() {
+    let path = temp_path("stored.npz");
+
+    npz::save_npz(&path, &checkpoint()).unwrap();
+    let loaded = npz::load_npz::<f32, _>(&path).unwrap();
+
+    assert_eq!(loaded.len(), 2, "Both arrays should be loaded");
+    for (name, collective) in checkpoint() {
+        assert_eq!(loaded[&name].data, collective.data, "{} should round-trip", name);
+        assert_eq!(
+            loaded[&name].shape.as_ref().unwrap().to_vec(),
+            collective.shape.unwrap().to_vec(),
+            "{} shape should round-trip",
+            name
+        );
+    }
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_compressed_archive_is_smaller() {
+    let stored_path = temp_path("uncompressed.npz");
+    let compressed_path = temp_path("compressed.npz");
+
+    let mut collectives = BTreeMap::new();
+    collectives.insert(
+        "zeros".to_string(),
+        Collective::new(
+            Some(vec![0.0f64; 10_000].into_boxed_slice()),
+            Some(Box::new(Dimensions::new(100.0, 100.0))),
+        ),
+    );
+
+    npz::save_npz(&stored_path, &collectives).unwrap();
+    npz::save_npz_compressed(&compressed_path, &collectives).unwrap();
+
+    let stored = std::fs::metadata(&stored_path).unwrap().len();
+    let compressed = std::fs::metadata(&compressed_path).unwrap().len();
+    assert!(compressed < stored / 10, "Zeros should deflate well, {} vs {} bytes", compressed, stored);
+
+    let loaded: Collective<f64> = NpzArchive::open(&compressed_path).unwrap().load("zeros").unwrap();
+    assert_eq!(loaded.data, collectives["zeros"].data, "Compressed entries should load");
+
+    std::fs::remove_file(&stored_path).unwrap();
+    std::fs::remove_file(&compressed_path).unwrap();
+}
+
+#[test]
+fn test_lazy_loading_of_mixed_element_types() {
+    let labels = Collective::new(
+        Some(vec![3u8, 1, 4, 1, 5].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(5.0, 1.0))),
+    );
+
+    let mut writer = NpzWriter::new(Cursor::new(Vec::new()), true);
+    writer.add("weights", &checkpoint()["dense1_weights"]).unwrap();
+    writer.add("labels", &labels).unwrap();
+    let bytes = writer.finish().unwrap().into_inner();
+
+    let mut archive = NpzArchive::new(Cursor::new(bytes)).unwrap();
+    assert_eq!(archive.names(), vec!["weights", "labels"], "Names should be in write order without extension");
+    assert!(archive.contains("labels"), "Archive should contain labels");
+    assert!(!archive.contains("bias"), "Archive should not contain bias");
+
+    assert_eq!(archive.header("labels").unwrap().descr, "|u1", "Header should tell the element type");
+    assert_eq!(archive.load::<u8>("labels").unwrap().data, labels.data, "u8 entry should load");
+    assert_eq!(archive.header("weights").unwrap().shape, vec![3, 4], "Header should tell the shape");
+
+    let error = archive.load::<f32>("bias").err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound, "Missing entries should be NotFound");
+
+    let error = archive.load::<f64>("weights").err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "Wrong element type should be InvalidData");
+}
+
+#[test]
+fn test_entries_without_extension() {
+    // Other tools may write entries without the ".npy" extension
+    let mut bytes = Vec::new();
+    checkpoint()["dense1_bias"]
+        .write_npy(&mut bytes, numrs::npy::NpyOptions::new())
+        .unwrap();
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file("bias", zip::write::SimpleFileOptions::default()).unwrap();
+    std::io::Write::write_all(&mut zip, &bytes).unwrap();
+    let archive_bytes = zip.finish().unwrap().into_inner();
+
+    let mut archive = NpzArchive::new(Cursor::new(archive_bytes)).unwrap();
+    assert_eq!(archive.names(), vec!["bias"], "Name should be the entry name");
+    assert_eq!(
+        archive.load::<f32>("bias").unwrap().data,
+        checkpoint()["dense1_bias"].data,
+        "Entry without extension should load"
+    );
+}
+
+#[test]
+fn test_not_a_zip_archive() {
+    let result = NpzArchive::new(Cursor::new(b"\x93NUMPY not a zip".to_vec()));
+
+    assert!(result.is_err(), "A file that is not a zip archive should not open");
+}
@@ -18,7 +18,7 @@ use numrs::dimensions::Dimensions;
 use numrs::collective::Collective;
 
 // Import the traits and struct from num module
-use numrs::num::{Numrs, One, Zero};
+use numrs::num::{Bounded, Float, NumCast, One, Signed, Tensor, Zero};
 
 #[test]
 fn test_one_trait_i32() {
@@ -44,240 +44,285 @@ fn test_zero_trait_f64() {
     assert_eq!(zero_f64, 0.0, "f64::zero() should return 0.0");
 }
 
+#[test]
+fn test_identities_all_primitives() {
+    assert_eq!(u8::one(), 1u8, "u8::one() should return 1");
+    assert_eq!(u64::zero(), 0u64, "u64::zero() should return 0");
+    assert_eq!(i128::one(), 1i128, "i128::one() should return 1");
+    assert_eq!(f32::zero(), 0.0f32, "f32::zero() should return 0.0");
+    assert!(usize::zero().is_zero(), "usize::zero() should be zero");
+    assert!(!f64::one().is_zero(), "f64::one() should not be zero");
+}
+
+#[test]
+fn test_bounded_trait() {
+    assert_eq!(<u8 as Bounded>::max_value(), 255, "u8::max_value() should return 255");
+    assert_eq!(<i16 as Bounded>::min_value(), -32768, "i16::min_value() should return -32768");
+    assert_eq!(<f32 as Bounded>::max_value(), f32::MAX, "f32::max_value() should return f32::MAX");
+}
+
+#[test]
+fn test_num_cast_trait() {
+    assert_eq!(u8::from_f64(300.0), 255, "Float to integer casts should saturate");
+    assert_eq!(i32::from_f64(-2.7), -2, "Float to integer casts should truncate");
+    assert_eq!(u8::from_f64(f64::NAN), 0, "NaN should cast to zero");
+    assert_eq!(200u8.to_f64(), 200.0, "u8 should widen to f64 exactly");
+}
+
+#[test]
+fn test_signed_trait() {
+    assert_eq!((-5i32).abs(), 5, "abs() of -5 should be 5");
+    assert_eq!(Signed::signum(-3i8), -1, "signum() of a negative integer should be -1");
+    assert!(Signed::is_negative(-0.5f32), "-0.5 should be negative");
+    assert!(!Signed::is_positive(0i64), "0 should not be positive");
+}
+
+#[test]
+fn test_float_trait() {
+    assert!(<f32 as Float>::nan().is_nan(), "nan() should be NaN");
+    assert!(!<f64 as Float>::infinity().is_finite(), "infinity() should not be finite");
+    assert_eq!(Float::sqrt(16.0f32), 4.0, "sqrt(16) should be 4");
+    assert_eq!(Float::max(1.0f64, 2.0), 2.0, "max(1, 2) should be 2");
+}
+
+#[test]
+fn test_ones_with_u8_type() {
+    let collective: Collective<u8> = Tensor::ones::<u8>(Dimensions::new(4.0, 2.0));
+
+    if let Some(data) = &collective.data {
+        assert_eq!(data.len(), 8, "Data should have 8 elements");
+        for &value in data.iter() {
+            assert_eq!(value, 1, "All u8 elements should be one");
+        }
+    }
+}
+
+#[test]
+fn test_zeros_with_f32_type() {
+    let collective: Collective<f32> = Tensor::zeros::<f32>(Dimensions::new(3.0, 3.0));
+
+    if let Some(data) = &collective.data {
+        assert_eq!(data.len(), 9, "Data should have 9 elements");
+        for &value in data.iter() {
+            assert_eq!(value, 0.0, "All f32 elements should be zero");
+        }
+    }
+}
+
+#[test]
+fn test_normalize_f32() {
+    let mut collective = Collective::new(
+        Some(vec![0.0f32, 127.5, 255.0].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(3.0, 1.0))),
+    );
+
+    Tensor::normalize(&mut collective, 127.5, -1.0);
+
+    assert_eq!(collective[0], -1.0, "0 should map to -1");
+    assert_eq!(collective[1], 0.0, "127.5 should map to 0");
+    assert_eq!(collective[2], 1.0, "255 should map to 1");
+}
+
 #[test]
 fn test_zeros_single_dimension() {
-    let dim = Dimensions::new(5, 10); // 5 columns, 10 rows = 50 elements
+    let dim = Dimensions::new(5.0, 10.0); // 5 columns, 10 rows = 50 elements
     
-    let result: Option<Collective<f64>> = Numrs::zeros::<f64>(dim);
-    assert!(result.is_some(), "Numrs::zeros should return Some for valid dimensions");
+    let collective: Collective<f64> = Tensor::zeros::<f64>(dim);
     
-    if let Some(collective) = result {
-        assert!(collective.data.is_some(), "Data should be allocated");
-        assert!(collective.shape.is_some(), "Shape should be set");
-        
-        if let Some(data) = &collective.data {
-            assert_eq!(data.len(), 50, "Data should have 50 elements");
-            // Check that all elements are zero
-            for &value in data.iter() {
-                assert_eq!(value, 0.0, "All elements should be zero");
-            }
-        }
-        
-        if let Some(shape) = &collective.shape {
-            assert_eq!(shape.get_n(), 50, "Shape should indicate 50 total elements");
-            assert_eq!(shape.columns(), 5, "Shape should have 5 columns");
+    assert!(collective.data.is_some(), "Data should be allocated");
+    assert!(collective.shape.is_some(), "Shape should be set");
+    
+    if let Some(data) = &collective.data {
+        assert_eq!(data.len(), 50, "Data should have 50 elements");
+        // Check that all elements are zero
+        for &value in data.iter() {
+            assert_eq!(value, 0.0, "All elements should be zero");
         }
     }
+    
+    if let Some(shape) = &collective.shape {
+        assert_eq!(shape.get_n(), 50, "Shape should indicate 50 total elements");
+        assert_eq!(shape.columns(), 5.0, "Shape should have 5 columns");
+    }
 }
 
 #[test]
 fn test_ones_single_dimension() {
-    let dim = Dimensions::new(3, 4); // 3 columns, 4 rows = 12 elements
+    let dim = Dimensions::new(3.0, 4.0); // 3 columns, 4 rows = 12 elements
     
-    let result: Option<Collective<f64>> = Numrs::ones::<f64>(dim);
-    assert!(result.is_some(), "Numrs::ones should return Some for valid dimensions");
+    let collective: Collective<f64> = Tensor::ones::<f64>(dim);
     
-    if let Some(collective) = result {
-        assert!(collective.data.is_some(), "Data should be allocated");
-        assert!(collective.shape.is_some(), "Shape should be set");
-        
-        if let Some(data) = &collective.data {
-            assert_eq!(data.len(), 12, "Data should have 12 elements");
-            // Check that all elements are one
-            for &value in data.iter() {
-                assert_eq!(value, 1.0, "All elements should be one");
-            }
-        }
-        
-        if let Some(shape) = &collective.shape {
-            assert_eq!(shape.get_n(), 12, "Shape should indicate 12 total elements");
-            assert_eq!(shape.columns(), 3, "Shape should have 3 columns");
+    assert!(collective.data.is_some(), "Data should be allocated");
+    assert!(collective.shape.is_some(), "Shape should be set");
+    
+    if let Some(data) = &collective.data {
+        assert_eq!(data.len(), 12, "Data should have 12 elements");
+        // Check that all elements are one
+        for &value in data.iter() {
+            assert_eq!(value, 1.0, "All elements should be one");
         }
     }
+    
+    if let Some(shape) = &collective.shape {
+        assert_eq!(shape.get_n(), 12, "Shape should indicate 12 total elements");
+        assert_eq!(shape.columns(), 3.0, "Shape should have 3 columns");
+    }
 }
 
 #[test]
 fn test_zeros_multidimensional() {
     // Create a 3D structure: 2 -> 3 -> 5 (columns) = 2 * 3 * 5 = 30 elements
-    let dim3 = Dimensions::new(0, 3).with_columns(5);
-    let dim2 = Dimensions::new(0, 2).with_next(Rc::new(RefCell::new(dim3)));
-    let dim1 = Dimensions::new(0, 2).with_next(Rc::new(RefCell::new(dim2)));
+    let dim3 = Dimensions::new(0.0, 3.0).with_columns(5.0);
+    let dim2 = Dimensions::new(0.0, 2.0).with_next(Rc::new(RefCell::new(dim3)));
+    let dim1 = Dimensions::new(0.0, 2.0).with_next(Rc::new(RefCell::new(dim2)));
 
-    let result: Option<Collective<f64>> = Numrs::zeros::<f64>(dim1);
-    assert!(result.is_some(), "Numrs::zeros should return Some for valid multidimensional structure");
+    let collective: Collective<f64> = Tensor::zeros::<f64>(dim1);
 
-    if let Some(collective) = result {
-        assert!(collective.data.is_some(), "Data should be allocated");
-        assert!(collective.shape.is_some(), "Shape should be set");
-        
-        if let Some(data) = &collective.data {
-            let expected_size = 2 * 2 * 3 * 5; // Based on your dimension chain
-            assert_eq!(data.len(), expected_size, "Data should have {} elements", expected_size);
-            
-            // Check that all elements are zero
-            for &value in data.iter() {
-                assert_eq!(value, 0.0, "All elements should be zero");
-            }
-        }
+    assert!(collective.data.is_some(), "Data should be allocated");
+    assert!(collective.shape.is_some(), "Shape should be set");
+    
+    if let Some(data) = &collective.data {
+        let expected_size = 2 * 2 * 3 * 5; // Based on your dimension chain
+        assert_eq!(data.len(), expected_size, "Data should have {} elements", expected_size);
         
-        if let Some(shape) = &collective.shape {
-            assert!(shape.is_valid(), "Shape should be valid");
-            assert_eq!(shape.columns(), 5, "Shape should have 5 columns");
+        // Check that all elements are zero
+        for &value in data.iter() {
+            assert_eq!(value, 0.0, "All elements should be zero");
         }
     }
+    
+    if let Some(shape) = &collective.shape {
+        assert!(shape.is_valid(), "Shape should be valid");
+        assert_eq!(shape.columns(), 5.0, "Shape should have 5 columns");
+    }
 }
 
 #[test]
 fn test_ones_multidimensional() {
     // Create a 3D structure: 2 -> 3 -> 4 (columns) = 2 * 3 * 4 = 24 elements
-    let dim3 = Dimensions::new(0, 3).with_columns(4);
-    let dim2 = Dimensions::new(0, 2).with_next(Rc::new(RefCell::new(dim3)));
-    let dim1 = Dimensions::new(0, 2).with_next(Rc::new(RefCell::new(dim2)));
+    let dim3 = Dimensions::new(0.0, 3.0).with_columns(4.0);
+    let dim2 = Dimensions::new(0.0, 2.0).with_next(Rc::new(RefCell::new(dim3)));
+    let dim1 = Dimensions::new(0.0, 2.0).with_next(Rc::new(RefCell::new(dim2)));
 
-    let result: Option<Collective<f64>> = Numrs::ones::<f64>(dim1);
-    assert!(result.is_some(), "Numrs::ones should return Some for valid multidimensional structure");
+    let collective: Collective<f64> = Tensor::ones::<f64>(dim1);
 
-    if let Some(collective) = result {
-        assert!(collective.data.is_some(), "Data should be allocated");
-        assert!(collective.shape.is_some(), "Shape should be set");
+    assert!(collective.data.is_some(), "Data should be allocated");
+    assert!(collective.shape.is_some(), "Shape should be set");
+    
+    if let Some(data) = &collective.data {
+        let expected_size = 2 * 2 * 3 * 4; // Based on your dimension chain
+        assert_eq!(data.len(), expected_size, "Data should have {} elements", expected_size);
         
-        if let Some(data) = &collective.data {
-            let expected_size = 2 * 2 * 3 * 4; // Based on your dimension chain
-            assert_eq!(data.len(), expected_size, "Data should have {} elements", expected_size);
-            
-            // Check that all elements are one
-            for &value in data.iter() {
-                assert_eq!(value, 1.0, "All elements should be one");
-            }
+        // Check that all elements are one
+        for &value in data.iter() {
+            assert_eq!(value, 1.0, "All elements should be one");
         }
     }
 }
 
 #[test]
 fn test_zeros_with_integer_type() {
-    let dim = Dimensions::new(2, 3); // 2 columns, 3 rows = 6 elements
+    let dim = Dimensions::new(2.0, 3.0); // 2 columns, 3 rows = 6 elements
     
-    let result: Option<Collective<i32>> = Numrs::zeros::<i32>(dim);
-    assert!(result.is_some(), "Numrs::zeros should work with i32");
+    let collective: Collective<i32> = Tensor::zeros::<i32>(dim);
     
-    if let Some(collective) = result {
-        if let Some(data) = &collective.data {
-            assert_eq!(data.len(), 6, "Data should have 6 elements");
-            for &value in data.iter() {
-                assert_eq!(value, 0, "All i32 elements should be zero");
-            }
+    if let Some(data) = &collective.data {
+        assert_eq!(data.len(), 6, "Data should have 6 elements");
+        for &value in data.iter() {
+            assert_eq!(value, 0, "All i32 elements should be zero");
         }
     }
 }
 
 #[test]
 fn test_ones_with_integer_type() {
-    let dim = Dimensions::new(3, 2); // 3 columns, 2 rows = 6 elements
+    let dim = Dimensions::new(3.0, 2.0); // 3 columns, 2 rows = 6 elements
     
-    let result: Option<Collective<i32>> = Numrs::ones::<i32>(dim);
-    assert!(result.is_some(), "Numrs::ones should work with i32");
+    let collective: Collective<i32> = Tensor::ones::<i32>(dim);
     
-    if let Some(collective) = result {
-        if let Some(data) = &collective.data {
-            assert_eq!(data.len(), 6, "Data should have 6 elements");
-            for &value in data.iter() {
-                assert_eq!(value, 1, "All i32 elements should be one");
-            }
+    if let Some(data) = &collective.data {
+        assert_eq!(data.len(), 6, "Data should have 6 elements");
+        for &value in data.iter() {
+            assert_eq!(value, 1, "All i32 elements should be one");
         }
     }
 }
 
 #[test]
 fn test_zeros_empty_dimensions() {
-    let dim = Dimensions::new(0, 0); // Invalid dimensions with 0 size
+    let dim = Dimensions::new(0.0, 0.0); // Invalid dimensions with 0 size
     
-    let result: Option<Collective<f64>> = Numrs::zeros::<f64>(dim);
-    assert!(result.is_some(), "Numrs::zeros should return Some even for empty dimensions");
+    let collective: Collective<f64> = Tensor::zeros::<f64>(dim);
     
-    if let Some(collective) = result {
-        assert!(collective.data.is_none(), "Data should be None for empty dimensions");
-        assert!(collective.shape.is_some(), "Shape should still be set");
-        
-        if let Some(shape) = &collective.shape {
-            assert_eq!(shape.get_n(), 0, "Empty shape should have 0 total elements");
-        }
+    assert!(collective.data.is_none(), "Data should be None for empty dimensions");
+    assert!(collective.shape.is_some(), "Shape should still be set");
+    
+    if let Some(shape) = &collective.shape {
+        assert_eq!(shape.get_n(), 0, "Empty shape should have 0 total elements");
     }
 }
 
 #[test]
 fn test_ones_empty_dimensions() {
-    let dim = Dimensions::new(0, 0); // Invalid dimensions with 0 size
+    let dim = Dimensions::new(0.0, 0.0); // Invalid dimensions with 0 size
     
-    let result: Option<Collective<f64>> = Numrs::ones::<f64>(dim);
-    assert!(result.is_some(), "Numrs::ones should return Some even for empty dimensions");
+    let collective: Collective<f64> = Tensor::ones::<f64>(dim);
     
-    if let Some(collective) = result {
-        assert!(collective.data.is_none(), "Data should be None for empty dimensions");
-        assert!(collective.shape.is_some(), "Shape should still be set");
-        
-        if let Some(shape) = &collective.shape {
-            assert_eq!(shape.get_n(), 0, "Empty shape should have 0 total elements");
-        }
+    assert!(collective.data.is_none(), "Data should be None for empty dimensions");
+    assert!(collective.shape.is_some(), "Shape should still be set");
+    
+    if let Some(shape) = &collective.shape {
+        assert_eq!(shape.get_n(), 0, "Empty shape should have 0 total elements");
     }
 }
 
 #[test]
 fn test_zeros_large_dimensions() {
-    let dim = Dimensions::new(100, 50); // 100 columns, 50 rows = 5000 elements
-    
-    let result: Option<Collective<f64>> = Numrs::zeros::<f64>(dim);
-    assert!(result.is_some(), "Numrs::zeros should work with large dimensions");
-    
-    if let Some(collective) = result {
-        if let Some(data) = &collective.data {
-            assert_eq!(data.len(), 5000, "Large array should have 5000 elements");
-            // Spot check some elements
-            assert_eq!(data[0], 0.0, "First element should be zero");
-            assert_eq!(data[2500], 0.0, "Middle element should be zero");
-            assert_eq!(data[4999], 0.0, "Last element should be zero");
-        }
+    let dim = Dimensions::new(100.0, 50.0); // 100 columns, 50 rows = 5000 elements
+    
+    let collective: Collective<f64> = Tensor::zeros::<f64>(dim);
+    
+    if let Some(data) = &collective.data {
+        assert_eq!(data.len(), 5000, "Large array should have 5000 elements");
+        // Spot check some elements
+        assert_eq!(data[0], 0.0, "First element should be zero");
+        assert_eq!(data[2500], 0.0, "Middle element should be zero");
+        assert_eq!(data[4999], 0.0, "Last element should be zero");
     }
 }
 
 #[test]
 fn test_ones_large_dimensions() {
-    let dim = Dimensions::new(80, 25); // 80 columns, 25 rows = 2000 elements
-    
-    let result: Option<Collective<f64>> = Numrs::ones::<f64>(dim);
-    assert!(result.is_some(), "Numrs::ones should work with large dimensions");
-    
-    if let Some(collective) = result {
-        if let Some(data) = &collective.data {
-            assert_eq!(data.len(), 2000, "Large array should have 2000 elements");
-            // Spot check some elements
-            assert_eq!(data[0], 1.0, "First element should be one");
-            assert_eq!(data[1000], 1.0, "Middle element should be one");
-            assert_eq!(data[1999], 1.0, "Last element should be one");
-        }
+    let dim = Dimensions::new(80.0, 25.0); // 80 columns, 25 rows = 2000 elements
+    
+    let collective: Collective<f64> = Tensor::ones::<f64>(dim);
+    
+    if let Some(data) = &collective.data {
+        assert_eq!(data.len(), 2000, "Large array should have 2000 elements");
+        // Spot check some elements
+        assert_eq!(data[0], 1.0, "First element should be one");
+        assert_eq!(data[1000], 1.0, "Middle element should be one");
+        assert_eq!(data[1999], 1.0, "Last element should be one");
     }
 }
 
 #[test]
 fn test_consistency_between_zeros_and_ones() {
-    let dim = Dimensions::new(4, 5); // Same dimensions for both
+    let dim = Dimensions::new(4.0, 5.0); // Same dimensions for both
     
-    let zeros_result: Option<Collective<f64>> = Numrs::zeros::<f64>(dim.clone());
-    let ones_result: Option<Collective<f64>> = Numrs::ones::<f64>(dim);
+    let zeros_collective: Collective<f64> = Tensor::zeros::<f64>(dim.clone());
+    let ones_collective: Collective<f64> = Tensor::ones::<f64>(dim);
     
-    assert!(zeros_result.is_some() && ones_result.is_some(), "Both should succeed");
+    // Both should have the same structure
+    assert_eq!(
+        zeros_collective.data.as_ref().map(|d| d.len()),
+        ones_collective.data.as_ref().map(|d| d.len()),
+        "Both should have same data length"
+    );
     
-    if let (Some(zeros_collective), Some(ones_collective)) = (zeros_result, ones_result) {
-        // Both should have the same structure
-        assert_eq!(
-            zeros_collective.data.as_ref().map(|d| d.len()),
-            ones_collective.data.as_ref().map(|d| d.len()),
-            "Both should have same data length"
-        );
-        
-        if let (Some(zeros_shape), Some(ones_shape)) = (&zeros_collective.shape, &ones_collective.shape) {
-            assert_eq!(zeros_shape.get_n(), ones_shape.get_n(), "Both should have same total elements");
-            assert_eq!(zeros_shape.columns(), ones_shape.columns(), "Both should have same columns");
-        }
+    if let (Some(zeros_shape), Some(ones_shape)) = (&zeros_collective.shape, &ones_collective.shape) {
+        assert_eq!(zeros_shape.get_n(), ones_shape.get_n(), "Both should have same total elements");
+        assert_eq!(zeros_shape.columns(), ones_shape.columns(), "Both should have same columns");
     }
 }
 
@@ -285,38 +330,76 @@ fn test_consistency_between_zeros_and_ones() {
 #[test]
 fn test_memory_efficiency() {
     // Test that we're not wasting memory
-    let dim = Dimensions::new(10, 10); // 100 elements
-    
-    let result: Option<Collective<f64>> = Numrs::zeros::<f64>(dim);
-    
-    if let Some(collective) = result {
-        if let Some(data) = &collective.data {
-            // Ensure we allocated exactly the right amount
-            assert_eq!(data.len(), 100, "Should allocate exactly 100 elements");
-            
-            // Box<[T]> should be properly sized
-            let box_size = std::mem::size_of_val(&**data);
-            let expected_size = 100 * std::mem::size_of::<f64>();
-            assert_eq!(box_size, expected_size, "Memory allocation should be efficient");
-        }
+    let dim = Dimensions::new(10.0, 10.0); // 100 elements
+    
+    let collective: Collective<f64> = Tensor::zeros::<f64>(dim);
+    
+    if let Some(data) = &collective.data {
+        // Ensure we allocated exactly the right amount
+        assert_eq!(data.len(), 100, "Should allocate exactly 100 elements");
+        
+        // Box<[T]> should be properly sized
+        let box_size = std::mem::size_of_val(&**data);
+        let expected_size = 100 * std::mem::size_of::<f64>();
+        assert_eq!(box_size, expected_size, "Memory allocation should be efficient");
     }
 }
 
 #[test]
 fn test_randn() {
-    let dim = Dimensions::new(10, 10); // 100 elements
-    
-    let result: Option<Collective<f64>> = Numrs::randn::<f64>(dim);
-    
-    if let Some(collective) = result {
-        if let Some(data) = &collective.data {
-            // Ensure we allocated exactly the right amount
-            assert_eq!(data.len(), 100, "Should allocate exactly 100 elements");
-            
-            // Box<[T]> should be properly sized
-            let box_size = std::mem::size_of_val(&**data);
-            let expected_size = 100 * std::mem::size_of::<f64>();
-            assert_eq!(box_size, expected_size, "Memory allocation should be efficient");
-        }
+    let dim = Dimensions::new(10.0, 10.0); // 100 elements
+    
+    let collective: Collective<f64> = Tensor::randn::<f64>(dim);
+    
+    if let Some(data) = &collective.data {
+        // Ensure we allocated exactly the right amount
+        assert_eq!(data.len(), 100, "Should allocate exactly 100 elements");
+        
+        // Box<[T]> should be properly sized
+        let box_size = std::mem::size_of_val(&**data);
+        let expected_size = 100 * std::mem::size_of::<f64>();
+        assert_eq!(box_size, expected_size, "Memory allocation should be efficient");
     }
 }
+
+#[test]
+fn test_normalized_leaves_input_untouched() {
+    let pixels = Collective::new(
+        Some(vec![0u8, 51, 255].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(3.0, 1.0))),
+    );
+
+    let unit: Collective<f32> = Tensor::normalized(&pixels, 255.0, 0.0);
+    let centered: Collective<f64> = Tensor::normalized(&pixels, 127.5, -1.0);
+
+    assert_eq!(&unit.data.unwrap()[..], &[0.0, 0.2, 1.0], "[0, 1] range should divide by 255");
+    assert_eq!(centered[0], -1.0, "0 should map to -1");
+    assert_eq!(centered[2], 1.0, "255 should map to 1");
+    assert_eq!(&pixels.data.unwrap()[..], &[0, 51, 255], "Raw u8 values should be preserved");
+}
+
+#[test]
+fn test_denormalize_round_trip() {
+    let pixels = Collective::new(
+        Some((0..=255u8).collect::<Vec<u8>>().into_boxed_slice()),
+        Some(Box::new(Dimensions::new(16.0, 16.0))),
+    );
+
+    let centered: Collective<f32> = Tensor::normalized(&pixels, 127.5, -1.0);
+    let restored: Collective<u8> = Tensor::denormalize(&centered, 127.5, -1.0);
+
+    assert_eq!(restored.shape.as_ref().unwrap().get_n(), 256, "Shape should be preserved");
+    assert_eq!(restored.data, pixels.data, "Normalizing and denormalizing should give the original pixels back");
+}
+
+#[test]
+fn test_denormalize_rounds_and_clamps() {
+    let outputs = Collective::new(
+        Some(vec![-1.3f32, 0.5, 1.02, 0.001, f32::NAN].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(5.0, 1.0))),
+    );
+
+    let restored: Collective<u8> = Tensor::denormalize(&outputs, 255.0, 0.0);
+
+    assert_eq!(&restored.data.unwrap()[..], &[0, 128, 255, 0, 0], "Values should be rounded to nearest and clamped to [0, 255]");
+}
@@ -0,0 +1,168 @@
+/*
+ * numrs/tests/optim_test.rs
+ * Tests for the optimizers, gradient clipping and learning rate schedules in optim.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test optim_test -- --nocapture
+*/
+
+mod common;
+
+use common::collective;
+use numrs::approx::CloseOptions;
+use numrs::assert_allclose;
+use numrs::autograd::Variable;
+use numrs::collective::Collective;
+use numrs::optim::{
+    self, Adam, AdamW, CosineSchedule, LearningRate, Optimizer, RMSProp, Schedule, StepSchedule, WarmupSchedule, SGD,
+};
+
+fn row(data: Vec<f64>) -> Collective<f64> {
+    let columns = data.len() as f64;
+
+    collective(data, vec![1.0, columns])
+}
+
+// One step of `optimizer` from [1, 2] with the gradient [0.5, -1]
+fn first_step(optimizer: &mut impl Optimizer<f64>) -> Collective<f64> {
+    let mut parameter = row(vec![1.0, 2.0]);
+    optimizer.step(&mut [&mut parameter], &[&row(vec![0.5, -1.0])]);
+
+    parameter
+}
+
+/*
+   Minimizes sum((p - target)^2), whose gradient is 2 * (p - target), starting from zeros.
+*/
+fn minimize(optimizer: &mut impl Optimizer<f64>, steps: usize) -> Collective<f64> {
+    let target = row(vec![3.0, -2.0, 0.5]);
+    let mut parameter = row(vec![0.0; 3]);
+
+    for _ in 0..steps {
+        let gradient = parameter.zip_with(&target, |p, t| 2.0 * (p - t));
+        optimizer.step(&mut [&mut parameter], &[&gradient]);
+    }
+
+    assert_allclose!(parameter, target, CloseOptions::new().with_atol(0.05));
+    parameter
+}
+
+#[test]
+fn test_sgd() {
+    assert_allclose!(first_step(&mut SGD::new(0.1)), row(vec![0.95, 2.1]));
+
+    // The momentum buffer starts at the gradient and then accumulates it
+    let mut optimizer = SGD::new(0.1).with_momentum(0.9);
+    let mut parameter = row(vec![1.0, 2.0]);
+    let gradient = row(vec![0.5, -1.0]);
+
+    optimizer.step(&mut [&mut parameter], &[&gradient]);
+    optimizer.step(&mut [&mut parameter], &[&gradient]);
+    assert_allclose!(parameter, row(vec![0.855, 2.29]));
+
+    minimize(&mut SGD::new(0.1).with_momentum(0.9), 200);
+    minimize(&mut SGD::new(0.1).with_momentum(0.9).with_nesterov(true), 200);
+}
+
+#[test]
+fn test_adam_and_adamw() {
+    // The bias corrected first step moves every element by the learning rate
+    assert_allclose!(first_step(&mut Adam::new(0.01)), row(vec![0.99, 2.01]));
+
+    minimize(&mut Adam::new(0.05), 500);
+    minimize(&mut AdamW::new(0.05).with_weight_decay(0.0), 500);
+
+    // With a zero gradient, L2 weight decay passes through the normalized update, decoupled decay shrinks the weights directly
+    let zero = row(vec![0.0]);
+    let (mut l2, mut decoupled) = (row(vec![1.0]), row(vec![1.0]));
+
+    Adam::new(0.01).with_weight_decay(0.1).step(&mut [&mut l2], &[&zero]);
+    AdamW::new(0.01).with_weight_decay(0.1).step(&mut [&mut decoupled], &[&zero]);
+
+    assert_allclose!(l2, row(vec![0.99]));
+    assert_allclose!(decoupled, row(vec![0.999]));
+}
+
+#[test]
+fn test_rmsprop() {
+    // v = (1 - alpha) * g^2, so the first step is lr * g / (sqrt(0.01) * |g|) = 10 * lr in the direction of the gradient
+    assert_allclose!(first_step(&mut RMSProp::new(0.01)), row(vec![0.9, 2.1]));
+
+    minimize(&mut RMSProp::new(0.01), 1000);
+    minimize(&mut RMSProp::new(0.01).with_momentum(0.5).with_centered(true), 1000);
+}
+
+#[test]
+fn test_step_variables() {
+    let target = row(vec![3.0, -2.0]);
+    let w = Variable::new(row(vec![0.0, 0.0]));
+    let t = Variable::constant(target.clone());
+
+    let mut optimizer = SGD::new(0.1);
+    for _ in 0..100 {
+        let difference = &w - &t;
+        (&difference * &difference).sum(None).backward();
+
+        optimizer.step_variables(std::slice::from_ref(&w));
+        w.zero_grad();
+    }
+
+    assert_allclose!(*w.value(), target);
+
+    // Variables without a gradient are skipped
+    let untouched = Variable::new(row(vec![1.0]));
+    optimizer.step_variables(std::slice::from_ref(&untouched));
+    assert_allclose!(*untouched.value(), row(vec![1.0]));
+}
+
+#[test]
+#[should_panic(expected = "Adam::update(): Parameter 0 has 3 elements, its state was created for 2")]
+fn test_state_is_per_parameter() {
+    let mut optimizer = Adam::new(0.01);
+
+    first_step(&mut optimizer);
+
+    let mut other = row(vec![0.0; 3]);
+    optimizer.step(&mut [&mut other], &[&row(vec![1.0; 3])]);
+}
+
+#[test]
+fn test_gradient_clipping() {
+    let (mut a, mut b) = (row(vec![3.0, 0.0]), row(vec![0.0, -4.0]));
+
+    let norm = optim::clip_grad_norm(&mut [&mut a, &mut b], 1.0);
+    assert_eq!(norm, 5.0, "clip_grad_norm() should return the norm before clipping");
+    assert_allclose!(a, row(vec![0.6, 0.0]), CloseOptions::new().with_atol(1e-6));
+    assert_allclose!(b, row(vec![0.0, -0.8]), CloseOptions::new().with_atol(1e-6));
+
+    // Below the limit nothing changes
+    assert!(optim::clip_grad_norm(&mut [&mut a, &mut b], 2.0) < 2.0);
+    assert_allclose!(a, row(vec![0.6, 0.0]), CloseOptions::new().with_atol(1e-6));
+
+    let mut c = row(vec![-2.0, 0.3, 7.0]);
+    optim::clip_grad_value(&mut [&mut c], 0.5);
+    assert_allclose!(c, row(vec![-0.5, 0.3, 0.5]));
+}
+
+#[test]
+fn test_schedules() {
+    let step = StepSchedule::new(0.1, 10, 0.5);
+    assert_eq!([0, 9, 10, 25].map(|s| step.learning_rate(s)), [0.1, 0.1, 0.05, 0.025]);
+
+    let cosine = CosineSchedule::new(1.0, 0.0, 100);
+    assert_eq!(cosine.learning_rate(0), 1.0);
+    assert!((cosine.learning_rate(50) - 0.5).abs() < 1e-12, "Half way the learning rate should be the average");
+    assert!(cosine.learning_rate(100).abs() < 1e-12 && cosine.learning_rate(500).abs() < 1e-12);
+
+    let warmup = WarmupSchedule::new(4, cosine);
+    assert_eq!([0, 1, 3].map(|s| warmup.learning_rate(s)), [0.25, 0.5, 1.0]);
+    assert_eq!(warmup.learning_rate(54), cosine.learning_rate(50), "After the warmup the schedule should start from its step 0");
+
+    assert_eq!(WarmupSchedule::new(2, 0.5).learning_rate(0), 0.25, "An f64 should be a constant schedule");
+
+    let mut optimizer = SGD::new(0.1);
+    optimizer.set_learning_rate(step.learning_rate(10));
+    assert_eq!(optimizer.learning_rate(), 0.05);
+}
@@ -0,0 +1,183 @@
+/*
+ * numrs/tests/precision_test.rs
+ * Tests for the reduced precision element types in precision.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --all-features --test precision_test -- --nocapture
+*/
+
+#![cfg(any(feature = "f16", feature = "bf16"))]
+
+use numrs::collective::Collective;
+use numrs::dimensions::Dimensions;
+use numrs::num::{NumCast, One, Tensor, Zero};
+#[cfg(feature = "bf16")]
+use numrs::bf16;
+#[cfg(feature = "f16")]
+use numrs::f16;
+#[cfg(feature = "bf16")]
+use numrs::{Rounding, Saturation};
+
+#[cfg(feature = "f16")]
+#[test]
+fn test_f16_identities() {
+    assert_eq!(f16::one(), f16::from_f32(1.0), "f16::one() should return 1.0");
+    assert_eq!(f16::zero(), f16::from_f32(0.0), "f16::zero() should return 0.0");
+    assert!(<f16 as numrs::num::Float>::nan().is_nan(), "f16 nan() should be NaN");
+}
+
+#[cfg(feature = "f16")]
+#[test]
+fn test_f16_ones_and_zeros() {
+    let ones: Collective<f16> = Tensor::ones::<f16>(Dimensions::new(4.0, 2.0));
+    let zeros: Collective<f16> = Tensor::zeros::<f16>(Dimensions::new(4.0, 2.0));
+
+    for i in 0..8 {
+        assert_eq!(ones[i], f16::ONE, "All f16 elements should be one");
+        assert_eq!(zeros[i], f16::ZERO, "All f16 elements should be zero");
+    }
+}
+
+#[cfg(feature = "f16")]
+#[test]
+fn test_f16_normalize() {
+    let source = Collective::new(
+        Some(vec![0.0f32, 127.5, 255.0].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(3.0, 1.0))),
+    );
+    let mut collective = Collective::<f16>::from_f32(&source);
+
+    Tensor::normalize(&mut collective, f16::from_f32(127.5), f16::from_f32(-1.0));
+
+    assert_eq!(collective[0], f16::from_f32(-1.0), "0 should map to -1");
+    assert_eq!(collective[1], f16::from_f32(0.0), "127.5 should map to 0");
+    assert_eq!(collective[2], f16::from_f32(1.0), "255 should map to 1");
+}
+
+#[cfg(feature = "f16")]
+#[test]
+fn test_f16_conversions_round_trip() {
+    let source = Collective::new(
+        Some(vec![0.5f64, -2.0, 1024.0, 1.0e6].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(2.0, 2.0))),
+    );
+
+    let half = Collective::<f16>::from_f64(&source);
+    let widened = half.to_f64();
+    let single = half.to_f32();
+
+    assert_eq!(widened.shape.as_ref().unwrap().get_n(), 4, "Shape should be preserved");
+    assert_eq!(widened[0], 0.5, "0.5 is exactly representable in f16");
+    assert_eq!(widened[1], -2.0, "-2.0 is exactly representable in f16");
+    assert_eq!(single[2], 1024.0, "1024.0 is exactly representable in f16");
+    assert!(widened[3].is_infinite(), "Values beyond f16::MAX should become infinity");
+}
+
+#[cfg(feature = "f16")]
+#[test]
+fn test_f16_conversion_of_unallocated_collective() {
+    let source: Collective<f32> = Collective::new(None, Some(Box::new(Dimensions::new(2.0, 2.0))));
+
+    let half = Collective::<f16>::from_f32(&source);
+
+    assert!(half.data.is_none(), "Unallocated data should stay unallocated");
+    assert!(half.shape.is_some(), "Shape should still be set");
+}
+
+#[cfg(feature = "bf16")]
+#[test]
+fn test_bf16_identities_and_range() {
+    assert_eq!(bf16::one(), bf16::from_f32(1.0), "bf16::one() should return 1.0");
+    assert_eq!(bf16::zero(), bf16::from_f32(0.0), "bf16::zero() should return 0.0");
+    assert!(<bf16 as numrs::num::Bounded>::max_value().to_f32() > 3.0e38, "bf16 should have the range of f32");
+}
+
+#[cfg(feature = "bf16")]
+#[test]
+fn test_bf16_ones_and_normalize() {
+    let mut collective: Collective<bf16> = Tensor::ones::<bf16>(Dimensions::new(2.0, 2.0));
+
+    Tensor::normalize(&mut collective, bf16::from_f32(0.5), bf16::from_f32(-1.0));
+
+    for i in 0..4 {
+        assert_eq!(collective[i], bf16::ONE, "1 / 0.5 - 1 should be 1");
+    }
+}
+
+#[cfg(feature = "bf16")]
+#[test]
+fn test_f32_master_weights_to_bf16() {
+    // 1 + 2^-8 + 2^-9 lies between the bf16 values 1 + 2^-7 and 1, closer to 1 + 2^-7
+    let master = Collective::new(
+        Some(vec![1.0f32 + 2f32.powi(-8) + 2f32.powi(-9), f32::MIN].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(2.0, 1.0))),
+    );
+
+    let nearest = master.cast::<bf16>();
+    let truncated = master.astype::<bf16>(Rounding::Truncate, Saturation::Saturate);
+
+    assert_eq!(nearest[0].to_f32(), 1.0 + 2f32.powi(-7), "Nearest-even should round up");
+    assert_eq!(truncated[0].to_f32(), 1.0, "Truncation should round toward zero");
+    assert!(nearest[1].is_finite(), "f32::MIN is beyond bf16::MIN, saturation should keep it finite");
+
+    let widened = Collective::<bf16>::to_f32(&nearest);
+    assert_eq!(widened[0], 1.0 + 2f32.powi(-7), "bf16 to f32 should be exact");
+}
+
+#[cfg(feature = "bf16")]
+#[test]
+fn test_large_integer_to_bf16_rounds_once() {
+    // Through f64 the value would round to the bf16 tie 2^60 + 2^52, and from there down to 2^60
+    let source = Collective::new(
+        Some(vec![(1i64 << 60) + (1 << 52) + 1, i64::MAX].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(2.0, 1.0))),
+    );
+
+    let nearest = source.cast::<bf16>();
+    let truncated = source.astype::<bf16>(Rounding::Truncate, Saturation::Saturate);
+
+    assert_eq!(nearest[0].to_f64(), 2f64.powi(60) + 2f64.powi(53), "Nearest-even should round the exact integer");
+    assert_eq!(truncated[1].to_f64(), 2f64.powi(63) - 2f64.powi(55), "Truncation should not round up");
+    assert_eq!(<bf16 as NumCast>::from_i128((1i128 << 60) + (1 << 52) + 1).to_f64(), 2f64.powi(60) + 2f64.powi(53));
+}
+
+#[cfg(all(feature = "f16", feature = "bf16"))]
+#[test]
+fn test_f16_to_bf16_cast() {
+    let source = Collective::new(
+        Some(vec![f16::from_f32(0.5), f16::MAX, f16::NEG_INFINITY].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(3.0, 1.0))),
+    );
+
+    let brain = source.astype::<bf16>(Rounding::NearestEven, Saturation::Wrap);
+    let back = brain.astype::<f16>(Rounding::NearestEven, Saturation::Saturate);
+
+    assert_eq!(brain[0], bf16::from_f32(0.5), "0.5 is exact in both types");
+    assert_eq!(brain[1], bf16::from_f32(65536.0), "65504 rounds to 65536 in bf16");
+    assert!(brain[2].is_infinite(), "Wrap should keep infinity");
+    assert_eq!(back[1], f16::MAX, "Saturation should clamp 65536 to f16::MAX");
+    assert_eq!(back[2], f16::NEG_INFINITY, "Saturation should keep -inf");
+}
+
+#[cfg(all(feature = "f16", feature = "bf16"))]
+#[test]
+fn test_infinities_survive_saturating_casts() {
+    let source = Collective::new(
+        Some(vec![f32::INFINITY, f32::NEG_INFINITY, 1.0e6].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(3.0, 1.0))),
+    );
+
+    let half = source.cast::<f16>();
+    assert_eq!(half[0], f16::INFINITY, "inf should stay inf");
+    assert_eq!(half[1], f16::NEG_INFINITY, "-inf should stay -inf");
+    assert_eq!(half[2], f16::MAX, "Finite values out of range should still clamp");
+
+    let brain = source.cast::<bf16>();
+    assert_eq!(brain[0], bf16::INFINITY, "inf should stay inf");
+    assert_eq!(brain[1], bf16::NEG_INFINITY, "-inf should stay -inf");
+
+    let truncated = source.astype::<bf16>(Rounding::Truncate, Saturation::Saturate);
+    assert_eq!(truncated[1], bf16::NEG_INFINITY, "Truncation should keep -inf");
+}
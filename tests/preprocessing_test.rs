@@ -0,0 +1,202 @@
+/*
+ * numrs/tests/preprocessing_test.rs
+ * Tests for the feature scalers in preprocessing.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test preprocessing_test -- --nocapture
+*/
+
+use numrs::collective::Collective;
+use numrs::dimensions::Dimensions;
+use numrs::preprocessing::{MinMaxScaler, RobustScaler, Scaler, ScalerParameters, StandardScaler};
+use numrs::Axis;
+
+// 4 samples (rows) x 2 features (columns)
+fn samples() -> Collective<f64> {
+    Collective::new(
+        Some(vec![1.0, 10.0, 2.0, 20.0, 3.0, 30.0, 4.0, 40.0].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(2.0, 4.0))),
+    )
+}
+
+fn assert_close(actual: &[f64], expected: &[f64]) {
+    assert_eq!(actual.len(), expected.len(), "Lengths should match");
+
+    for (a, e) in actual.iter().zip(expected.iter()) {
+        assert!((a - e).abs() < 1e-12, "Expected {:?}, got {:?}", expected, actual);
+    }
+}
+
+#[test]
+fn test_standard_scaler_per_column() {
+    let mut scaler = StandardScaler::new();
+    let scaled = scaler.fit_transform(&samples(), Axis::Rows);
+
+    let parameters = scaler.parameters().unwrap();
+    assert_close(&parameters.center, &[2.5, 25.0]);
+    assert_close(&parameters.scale, &[1.25f64.sqrt(), 125.0f64.sqrt()]);
+
+    // Both columns have the same relative spread, so they scale to the same values
+    let data = scaled.data.unwrap();
+    for row in 0..4 {
+        assert!((data[row * 2] - data[row * 2 + 1]).abs() < 1e-12, "Columns should scale identically");
+    }
+
+    let mean = data.iter().step_by(2).sum::<f64>() / 4.0;
+    assert!(mean.abs() < 1e-12, "Scaled column should have zero mean");
+}
+
+#[test]
+fn test_standard_scaler_inverse_transform() {
+    let mut scaler = StandardScaler::new();
+    let scaled = scaler.fit_transform(&samples(), Axis::Rows);
+
+    let restored = scaler.inverse_transform(&scaled);
+
+    assert_close(&restored.data.unwrap(), &samples().data.unwrap());
+}
+
+#[test]
+fn test_standard_scaler_without_mean() {
+    let mut scaler = StandardScaler::new().with_mean(false);
+    scaler.fit(&samples(), Axis::Rows);
+
+    assert_close(&scaler.parameters().unwrap().center, &[0.0, 0.0]);
+}
+
+#[test]
+fn test_min_max_scaler_per_row() {
+    let mut scaler = MinMaxScaler::new();
+    let scaled = scaler.fit_transform(&samples(), Axis::Columns);
+
+    // Every row has exactly two values, its minimum becomes 0 and its maximum 1
+    assert_close(&scaled.data.unwrap(), &[0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0]);
+    assert_eq!(scaler.parameters().unwrap().center.len(), 4, "One set of parameters per row");
+}
+
+#[test]
+fn test_min_max_scaler_feature_range() {
+    let mut scaler = MinMaxScaler::new().with_feature_range(-1.0, 1.0);
+    let scaled = scaler.fit_transform(&samples(), Axis::Rows);
+
+    let data = scaled.data.unwrap();
+    assert_close(&[data[0], data[6], data[1], data[7]], &[-1.0, 1.0, -1.0, 1.0]);
+}
+
+#[test]
+fn test_min_max_scaler_constant_feature() {
+    let constant = Collective::new(
+        Some(vec![5.0f32, 5.0, 5.0].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(1.0, 3.0))),
+    );
+
+    let scaled = MinMaxScaler::new().fit_transform(&constant, Axis::Rows);
+
+    assert_eq!(&scaled.data.unwrap()[..], &[0.0, 0.0, 0.0], "A constant feature should not divide by zero");
+}
+
+#[test]
+fn test_robust_scaler_ignores_outliers() {
+    let data = Collective::new(
+        Some(vec![1.0, 2.0, 3.0, 4.0, 1000.0].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(5.0, 1.0))),
+    );
+
+    let mut scaler = RobustScaler::new();
+    scaler.fit(&data, Axis::None);
+
+    // Median 3, quartiles 2 and 4
+    assert_close(&scaler.parameters().unwrap().center, &[3.0]);
+    assert_close(&scaler.parameters().unwrap().scale, &[2.0]);
+}
+
+#[test]
+fn test_robust_scaler_quantile_range() {
+    let mut scaler = RobustScaler::new().with_centering(false).with_quantile_range(0.0, 100.0);
+    scaler.fit(&samples(), Axis::Rows);
+
+    assert_close(&scaler.parameters().unwrap().center, &[0.0, 0.0]);
+    assert_close(&scaler.parameters().unwrap().scale, &[3.0, 30.0]);
+}
+
+#[test]
+fn test_scaler_save_and_load() {
+    let path = std::env::temp_dir().join(format!("numrs_scaler_{}.txt", std::process::id()));
+
+    let mut scaler = StandardScaler::new();
+    scaler.fit(&samples(), Axis::Rows);
+    scaler.save(&path).unwrap();
+
+    let loaded = StandardScaler::load(&path).unwrap();
+    assert_eq!(loaded.parameters(), scaler.parameters(), "Parameters should round-trip exactly");
+    assert_eq!(
+        loaded.transform(&samples()).data,
+        scaler.transform(&samples()).data,
+        "Loaded scaler should transform identically"
+    );
+
+    let error = MinMaxScaler::load(&path).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "Loading a different kind of scaler should fail");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_scaler_parameters_text_format() {
+    let parameters = ScalerParameters {
+        axis: Axis::Columns,
+        center: vec![0.1, -2.0],
+        scale: vec![1.0 / 3.0, 1e-300],
+    };
+
+    let mut buffer = Vec::new();
+    parameters.write_to("RobustScaler", &mut buffer).unwrap();
+
+    let read = ScalerParameters::read_from("RobustScaler", &buffer[..]).unwrap();
+    assert_eq!(read, parameters, "Text format should round-trip exactly");
+}
+
+#[test]
+#[should_panic(expected = "has not been fitted")]
+fn test_transform_without_fit_panics() {
+    StandardScaler::new().transform(&samples());
+}
+
+#[test]
+#[should_panic(expected = "fitted on 2 features")]
+fn test_transform_feature_mismatch_panics() {
+    let mut scaler = StandardScaler::new();
+    scaler.fit(&samples(), Axis::Rows);
+
+    let other = Collective::new(
+        Some(vec![1.0f64, 2.0, 3.0].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(3.0, 1.0))),
+    );
+    scaler.transform(&other);
+}
+
+#[test]
+#[should_panic(expected = "StandardScaler: Shape [4.0, 2.0] does not match the 7 elements of the data")]
+fn test_fit_shape_mismatch_panics() {
+    let mismatched = Collective::new(
+        Some(vec![1.0f64; 7].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(2.0, 4.0))),
+    );
+
+    StandardScaler::new().fit(&mismatched, Axis::Rows);
+}
+
+#[test]
+#[should_panic(expected = "Scaler::transform(): Shape [4.0, 2.0] does not match the 9 elements of the data")]
+fn test_transform_shape_mismatch_panics() {
+    let mut scaler = MinMaxScaler::new();
+    scaler.fit(&samples(), Axis::Rows);
+
+    let mismatched = Collective::new(
+        Some(vec![1.0f64; 9].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(2.0, 4.0))),
+    );
+    scaler.transform(&mismatched);
+}
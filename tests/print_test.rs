@@ -0,0 +1,133 @@
+/*
+ * numrs/tests/print_test.rs
+ * Tests for printing Collectives in print.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test print_test -- --nocapture
+*/
+
+mod common;
+
+use common::collective;
+use numrs::collective::Collective;
+use numrs::print::{self, PrintOptions};
+
+#[test]
+fn test_nested_brackets() {
+    let matrix = collective(vec![0.0, 1.5, -2.0, 3.0, 4.0, 5.0], vec![2.0, 3.0]);
+    assert_eq!(
+        matrix.to_string_with(&PrintOptions::new()),
+        "[[ 0.   1.5 -2. ]\n [ 3.   4.   5. ]]",
+        "Floats should line up on the decimal point"
+    );
+
+    let cube = collective((0..8).collect(), vec![2.0, 2.0, 2.0]);
+    assert_eq!(
+        cube.to_string_with(&PrintOptions::new()),
+        "[[[0 1]\n  [2 3]]\n\n [[4 5]\n  [6 7]]]",
+        "Blocks of 3-D data should be separated by a blank line"
+    );
+
+    let row = collective(vec![-1i32, 20, 300], vec![1.0, 3.0]);
+    assert_eq!(row.to_string_with(&PrintOptions::new()), "[[ -1  20 300]]", "Integers should be right aligned");
+
+    let empty: Collective<f64> = Collective { data: None, shape: None };
+    assert_eq!(empty.to_string(), "[]", "Unallocated data should print as empty brackets");
+
+    let unshaped = Collective { data: Some(vec![1.0, 2.0, 3.0].into_boxed_slice()), shape: None };
+    assert_eq!(unshaped.to_string_with(&PrintOptions::new()), "[1. 2. 3.]", "Data without a shape should print as one axis");
+    assert_eq!(format!("{:?}", unshaped), "Collective([1., 2., 3.], shape=None)");
+}
+
+#[test]
+fn test_precision_and_suppress() {
+    let values = collective(vec![1.0 / 3.0, 2.0], vec![1.0, 2.0]);
+
+    assert_eq!(values.to_string_with(&PrintOptions::new()), "[[0.33333333 2.        ]]", "Default precision is 8");
+    assert_eq!(
+        values.to_string_with(&PrintOptions::new().with_precision(3)),
+        "[[0.333 2.   ]]",
+        "Precision should limit the fractional digits"
+    );
+    assert_eq!(format!("{:.2}", values), "[[0.33 2.  ]]", "Format precision should override the options");
+
+    let small = collective(vec![1e-6, 1.0], vec![1.0, 2.0]);
+    assert_eq!(
+        small.to_string_with(&PrintOptions::new()),
+        "[[1.e-06 1.e+00]]",
+        "Small values should switch to scientific notation"
+    );
+    assert_eq!(
+        small.to_string_with(&PrintOptions::new().with_suppress(true)),
+        "[[0.000001 1.      ]]",
+        "Suppress should keep fixed notation"
+    );
+
+    let tiny = collective(vec![1e-10, 1.0], vec![1.0, 2.0]);
+    assert_eq!(
+        tiny.to_string_with(&PrintOptions::new().with_suppress(true)),
+        "[[0. 1.]]",
+        "Suppressed values below the precision should round to 0"
+    );
+
+    let large = collective(vec![1.5e10, f64::NAN, -f64::INFINITY], vec![1.0, 3.0]);
+    assert_eq!(
+        large.to_string_with(&PrintOptions::new().with_suppress(true)),
+        "[[1.5e+10     nan    -inf]]",
+        "Large values are scientific even when suppressed, nan and inf are right aligned"
+    );
+}
+
+#[test]
+fn test_summarize_large_collectives() {
+    let long = collective((0..2000).collect::<Vec<i64>>(), vec![1.0, 2000.0]);
+    assert_eq!(
+        long.to_string_with(&PrintOptions::new()),
+        "[[   0    1    2 ... 1997 1998 1999]]",
+        "Axes beyond the threshold should show edge items"
+    );
+
+    let matrix = collective((0..100).collect::<Vec<i32>>(), vec![10.0, 10.0]);
+    let options = PrintOptions::new().with_threshold(10).with_edgeitems(1);
+    assert_eq!(
+        matrix.to_string_with(&options),
+        "[[ 0 ...  9]\n ...\n [90 ... 99]]",
+        "Outer axes should be summarized too"
+    );
+}
+
+#[test]
+fn test_line_wrapping() {
+    let row = collective((0..20).collect::<Vec<i32>>(), vec![1.0, 20.0]);
+    let text = row.to_string_with(&PrintOptions::new().with_linewidth(30));
+
+    assert_eq!(text, "[[ 0  1  2  3  4  5  6  7  8\n   9 10 11 12 13 14 15 16 17\n  18 19]]", "Rows should wrap");
+    assert!(text.lines().all(|line| line.len() <= 30), "No line should exceed the line width");
+}
+
+#[test]
+fn test_global_and_scoped_options() {
+    let values = collective(vec![0.125, 0.5], vec![1.0, 2.0]);
+
+    let scoped = print::with_printoptions(PrintOptions::new().with_precision(1), || {
+        assert_eq!(print::get_printoptions().precision, 1, "Scoped options should be in effect");
+        values.to_string()
+    });
+    assert_eq!(scoped, "[[0.1 0.5]]", "Display should use the scoped options");
+    assert_eq!(values.to_string(), "[[0.125 0.5  ]]", "Scoped options should be restored");
+
+    // Global options, scoped options on this thread take precedence so other tests are not affected
+    print::with_printoptions(PrintOptions::new(), || {
+        print::set_printoptions(PrintOptions::new().with_precision(2));
+        assert_eq!(values.to_string(), "[[0.125 0.5  ]]", "Scoped options should win over global ones");
+    });
+
+    let global = std::thread::spawn(|| collective(vec![0.125, 0.5], vec![1.0, 2.0]).to_string())
+        .join()
+        .unwrap();
+    print::reset_printoptions();
+
+    assert_eq!(global, "[[0.12 0.5 ]]", "Global options should apply to every thread");
+}
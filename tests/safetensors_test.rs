@@ -0,0 +1,195 @@
+/*
+ * numrs/tests/safetensors_test.rs
+ * Tests for reading and writing .safetensors files in safetensors.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --features safetensors --test safetensors_test -- --nocapture
+*/
+
+#![cfg(feature = "safetensors")]
+
+mod common;
+
+use common::temp_path;
+use numrs::collective::Collective;
+use numrs::dimensions::Dimensions;
+use numrs::safetensors::{self, Safetensors, SafetensorsWriter};
+use std::collections::BTreeMap;
+
+// A file laid out by hand: header size, JSON header, data
+fn file_bytes(header: &str, data: &[u8]) -> Vec<u8> {
+    let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+    bytes.extend_from_slice(header.as_bytes());
+    bytes.extend_from_slice(data);
+    bytes
+}
+
+fn weights() -> Collective<f32> {
+    Collective::new(
+        Some((0..12).map(|i| i as f32 - 6.0).collect::<Vec<f32>>().into_boxed_slice()),
+        Some(Box::new(Dimensions::new(3.0, 4.0))),
+    )
+}
+
+#[test]
+fn test_read_reference_layout() {
+    let data: Vec<u8> = [1.0f32, 2.0, 3.0].iter().flat_map(|v| v.to_le_bytes()).chain([7u8, 9]).collect();
+    let header = r#"{"b":{"dtype":"U8","shape":[2],"data_offsets":[12,14]},"a":{"dtype":"F32","shape":[3],"data_offsets":[0,12]},"__metadata__":{"format":"pt"}}"#;
+
+    let file = Safetensors::from_bytes(file_bytes(header, &data)).unwrap();
+
+    assert_eq!(file.names(), vec!["a", "b"], "Names should be in the order of their data");
+    assert_eq!(file.metadata()["format"], "pt", "Metadata should be read");
+    assert_eq!(file.info("b").unwrap().data_offsets, (12, 14), "Offsets should be read");
+
+    let a: Collective<f32> = file.load("a").unwrap();
+    assert_eq!(&a.data.unwrap()[..], &[1.0, 2.0, 3.0], "F32 tensor should load");
+    assert_eq!(a.shape.unwrap().columns(), 3.0, "1-D tensors should load as one row");
+
+    let b: Collective<u8> = file.load("b").unwrap();
+    assert_eq!(&b.data.unwrap()[..], &[7, 9], "U8 tensor should load");
+}
+
+#[test]
+fn test_save_and_load_round_trip() {
+    let path = temp_path("round_trip.safetensors");
+
+    let mut collectives = BTreeMap::new();
+    collectives.insert("dense.weight".to_string(), weights());
+    collectives.insert(
+        "dense.bias".to_string(),
+        Collective::new(Some(vec![0.5f32; 3].into_boxed_slice()), Some(Box::new(Dimensions::new(3.0, 1.0)))),
+    );
+
+    safetensors::save_safetensors(&path, &collectives).unwrap();
+    let loaded = safetensors::load_safetensors::<f32, _>(&path).unwrap();
+
+    for (name, collective) in &collectives {
+        assert_eq!(loaded[name].data, collective.data, "{} should round-trip", name);
+        assert_eq!(
+            loaded[name].shape.as_ref().unwrap().to_vec(),
+            collective.shape.as_ref().unwrap().to_vec(),
+            "{} shape should round-trip",
+            name
+        );
+    }
+
+    let header_size = u64::from_le_bytes(std::fs::read(&path).unwrap()[..8].try_into().unwrap());
+    assert_eq!(header_size % 8, 0, "Header should be padded to 8 bytes");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_zero_copy_view() {
+    let path = temp_path("view.safetensors");
+
+    let labels = Collective::new(Some(vec![1i64, 2, 3].into_boxed_slice()), Some(Box::new(Dimensions::new(3.0, 1.0))));
+
+    let mut writer = SafetensorsWriter::new().with_metadata("epoch", "3");
+    writer.add("weight", &weights()).unwrap();
+    writer.add("labels", &labels).unwrap();
+    writer.save(&path).unwrap();
+
+    let file = Safetensors::open(&path).unwrap();
+    assert_eq!(file.metadata()["epoch"], "3", "Metadata should round-trip");
+
+    let view = file.view::<f32>("weight").unwrap();
+    assert_eq!(view.data, &weights().data.unwrap()[..], "View should see the saved elements");
+    assert_eq!(view.shape.to_vec(), vec![4.0, 3.0], "View should carry the shape");
+    assert_eq!(view.to_collective().data, weights().data, "View should copy into a Collective");
+
+    let view = file.view::<i64>("labels").unwrap();
+    assert_eq!(view.data, &[1, 2, 3], "8 byte elements should be aligned");
+
+    let error = file.view::<f64>("weight").err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "A different dtype should be rejected");
+
+    let error = file.load::<f32>("missing").err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound, "Missing tensors should be NotFound");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_invalid_offsets_are_rejected() {
+    let data = [0u8; 16];
+
+    let cases = [
+        (r#"{"a":{"dtype":"F32","shape":[2],"data_offsets":[4,12]}}"#, "gap before the first tensor"),
+        (r#"{"a":{"dtype":"F32","shape":[2],"data_offsets":[0,8]},"b":{"dtype":"F32","shape":[2],"data_offsets":[4,12]}}"#, "overlap"),
+        (r#"{"a":{"dtype":"F32","shape":[3],"data_offsets":[0,8]}}"#, "length does not match the shape"),
+        (r#"{"a":{"dtype":"F32","shape":[2],"data_offsets":[0,8]}}"#, "data not fully covered"),
+        (r#"{"a":{"dtype":"F32","shape":[4],"data_offsets":[0,16]},"b":{"dtype":"F32","shape":[1],"data_offsets":[16,20]}}"#, "past the end"),
+        (r#"{"a":{"dtype":"C64","shape":[2],"data_offsets":[0,16]}}"#, "unknown dtype"),
+        (r#"{"a":{"dtype":"F32","shape":[-4],"data_offsets":[0,16]}}"#, "negative extent"),
+        (r#"{"__metadata__":{"epoch":3},"a":{"dtype":"F32","shape":[4],"data_offsets":[0,16]}}"#, "non-string metadata"),
+        (r#"[1, 2, 3]"#, "header is not an object"),
+    ];
+
+    for (header, reason) in cases {
+        let error = Safetensors::from_bytes(file_bytes(header, &data)).err();
+        assert!(error.is_some(), "Header should be rejected: {}", reason);
+        assert_eq!(error.unwrap().kind(), std::io::ErrorKind::InvalidData, "{} should be invalid data", reason);
+    }
+
+    let mut huge = file_bytes("{}", &[]);
+    huge[..8].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert!(Safetensors::from_bytes(huge).is_err(), "A header size beyond the file should be rejected");
+}
+
+#[test]
+fn test_unsupported_dtype_is_validated_but_not_loadable() {
+    let header = r#"{"mask":{"dtype":"BOOL","shape":[2,2],"data_offsets":[0,4]}}"#;
+
+    let file = Safetensors::from_bytes(file_bytes(header, &[1, 0, 0, 1])).unwrap();
+
+    assert_eq!(file.info("mask").unwrap().dtype, "BOOL", "BOOL tensors should be listed");
+    assert!(file.load::<u8>("mask").is_err(), "BOOL tensors cannot be loaded as U8");
+}
+
+#[test]
+fn test_writer_rejects_duplicate_names() {
+    let mut writer = SafetensorsWriter::new();
+    writer.add("w", &weights()).unwrap();
+
+    let error = writer.add("w", &weights()).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput, "Duplicate names should be rejected");
+
+    let error = writer.add("__metadata__", &weights()).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput, "The metadata key is reserved");
+}
+
+#[test]
+fn test_writer_rejects_shape_that_does_not_match_data() {
+    let mismatched = Collective::new(weights().data, Some(Box::new(Dimensions::new(5.0, 2.0))));
+
+    let mut writer = SafetensorsWriter::new();
+    let error = writer.add("w", &mismatched).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput, "A shape that does not match the data should be rejected");
+
+    // Nothing was added, so the name is still free
+    writer.add("w", &weights()).unwrap();
+}
+
+#[cfg(feature = "bf16")]
+#[test]
+fn test_bf16_tensors() {
+    use numrs::bf16;
+
+    let collective = Collective::new(
+        Some(vec![bf16::from_f32(1.5), bf16::from_f32(-0.25)].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(2.0, 1.0))),
+    );
+
+    let mut writer = SafetensorsWriter::new();
+    writer.add("scale", &collective).unwrap();
+    let mut bytes = Vec::new();
+    writer.write_to(&mut bytes).unwrap();
+
+    let file = Safetensors::from_bytes(bytes).unwrap();
+    assert_eq!(file.info("scale").unwrap().dtype, "BF16", "bf16 should be saved as BF16");
+    assert_eq!(file.load::<bf16>("scale").unwrap().data, collective.data, "bf16 should round-trip");
+}
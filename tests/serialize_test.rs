@@ -0,0 +1,99 @@
+/*
+ * numrs/tests/serialize_test.rs
+ * Tests for the serde support in serialize.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --features serde --test serialize_test -- --nocapture
+*/
+
+#![cfg(feature = "serde")]
+
+use numrs::collective::Collective;
+use numrs::dimensions::Dimensions;
+use numrs::num::Dtype;
+
+#[test]
+fn test_dimensions_serialize_as_extents() {
+    let dimensions = Dimensions::from_vec(vec![60000.0, 28.0, 28.0]);
+
+    let json = serde_json::to_string(&dimensions).unwrap();
+    assert_eq!(json, "[60000.0,28.0,28.0]", "Dimensions should serialize as to_vec()");
+
+    let back: Dimensions = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.to_vec(), dimensions.to_vec(), "Dimensions should round-trip");
+    assert_eq!(back.get_n(), 60000 * 28 * 28, "The chain should be rebuilt");
+}
+
+#[test]
+fn test_invalid_dimensions_are_rejected() {
+    for json in ["[5.0]", "[0.0, 3.0]", "[2.5, 3.0]", "[-1.0, 3.0]"] {
+        assert!(serde_json::from_str::<Dimensions>(json).is_err(), "{} should not deserialize", json);
+    }
+}
+
+#[test]
+fn test_collective_json_round_trip() {
+    let collective = Collective::new(
+        Some(vec![1.5f32, -2.0, 0.25, 4.0, 5.0, 6.0].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(3.0, 2.0))),
+    );
+
+    let json = serde_json::to_string(&collective).unwrap();
+    assert_eq!(
+        json,
+        r#"{"shape":[2.0,3.0],"dtype":"f32","data":[1.5,-2.0,0.25,4.0,5.0,6.0]}"#,
+        "Collective should serialize as shape, dtype and flat data"
+    );
+
+    let back: Collective<f32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.data, collective.data, "Data should round-trip");
+    assert_eq!(back.shape.unwrap().to_vec(), vec![2.0, 3.0], "Shape should round-trip");
+}
+
+#[test]
+fn test_unallocated_collective_round_trip() {
+    let collective: Collective<u8> = Collective::new(None, Some(Box::new(Dimensions::new(2.0, 2.0))));
+
+    let json = serde_json::to_string(&collective).unwrap();
+    assert!(json.contains(r#""data":null"#), "Unallocated data should be null, got {}", json);
+
+    let back: Collective<u8> = serde_json::from_str(&json).unwrap();
+    assert!(back.data.is_none(), "Data should stay unallocated");
+}
+
+#[test]
+fn test_collective_validation() {
+    let wrong_length = r#"{"shape":[2.0,2.0],"dtype":"f64","data":[1.0,2.0,3.0]}"#;
+    let error = serde_json::from_str::<Collective<f64>>(wrong_length).err().unwrap();
+    assert!(error.to_string().contains("3 elements"), "Length mismatch should be reported, got {}", error);
+
+    let wrong_dtype = r#"{"shape":[1.0,2.0],"dtype":"f32","data":[1.0,2.0]}"#;
+    let error = serde_json::from_str::<Collective<f64>>(wrong_dtype).err().unwrap();
+    assert!(error.to_string().contains("'f32'"), "dtype mismatch should be reported, got {}", error);
+}
+
+#[test]
+fn test_dtype_names() {
+    assert_eq!(<u8 as Dtype>::NAME, "u8", "u8 name");
+    assert_eq!(<i64 as Dtype>::NAME, "i64", "i64 name");
+    assert_eq!(<f64 as Dtype>::NAME, "f64", "f64 name");
+}
+
+#[cfg(feature = "bf16")]
+#[test]
+fn test_bf16_collective_round_trip() {
+    use numrs::bf16;
+
+    let collective = Collective::new(
+        Some(vec![bf16::from_f32(0.5), bf16::from_f32(-3.0)].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(2.0, 1.0))),
+    );
+
+    let json = serde_json::to_string(&collective).unwrap();
+    assert!(json.contains(r#""dtype":"bf16""#), "bf16 dtype should be named, got {}", json);
+
+    let back: Collective<bf16> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.data, collective.data, "bf16 data should round-trip");
+}
@@ -0,0 +1,152 @@
+/*
+ * numrs/tests/softmax_test.rs
+ * Tests for softmax, log_softmax and logsumexp in softmax.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test softmax_test -- --nocapture
+*/
+
+mod common;
+
+use common::collective;
+use numrs::approx::CloseOptions;
+use numrs::assert_allclose;
+use numrs::collective::Collective;
+use numrs::dimensions::Dimensions;
+use numrs::num::Float;
+
+// 2 x 3 x 4 logits of moderate size, where the naive formulas are accurate
+fn logits<E: Float>() -> Collective<E> {
+    collective((0..24).map(|i| E::from_f64(((i * 7) % 11) as f64 * 0.37 - 1.5)).collect(), vec![2.0, 3.0, 4.0])
+}
+
+/*
+   The textbook formulas, lane by lane in E: sum the exponentials along the axis and divide.
+*/
+fn naive_logsumexp<E: Float>(x: &Collective<E>, axis: usize) -> Collective<E> {
+    x.exp().fold(Some(axis), E::zero(), |sum, e| sum + e).ln()
+}
+
+fn naive_softmax<E: Float>(x: &Collective<E>, axis: usize) -> Collective<E> {
+    let sums = x.exp().fold(Some(axis), E::zero(), |sum, e| sum + e);
+    let exps = x.exp();
+
+    // Put the removed axis back as an extent of 1 so the sums broadcast
+    let mut extents: Vec<f64> = x.shape.as_ref().unwrap().to_vec();
+    extents[axis] = 1.0;
+    let sums = Collective::new(sums.data, Some(Box::new(Dimensions::from_vec(extents))));
+
+    exps.zip_with(&sums, |e, sum| e / sum)
+}
+
+fn check_against_naive<E: Float>(options: CloseOptions) {
+    let x = logits::<E>();
+
+    for axis in 0..3 {
+        assert_allclose!(x.softmax(axis, None), naive_softmax(&x, axis), options);
+        assert_allclose!(x.log_softmax(axis, None), naive_softmax(&x, axis).ln(), options);
+        assert_allclose!(x.logsumexp(axis, None), naive_logsumexp(&x, axis), options);
+    }
+}
+
+#[test]
+fn test_matches_naive_f64() {
+    check_against_naive::<f64>(CloseOptions::new().with_rtol(1e-12).with_atol(1e-12));
+}
+
+#[test]
+fn test_matches_naive_f32() {
+    check_against_naive::<f32>(CloseOptions::new().with_rtol(1e-5).with_atol(1e-6));
+}
+
+#[test]
+fn test_shapes_and_normalization() {
+    let x = logits::<f64>();
+
+    let probabilities = x.softmax(2, None);
+    assert_eq!(probabilities.shape.as_ref().unwrap().to_vec(), vec![2.0, 3.0, 4.0], "softmax should keep the shape");
+    assert_allclose!(probabilities.fold(Some(2), 0.0, |sum, p| sum + p), collective(vec![1.0; 6], vec![2.0, 3.0]));
+
+    let logsumexp = x.logsumexp(1, None);
+    assert_eq!(logsumexp.shape.as_ref().unwrap().to_vec(), vec![2.0, 4.0], "logsumexp should remove the axis");
+}
+
+#[test]
+fn test_large_logits_do_not_overflow() {
+    let x = collective(vec![1000.0f32, 1001.0, 1002.0, -1000.0, 0.0, 1000.0], vec![2.0, 3.0]);
+
+    let probabilities = x.softmax(1, None);
+    assert!(probabilities.iter().all(|p| p.is_finite()), "softmax of large logits should be finite, got {:?}", probabilities);
+
+    // The same as the small logits [0, 1, 2], shifting the logits does not change the softmax
+    let shifted = collective(vec![0.0f32, 1.0, 2.0], vec![1.0, 3.0]);
+    assert_allclose!(
+        collective(probabilities.iter().take(3).copied().collect(), vec![1.0, 3.0]),
+        naive_softmax(&shifted, 1)
+    );
+    assert_eq!(probabilities[3], 0.0, "e^-2000 should underflow to 0");
+    assert_eq!(probabilities[4], 0.0, "e^-1000 should underflow to 0");
+    assert_eq!(probabilities[5], 1.0, "The largest logit should take all of the probability");
+
+    let logsumexp = x.logsumexp(1, None);
+    assert_allclose!(logsumexp, collective(vec![1002.0 + (1.0f32 + (-1.0f32).exp() + (-2.0f32).exp()).ln(), 1000.0], vec![1.0, 2.0]));
+
+    let log_probabilities = x.log_softmax(1, None);
+    assert_eq!(log_probabilities[3], -2000.0, "log_softmax should stay finite where softmax underflows");
+}
+
+#[test]
+fn test_mask_excludes_positions() {
+    // Two sequences padded to 4, of lengths 2 and 3
+    let x = collective(vec![1.0f64, 2.0, 9.0, 9.0, 0.5, 0.5, 0.5, 9.0], vec![2.0, 4.0]);
+    let mask = collective(vec![true, true, false, false, true, true, true, false], vec![2.0, 4.0]);
+
+    let probabilities = x.softmax(1, Some(&mask));
+    let expected = collective(vec![1.0f64, 2.0], vec![1.0, 2.0]).softmax(1, None);
+    assert_allclose!(collective(probabilities.iter().take(2).copied().collect(), vec![1.0, 2.0]), expected);
+    assert_eq!(&probabilities.data.as_ref().unwrap()[2..4], &[0.0, 0.0], "Masked positions should get probability 0");
+    assert_allclose!(
+        collective(probabilities.iter().skip(4).copied().collect(), vec![1.0, 4.0]),
+        collective(vec![1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0, 0.0], vec![1.0, 4.0])
+    );
+
+    let log_probabilities = x.log_softmax(1, Some(&mask));
+    assert_eq!(log_probabilities[7], f64::NEG_INFINITY, "Masked positions should get log-probability -infinity");
+
+    let logsumexp = x.logsumexp(1, Some(&mask));
+    assert_allclose!(logsumexp, collective(vec![(1.0f64.exp() + 2.0f64.exp()).ln(), 0.5 + 3.0f64.ln()], vec![1.0, 2.0]));
+}
+
+#[test]
+fn test_broadcast_and_fully_masked_lanes() {
+    let x = collective(vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0], vec![2.0, 3.0]);
+
+    // One mask row for every row, the last position is padding
+    let keys = collective(vec![true, true, false], vec![1.0, 3.0]);
+    let probabilities = x.softmax(1, Some(&keys));
+    assert_eq!(probabilities[2], 0.0, "The broadcast mask should apply to the first row");
+    assert_eq!(probabilities[5], 0.0, "The broadcast mask should apply to the second row");
+
+    let rows = collective(vec![false, true], vec![2.0, 1.0]);
+    let probabilities = x.softmax(1, Some(&rows));
+    assert_eq!(&probabilities.data.as_ref().unwrap()[..3], &[0.0, 0.0, 0.0], "A fully masked lane should be all 0");
+    assert_eq!(x.logsumexp(1, Some(&rows))[0], f32::NEG_INFINITY, "A fully masked lane should have logsumexp -infinity");
+    assert_eq!(x.log_softmax(1, Some(&rows))[0], f32::NEG_INFINITY, "A fully masked lane should have log-probabilities -infinity");
+}
+
+#[test]
+#[should_panic(expected = "Collective::softmax(): Mask of shape [2, 2] cannot be broadcast to [2, 3]")]
+fn test_mask_shape_mismatch_panics() {
+    let x = collective(vec![1.0f64; 6], vec![2.0, 3.0]);
+    let mask = collective(vec![true; 4], vec![2.0, 2.0]);
+
+    x.softmax(1, Some(&mask));
+}
+
+#[test]
+#[should_panic(expected = "Collective::logsumexp(): Axis 2 is out of range")]
+fn test_axis_out_of_range_panics() {
+    collective(vec![1.0f64; 6], vec![2.0, 3.0]).logsumexp(2, None);
+}
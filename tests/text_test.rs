@@ -0,0 +1,183 @@
+/*
+ * numrs/tests/text_test.rs
+ * Tests for delimited text import and export in text.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test text_test -- --nocapture
+*/
+
+use numrs::collective::Collective;
+use numrs::dimensions::Dimensions;
+use numrs::num::Tensor;
+use numrs::text::{TextDtype, TextOptions};
+
+// Formats a single value through savetxt
+fn formatted(format: &str, value: f64) -> String {
+    let collective = Collective::new(
+        Some(vec![value].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(1.0, 1.0))),
+    );
+
+    let mut buffer = Vec::new();
+    Tensor::savetxt_to(&mut buffer, &collective, &TextOptions::new().with_format(format)).unwrap();
+
+    String::from_utf8(buffer).unwrap().trim_end_matches('\n').to_string()
+}
+
+#[test]
+fn test_loadtxt_whitespace_separated() {
+    let text = "# x y z\n1 2 3\n\n4.5   5 6  # trailing comment\n";
+
+    let data: Collective<f64> = Tensor::loadtxt_from(text.as_bytes(), &TextOptions::new()).unwrap();
+
+    let shape = data.shape.as_ref().unwrap();
+    assert_eq!(shape.rows(), 2.0, "Blank and comment lines should be skipped");
+    assert_eq!(shape.columns(), 3.0, "Three columns");
+    assert_eq!(&data.data.unwrap()[..], &[1.0, 2.0, 3.0, 4.5, 5.0, 6.0], "Values should load in row order");
+}
+
+#[test]
+fn test_loadtxt_csv_with_header_and_columns() {
+    let text = "sepal_length,sepal_width,species\n5.1,3.5,0\n4.9,3.0,0\n6.3,3.3,2\n";
+    let options = TextOptions::new()
+        .with_delimiter(',')
+        .with_skip_header(1)
+        .with_columns(vec![2, 0]);
+
+    let data: Collective<f32> = Tensor::loadtxt_from(text.as_bytes(), &options).unwrap();
+
+    assert_eq!(data.shape.as_ref().unwrap().to_vec(), vec![3.0, 2.0], "3 rows of the 2 selected columns");
+    assert_eq!(&data.data.unwrap()[..], &[0.0, 5.1, 0.0, 4.9, 2.0, 6.3], "Columns should follow the selection order");
+}
+
+#[test]
+fn test_loadtxt_rejects_missing_and_ragged_rows() {
+    let options = TextOptions::new().with_delimiter(',');
+
+    let error = Tensor::loadtxt_from::<f64, _>("1,2\n3,\n".as_bytes(), &options).err().unwrap();
+    assert!(error.to_string().contains("line 2, column 1"), "Missing value should be located, got {}", error);
+
+    let error = Tensor::loadtxt_from::<f64, _>("1,2\n3,4,5\n".as_bytes(), &options).err().unwrap();
+    assert!(error.to_string().contains("line 2 has 3 columns"), "Ragged rows should be reported, got {}", error);
+
+    let error = Tensor::loadtxt_from::<f64, _>("# only a comment\n".as_bytes(), &options).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData, "A file without data should be an error");
+}
+
+#[test]
+fn test_genfromtxt_fills_missing_values() {
+    let text = "1,NA,3\n4,5,\n7,8,9\n";
+    let options = TextOptions::new()
+        .with_delimiter(',')
+        .with_missing_values(vec!["NA"])
+        .with_filling_value(-1.0)
+        .with_column_filling_value(2, 0.0);
+
+    let data: Collective<f64> = Tensor::genfromtxt_from(text.as_bytes(), &options).unwrap();
+
+    assert_eq!(
+        &data.data.unwrap()[..],
+        &[1.0, -1.0, 3.0, 4.0, 5.0, 0.0, 7.0, 8.0, 9.0],
+        "Missing fields should get the (per-column) filling value"
+    );
+
+    let data: Collective<f64> = Tensor::genfromtxt_from("1,\n".as_bytes(), &TextOptions::new().with_delimiter(',')).unwrap();
+    assert!(data[1].is_nan(), "The default filling value should be NaN");
+}
+
+#[test]
+fn test_column_dtypes() {
+    let text = "3\ttrue\t0.5\n-2\tNo\t1e3\n";
+    let options = TextOptions::new()
+        .with_delimiter('\t')
+        .with_column_dtype(0, TextDtype::Integer)
+        .with_column_dtype(1, TextDtype::Boolean);
+
+    let data: Collective<f64> = Tensor::loadtxt_from(text.as_bytes(), &options).unwrap();
+    assert_eq!(&data.data.unwrap()[..], &[3.0, 1.0, 0.5, -2.0, 0.0, 1000.0], "Each column should parse by its dtype");
+
+    let error = Tensor::loadtxt_from::<f64, _>("1.5\ttrue\t0\n".as_bytes(), &options).err().unwrap();
+    assert!(error.to_string().contains("Integer"), "1.5 is not an integer, got {}", error);
+}
+
+#[test]
+fn test_integer_element_type_and_skip_footer() {
+    let text = "1 2\n3 4\ntotal 10\n";
+    let options = TextOptions::new().with_skip_footer(1);
+
+    let data: Collective<u8> = Tensor::loadtxt_from(text.as_bytes(), &options).unwrap();
+
+    assert_eq!(&data.data.unwrap()[..], &[1, 2, 3, 4], "Footer line should be skipped");
+}
+
+#[test]
+fn test_savetxt_round_trip() {
+    let path = std::env::temp_dir().join(format!("numrs_text_{}.csv", std::process::id()));
+
+    let data = Collective::new(
+        Some(vec![0.1, -2.5, 1.0 / 3.0, 1e-20, 42.0, f64::MAX].into_boxed_slice()),
+        Some(Box::new(Dimensions::new(3.0, 2.0))),
+    );
+    let options = TextOptions::new().with_delimiter(',').with_header("a,b,c");
+
+    Tensor::savetxt(&path, &data, &options).unwrap();
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.starts_with("# a,b,c\n0.1,-2.5,"), "Header should be commented, got {}", text);
+
+    let loaded: Collective<f64> = Tensor::loadtxt(&path, &options).unwrap();
+    assert_eq!(loaded.data, data.data, "Default formatting should round-trip exactly");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_savetxt_higher_dimensions_and_integers() {
+    let data = Collective::new(
+        Some((1..=8).collect::<Vec<i32>>().into_boxed_slice()),
+        Some(Box::new(Dimensions::from_vec(vec![2.0, 2.0, 2.0]))),
+    );
+
+    let mut buffer = Vec::new();
+    Tensor::savetxt_to(&mut buffer, &data, &TextOptions::new().with_footer("end")).unwrap();
+
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "1 2\n3 4\n5 6\n7 8\n# end\n",
+        "3-D data should be written as rows() lines of columns() values"
+    );
+}
+
+#[test]
+fn test_float_formats_match_printf() {
+    // Expected values are Python's `format % value`
+    let cases = [
+        ("%.3e", 12345.678, "1.235e+04"),
+        ("%g", 0.0001, "0.0001"),
+        ("%g", 1e-5, "1e-05"),
+        ("%g", 123456789.0, "1.23457e+08"),
+        ("%g", 100000.0, "100000"),
+        ("%g", 1000000.0, "1e+06"),
+        ("%08.3f", -1.23456, "-001.235"),
+        ("%-8.2f|", 2.5, "2.50    |"),
+        ("%+d", 3.7, "+3"),
+        ("%d", -0.5, "0"),
+        ("%.2f", -0.0, "-0.00"),
+        ("%G", 1e-10, "1E-10"),
+        ("%10.4g", f64::NAN, "       nan"),
+        ("%E", f64::NEG_INFINITY, "-INF"),
+        ("x=%.1f;", 0.25, "x=0.2;"),
+    ];
+
+    for (format, value, expected) in cases {
+        assert_eq!(formatted(format, value), expected, "{} of {}", format, value);
+    }
+}
+
+#[test]
+#[should_panic(expected = "unsupported conversion")]
+fn test_invalid_format_panics() {
+    TextOptions::new().with_format("%s");
+}
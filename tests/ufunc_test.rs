@@ -0,0 +1,141 @@
+/*
+ * numrs/tests/ufunc_test.rs
+ * Tests for element-wise math functions in ufunc.rs
+ * Q@khaa.pk
+ */
+
+/*
+    cargo test --test ufunc_test -- --nocapture
+*/
+
+mod common;
+
+use common::{collective, values};
+use numrs::assert_allclose;
+use numrs::collective::Collective;
+
+fn row<E: Default + Copy>(data: Vec<E>) -> Collective<E> {
+    let n = data.len() as f64;
+
+    collective(data, vec![1.0, n])
+}
+
+#[test]
+fn test_exponentials_and_logarithms() {
+    let x = row(vec![0.5f64, 1.0, 2.0, 8.0]);
+
+    assert_allclose!(x.exp(), row(vec![0.5f64.exp(), 1.0f64.exp(), 2.0f64.exp(), 8.0f64.exp()]));
+    assert_allclose!(x.exp().ln(), x);
+    assert_allclose!(x.log2(), row(vec![-1.0, 0.0, 1.0, 3.0]));
+    assert_allclose!(row(vec![0.001f64, 100.0]).log10(), row(vec![-3.0, 2.0]));
+    assert_allclose!(x.sqrt().pow(2.0), x);
+    assert_allclose!(x.cbrt(), row(vec![0.5f64.cbrt(), 1.0, 2.0f64.cbrt(), 2.0]));
+
+    // log1p and expm1 keep their precision where 1 + x would round to 1
+    let tiny = row(vec![1e-17f64]);
+    assert_eq!(values(&tiny.log1p()), vec![1e-17], "log1p should be accurate near zero");
+    assert_eq!(values(&tiny.expm1()), vec![1e-17], "expm1 should be accurate near zero");
+
+    assert!(row(vec![-1.0f32]).ln()[0].is_nan(), "ln outside its domain should be NaN");
+}
+
+#[test]
+fn test_trigonometric_and_hyperbolic() {
+    let x = row(vec![-0.75f64, -0.25, 0.0, 0.5]);
+
+    assert_allclose!(x.sin().arcsin(), x);
+    assert_allclose!(x.cos().arccos(), x.abs());
+    assert_allclose!(x.tan().arctan(), x);
+    assert_allclose!(x.sinh().arcsinh(), x);
+    assert_allclose!(x.tanh().arctanh(), x);
+    assert_allclose!(x.cosh().arccosh(), x.abs());
+    assert_allclose!(x.sin().pow(2.0).zip_with(&x.cos().pow(2.0), |a, b| a + b), row(vec![1.0; 4]));
+
+    let mut y = row(vec![0.0f32, 1.0]);
+    y.sinh_inplace();
+    assert_allclose!(y, row(vec![0.0f32, 1.0f32.sinh()]));
+}
+
+#[test]
+fn test_rounding_sign_and_abs() {
+    let x = row(vec![-2.5f64, -0.4, 0.0, 0.5, 1.7]);
+
+    assert_eq!(values(&x.floor()), vec![-3.0, -1.0, 0.0, 0.0, 1.0], "floor");
+    assert_eq!(values(&x.ceil()), vec![-2.0, -0.0, 0.0, 1.0, 2.0], "ceil");
+    assert_eq!(values(&x.round()), vec![-2.0, -0.0, 0.0, 0.0, 2.0], "round should round halves to even");
+    assert_eq!(values(&row(vec![1.5f64, 2.5, -3.5]).round()), vec![2.0, 2.0, -4.0], "round should round halves to even");
+    assert_eq!(values(&x.trunc()), vec![-2.0, -0.0, 0.0, 0.0, 1.0], "trunc");
+    assert_eq!(values(&x.abs()), vec![2.5, 0.4, 0.0, 0.5, 1.7], "abs");
+    assert_eq!(values(&x.sign()), vec![-1.0, -1.0, 0.0, 1.0, 1.0], "sign of zero should be zero");
+    assert!(row(vec![f64::NAN]).sign()[0].is_nan(), "sign of NaN should be NaN");
+}
+
+#[test]
+fn test_inplace_forms_match_out_of_place() {
+    let x = collective(vec![0.1f32, 0.2, 0.3, 0.4, 0.5, 0.6], vec![2.0, 3.0]);
+
+    let mut y = x.clone();
+    y.exp_inplace();
+    y.log10_inplace();
+    y.pow_inplace(3.0);
+    y.arctan_inplace();
+
+    assert_eq!(y, x.exp().log10().pow(3.0).arctan(), "In-place and out-of-place forms should agree");
+    assert_eq!(y.shape.as_ref().unwrap().to_vec(), vec![2.0, 3.0], "Shape should be kept");
+}
+
+#[test]
+fn test_clip_maximum_minimum() {
+    let x = collective(vec![-2.0f64, 0.5, 3.0, f64::NAN], vec![2.0, 2.0]);
+
+    let clipped = x.clip(-1.0, 1.0);
+    assert_eq!(values(&clipped)[..3], [-1.0, 0.5, 1.0], "clip should limit to the range");
+    assert!(clipped[3].is_nan(), "clip should keep NaN");
+
+    let threshold = row(vec![0.0f64, 1.0]);
+    let relu = x.maximum(&threshold);
+    assert_eq!(values(&relu)[..3], [0.0, 1.0, 3.0], "maximum should broadcast a row");
+    assert!(relu[3].is_nan(), "maximum should propagate NaN");
+
+    let mut y = x.clone();
+    y.minimum_inplace(&threshold);
+    assert_eq!(values(&y)[..3], [-2.0, 0.5, 0.0], "minimum_inplace should broadcast a row");
+
+    // In place means in the same buffer
+    let mut w = x.clone();
+    let before = w.data.as_ref().unwrap().as_ptr();
+    w.maximum_inplace(&threshold);
+    w.minimum_inplace(&x);
+    assert_eq!(w.data.as_ref().unwrap().as_ptr(), before, "maximum_inplace and minimum_inplace should not reallocate");
+    assert_eq!(values(&w)[..3], [-2.0, 0.5, 3.0], "Combining should write the results into the buffer");
+
+    let mut z = x.clone();
+    z.clip_inplace(0.0, 0.0);
+    assert_eq!(values(&z)[..3], [0.0, 0.0, 0.0], "An empty range should clip to that value");
+}
+
+#[test]
+#[should_panic(expected = "cannot be broadcast to")]
+fn test_maximum_inplace_cannot_grow() {
+    let mut x = row(vec![1.0f64, 2.0]);
+    let y = collective(vec![0.0f64; 4], vec![2.0, 2.0]);
+
+    x.maximum_inplace(&y);
+}
+
+#[test]
+#[should_panic(expected = "min 1 is greater than max 0")]
+fn test_clip_invalid_range_panics() {
+    row(vec![1.0f64]).clip(1.0, 0.0);
+}
+
+#[cfg(feature = "bf16")]
+#[test]
+fn test_bf16_ufuncs() {
+    use numrs::bf16;
+
+    let x = row(vec![bf16::from_f32(4.0), bf16::from_f32(0.25)]);
+
+    assert_eq!(values(&x.sqrt()), vec![bf16::from_f32(2.0), bf16::from_f32(0.5)], "bf16 sqrt should compute in f32");
+    assert_eq!(values(&x.log2()), vec![bf16::from_f32(2.0), bf16::from_f32(-2.0)], "bf16 log2 should compute in f32");
+}